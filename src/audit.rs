@@ -0,0 +1,114 @@
+// 改ざん検知付きの監査ログ（状態ディレクトリの audit.log に JSON Lines で追記）
+//
+// 各行は直前の行のハッシュ（prev）を含み、hash = SHA-256(prev と本文) で連鎖させる。
+// 途中の行の書き換え・削除は連鎖の不一致で、末尾の削除は audit.head（件数と最終ハッシュ）との不一致で検出する。
+// シークレットや URL は記録しない
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+pub const AUDIT_FILE: &str = "audit.log";
+const HEAD_FILE: &str = "audit.head";
+const LOCK_FILE: &str = "audit.lock";
+const GENESIS: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub ts: String,
+    pub op: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    pub prev: String,
+    pub hash: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Head {
+    count: usize,
+    hash: String,
+}
+
+pub fn log_path(state_dir: &Path) -> PathBuf {
+    state_dir.join(AUDIT_FILE)
+}
+
+fn entry_hash(e: &Entry) -> String {
+    let body = serde_json::json!({
+        "ts": e.ts,
+        "op": e.op,
+        "id": e.id,
+        "ok": e.ok,
+        "detail": e.detail,
+        "prev": e.prev,
+    });
+    Sha256::digest(body.to_string().as_bytes()).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn read_head(state_dir: &Path) -> Option<Head> {
+    fs::read_to_string(state_dir.join(HEAD_FILE)).ok().and_then(|s| serde_json::from_str(&s).ok())
+}
+
+pub fn record(state_dir: &Path, op: &str, id: Option<&str>, ok: bool, detail: Option<&str>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    fs::create_dir_all(state_dir)?;
+    // 複数のプロセスが同時に記録しても連鎖が分岐しないよう、head の読み込みから更新までを排他する（lock は drop で解除）
+    let lock = fs::OpenOptions::new().create(true).truncate(false).write(true).open(state_dir.join(LOCK_FILE))?;
+    lock.lock()?;
+    let head = read_head(state_dir).unwrap_or(Head { count: 0, hash: GENESIS.to_string() });
+    let mut entry = Entry {
+        ts: Utc::now().to_rfc3339(),
+        op: op.to_string(),
+        id: id.map(str::to_string),
+        ok,
+        detail: detail.map(str::to_string),
+        prev: head.hash,
+        hash: String::new(),
+    };
+    entry.hash = entry_hash(&entry);
+    let mut f = fs::OpenOptions::new().create(true).append(true).open(log_path(state_dir))?;
+    writeln!(f, "{}", serde_json::to_string(&entry)?)?;
+    let next = Head { count: head.count + 1, hash: entry.hash };
+    fs::write(state_dir.join(HEAD_FILE), serde_json::to_string(&next)?)?;
+    Ok(())
+}
+
+pub fn read_entries(state_dir: &Path) -> Result<Vec<Entry>, String> {
+    let text = match fs::read_to_string(log_path(state_dir)) {
+        Ok(s) => s,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(t!("監査ログを読めません: {}", e)),
+    };
+    text.lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty())
+        .map(|(i, l)| serde_json::from_str(l).map_err(|e| t!("{} 行目を解釈できません: {}", i + 1, e)))
+        .collect()
+}
+
+// 連鎖を先頭から検証し、問題がなければ件数を返す
+pub fn verify(state_dir: &Path) -> Result<usize, String> {
+    let entries = read_entries(state_dir)?;
+    let mut prev = GENESIS.to_string();
+    for (i, e) in entries.iter().enumerate() {
+        if e.prev != prev {
+            return Err(t!("{} 行目: 直前の記録との連鎖が一致しません（削除または挿入）", i + 1));
+        }
+        if entry_hash(e) != e.hash {
+            return Err(t!("{} 行目: 内容がハッシュと一致しません（書き換え）", i + 1));
+        }
+        prev = e.hash.clone();
+    }
+    match read_head(state_dir) {
+        Some(head) if head.count != entries.len() || head.hash != prev => {
+            Err(t!("末尾の記録が失われています（記録 {} 件、ログ {} 件）", head.count, entries.len()))
+        }
+        None if !entries.is_empty() => Err(t!("{} が見つかりません", HEAD_FILE)),
+        _ => Ok(entries.len()),
+    }
+}
@@ -0,0 +1,158 @@
+// auth の検証と総当たり対策
+//
+// シークレットは環境変数と直接比較せず、状態ディレクトリの verifier（PBKDF2-HMAC-SHA256）と
+// 定数時間で比較する。verifier が無ければ初回の auth で AUTH_SECRET から作成する。
+// 失敗回数は auth_attempts に保存し、FREE_ATTEMPTS 回を超えると待ち時間を倍々に延ばす。
+// 並行した auth が失敗回数を上書きし合わないよう、確認から保存までを auth.lock で排他する
+
+use std::fs;
+use std::path::Path;
+use base64::{engine::general_purpose::STANDARD as B64, Engine as _};
+use chrono::Utc;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use subtle::ConstantTimeEq;
+
+use crate::audit;
+
+const VERIFIER_FILE: &str = "verifier";
+const ATTEMPTS_FILE: &str = "auth_attempts";
+const LOCK_FILE: &str = "auth.lock";
+const PBKDF2_ROUNDS: u32 = 100_000;
+const FREE_ATTEMPTS: u32 = 3;
+const MAX_BACKOFF_SECS: i64 = 3600;
+
+#[derive(Serialize, Deserialize)]
+struct Verifier {
+    algorithm: String,
+    rounds: u32,
+    salt: String,
+    hash: String,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct Attempts {
+    failures: u32,
+    last_failure: i64,
+}
+
+#[derive(Debug)]
+pub enum AuthError {
+    // 待ち時間中（残り秒数）
+    Locked(i64),
+    Rejected,
+    Other(String),
+}
+
+fn derive(secret: &str, salt: &[u8], rounds: u32) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(secret.as_bytes(), salt, rounds, &mut out);
+    out
+}
+
+fn write_private(path: &Path, contents: &str) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() { fs::create_dir_all(dir)?; }
+    fs::write(path, contents)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(())
+}
+
+// 新しく作る verifier の反復回数。デバッグビルドに限り TSUPASSWD_PBKDF2_ROUNDS で減らせる（テスト用）
+fn enroll_rounds() -> u32 {
+    #[cfg(debug_assertions)]
+    if let Some(rounds) = std::env::var("TSUPASSWD_PBKDF2_ROUNDS").ok().and_then(|v| v.parse().ok()).filter(|&r| r > 0) {
+        return rounds;
+    }
+    PBKDF2_ROUNDS
+}
+
+fn load_or_enroll(state_dir: &Path) -> Result<Verifier, AuthError> {
+    let path = state_dir.join(VERIFIER_FILE);
+    if let Ok(s) = fs::read_to_string(&path) {
+        return serde_json::from_str(&s).map_err(|e| AuthError::Other(t!("verifier が壊れています: {}", e)));
+    }
+    let secret = std::env::var("AUTH_SECRET").map_err(|_| AuthError::Other(t!("環境変数 AUTH_SECRET が未設定です")))?;
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    let rounds = enroll_rounds();
+    let verifier = Verifier {
+        algorithm: "pbkdf2-sha256".to_string(),
+        rounds,
+        salt: B64.encode(salt),
+        hash: B64.encode(derive(&secret, &salt, rounds)),
+    };
+    let json = serde_json::to_string(&verifier).map_err(|e| AuthError::Other(e.to_string()))?;
+    write_private(&path, &json).map_err(|e| AuthError::Other(t!("verifier を保存できません: {}", e)))?;
+    Ok(verifier)
+}
+
+fn load_attempts(state_dir: &Path) -> Attempts {
+    fs::read_to_string(state_dir.join(ATTEMPTS_FILE))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save_attempts(state_dir: &Path, attempts: &Attempts) -> Result<(), AuthError> {
+    let json = serde_json::to_string(attempts).map_err(|e| AuthError::Other(e.to_string()))?;
+    write_private(&state_dir.join(ATTEMPTS_FILE), &json).map_err(|e| AuthError::Other(t!("試行回数を保存できません: {}", e)))
+}
+
+// FREE_ATTEMPTS 回目の失敗から 1, 2, 4, ... 秒（上限 MAX_BACKOFF_SECS）
+fn backoff_secs(failures: u32) -> i64 {
+    if failures < FREE_ATTEMPTS { return 0; }
+    let exp = (failures - FREE_ATTEMPTS).min(31);
+    (1i64 << exp).min(MAX_BACKOFF_SECS)
+}
+
+// 待ち時間中なら残り秒数を返す（シークレットを入力させる前に確認する）
+pub fn check_lockout(state_dir: &Path) -> Result<(), AuthError> {
+    let attempts = load_attempts(state_dir);
+    let remaining = attempts.last_failure + backoff_secs(attempts.failures) - Utc::now().timestamp();
+    if remaining > 0 {
+        let _ = audit::record(state_dir, "auth", None, false, Some(&format!("locked failures={} remaining={}", attempts.failures, remaining)));
+        return Err(AuthError::Locked(remaining));
+    }
+    Ok(())
+}
+
+// lock は drop で解除
+fn lock(state_dir: &Path) -> Result<fs::File, AuthError> {
+    let lock = |e: std::io::Error| AuthError::Other(t!("認証の状態をロックできません: {}", e));
+    fs::create_dir_all(state_dir).map_err(lock)?;
+    let file = fs::OpenOptions::new().create(true).truncate(false).write(true).open(state_dir.join(LOCK_FILE)).map_err(lock)?;
+    file.lock().map_err(lock)?;
+    Ok(file)
+}
+
+pub fn verify(state_dir: &Path, secret: &str) -> Result<(), AuthError> {
+    let _lock = lock(state_dir)?;
+    check_lockout(state_dir)?;
+    let verifier = load_or_enroll(state_dir)?;
+    let salt = B64.decode(&verifier.salt).map_err(|e| AuthError::Other(t!("verifier が壊れています: {}", e)))?;
+    let expected = B64.decode(&verifier.hash).map_err(|e| AuthError::Other(t!("verifier が壊れています: {}", e)))?;
+    let actual = derive(secret, &salt, verifier.rounds);
+    if bool::from(actual.as_slice().ct_eq(expected.as_slice())) {
+        save_attempts(state_dir, &Attempts::default())?;
+        let _ = audit::record(state_dir, "auth", None, true, None);
+        return Ok(());
+    }
+    let mut attempts = load_attempts(state_dir);
+    attempts.failures += 1;
+    attempts.last_failure = Utc::now().timestamp();
+    save_attempts(state_dir, &attempts)?;
+    let _ = audit::record(
+        state_dir,
+        "auth",
+        None,
+        false,
+        Some(&format!("failures={} backoff={}", attempts.failures, backoff_secs(attempts.failures))),
+    );
+    Err(AuthError::Rejected)
+}
@@ -0,0 +1,612 @@
+// コマンドライン引数の定義（clap）
+//
+// サブコマンドごとに値を検証し、未知の引数や不正な値はエラーにする。
+// --help と補完スクリプト（tsupasswd completions <shell>）もこの定義から生成する
+
+use std::ffi::OsString;
+use std::io;
+use std::path::PathBuf;
+
+use chrono::Utc;
+use clap::builder::Str;
+use clap::parser::ValueSource;
+use clap::{Arg, ArgAction, ArgGroup, ArgMatches, Args, CommandFactory, FromArgMatches, Parser, Subcommand};
+use clap_complete::engine::{ArgValueCandidates, CompletionCandidate};
+use clap_complete::env::Shells;
+use tsupasswd::{site, CustomField, SortKey};
+
+use crate::config;
+use crate::exit::{self, Failure};
+use crate::i18n;
+use crate::output;
+use crate::run;
+use crate::secret_input::{self, SecretSource};
+
+// 補完を要求するときに使う環境変数（COMPLETE=bash tsupasswd -- ...）
+pub const COMPLETE_ENV: &str = "COMPLETE";
+pub const SHELLS: [&str; 4] = ["bash", "zsh", "fish", "powershell"];
+
+#[derive(Parser)]
+#[command(
+    name = "tsupasswd",
+    about = "パスワードの生成・保存・取得（コマンドを省略するとパスワードを生成して表示）",
+    disable_help_subcommand = true,
+    after_help = "環境変数:\n  \
+        AUTH_SECRET           認証用シークレット（tsupasswd auth で使用）\n  \
+        TSUPASSWD_VAULT       --vault を省略したときの保管庫（名前またはパス）\n  \
+        TSUPASSWD_CONFIG      設定ファイルのパス（既定 ~/.config/tsupasswd/config.toml）\n  \
+        XDG_DATA_HOME         保管庫の置き場所（既定 ~/.local/share/tsupasswd）\n  \
+        XDG_STATE_HOME        セッション・監査ログの置き場所（既定 ~/.local/state/tsupasswd）\n  \
+        TSUPASSWD_CLIPBOARD   クリップボードの種類（auto / wayland / x11 / osc52）\n  \
+        TSUPASSWD_ENCODING    出力エンコーディングを指定（utf8 / sjis）。Windowsでのリダイレクト時に有効\n  \
+        LANG / LC_MESSAGES    表示言語（設定 ui.language が auto のとき。LC_ALL が優先、ja_* で日本語）"
+)]
+pub struct Cli {
+    /// 使用する保管庫（例: --vault work）。名前ごとにセッション・監査ログが分かれ、拡張子 .vault のパスは暗号化ファイル1つに保存する
+    #[arg(long, global = true, display_order = 900, value_name = "名前|パス", value_parser = clap::builder::NonEmptyStringValueParser::new())]
+    pub vault: Option<String>,
+    /// 出力とエラーを JSON 形式にする（export / import ではファイル形式も JSON）
+    #[arg(long, global = true, display_order = 901)]
+    pub json: bool,
+    /// 生成するパスワードの文字数（省略時は config の generate.length、既定 16）
+    #[arg(value_name = "長さ", value_parser = positive)]
+    pub length: Option<usize>,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// レコードを保存（パスワード省略時は生成）
+    Add(AddArgs),
+    /// URL でレコードを検索してユーザ名・パスワード等を表示
+    Get(GetArgs),
+    /// 登録済みの OTP シークレットから現在のコードを表示（HOTP はカウンタを1進める）
+    Otp(OtpArgs),
+    /// 保管庫の値を環境変数に入れてコマンドを実行（値は表示しない）
+    Run(RunArgs),
+    /// テンプレートの {{ tsupasswd "URL" "項目" }} を保管庫の値に置き換えて書き出す
+    Inject(InjectArgs),
+    /// url / username / title / note を検索
+    Search(SearchArgs),
+    /// 一覧を表示（--show-passwords を指定しない限りパスワードは表示しない）
+    List(ListArgs),
+    /// レコードを更新
+    Update(UpdateArgs),
+    /// レコードをゴミ箱へ移動（確認あり。端末でない場合は --yes が必要）
+    Delete(DeleteArgs),
+    /// ゴミ箱の一覧・復元・完全削除
+    #[command(subcommand)]
+    Trash(TrashCommand),
+    /// 全画面で一覧・絞り込み・編集・削除
+    Tui,
+    /// CSV（--json では任意フィールドを含む JSON 配列）へ書き出す
+    Export(TransferArgs),
+    /// CSV（--json では JSON 配列）から取り込む
+    Import(TransferArgs),
+    /// 認証してセッションを開始（シークレット省略時は端末からエコーなしで入力）
+    Auth(AuthArgs),
+    /// セッションを終了
+    Logout,
+    /// セッションの残り時間と使用中の保管庫を表示
+    Status,
+    /// 監査ログを表示・検証
+    Log(LogArgs),
+    /// 設定ファイルの既定値を表示・変更
+    #[command(subcommand)]
+    Config(ConfigCommand),
+    /// シェルの補完スクリプトを出力（例: source <(tsupasswd completions bash)）
+    Completions {
+        /// 対象のシェル
+        #[arg(value_parser = SHELLS)]
+        shell: String,
+    },
+    /// get --copy が起動する消去用の子プロセス（内部用。値のハッシュは標準入力から読む）
+    #[command(name = "__clear-clipboard", hide = true)]
+    ClearClipboard { secs: u64 },
+}
+
+#[derive(Args)]
+pub struct AddArgs {
+    /// サイトURL等の識別子
+    #[arg(add = ArgValueCandidates::new(url_candidates))]
+    pub url: String,
+    /// ユーザ名
+    pub username: String,
+    /// 文字列を指定するとそのまま保存（警告あり）、数値を指定するとその長さで生成
+    #[arg(value_name = "PASSWORD|LENGTH")]
+    pub password_or_length: Option<String>,
+    /// タイトル
+    #[arg(long)]
+    pub title: Option<String>,
+    /// 備考
+    #[arg(long)]
+    pub note: Option<String>,
+    /// OTPシークレット（base32、otpauth://totp|hotp/... または steam://<base32>）
+    #[arg(long, value_name = "SECRET|URI")]
+    pub totp: Option<String>,
+    #[command(flatten)]
+    pub fields: FieldArgs,
+    /// フォルダ（例: work/aws）
+    #[arg(long, value_name = "F")]
+    pub folder: Option<String>,
+    /// タグ（複数指定可、カンマ区切り可）
+    #[arg(long = "tag", value_name = "T")]
+    pub tags: Vec<String>,
+    #[command(flatten)]
+    pub input: PasswordInput,
+}
+
+#[derive(Args)]
+pub struct FieldArgs {
+    /// 任意フィールド（複数指定可。PIN、秘密の質問、リカバリコード等）
+    #[arg(long = "field", value_name = "NAME=VALUE", value_parser = parse_field)]
+    pub fields: Vec<CustomField>,
+    /// 直前の --field を暗号化して保存
+    #[arg(long, action = ArgAction::Count)]
+    pub secret: u8,
+}
+
+#[derive(Args)]
+pub struct PasswordInput {
+    /// パスワードを端末からエコーなしで入力（確認のため2回）
+    #[arg(long)]
+    pub prompt: bool,
+    /// パスワードを標準入力から読む
+    #[arg(long)]
+    pub password_stdin: bool,
+    /// パスワードをファイルディスクリプタ N から読む
+    #[arg(long, value_name = "N", value_parser = fd)]
+    pub secret_fd: Option<u32>,
+}
+
+impl PasswordInput {
+    // 引数で渡されたパスワード（あれば）を含め、指定が1つだけであることを確かめる
+    pub fn source(&self, argv: Option<String>) -> Result<Option<SecretSource>, String> {
+        let mut slot = None;
+        let given = [
+            argv.map(SecretSource::Argv),
+            self.prompt.then_some(SecretSource::Prompt),
+            self.password_stdin.then_some(SecretSource::Stdin),
+            self.secret_fd.map(SecretSource::Fd),
+        ];
+        for source in given.into_iter().flatten() {
+            secret_input::choose(&mut slot, source)?;
+        }
+        Ok(slot)
+    }
+}
+
+#[derive(Args)]
+#[command(group(ArgGroup::new("extract").args(["field", "format"])))]
+pub struct GetArgs {
+    /// サイトURL
+    #[arg(add = ArgValueCandidates::new(url_candidates))]
+    pub url: String,
+    /// 照合方法 exact（入力どおり） / host（ホスト名） / domain（登録可能ドメイン）
+    #[arg(long = "match", value_name = "MODE", default_value = "host", value_parser = match_mode)]
+    pub mode: site::MatchMode,
+    /// パスワードを表示せずクリップボードにコピー
+    #[arg(long)]
+    pub copy: bool,
+    /// コピーした値がまだ残っていれば SECS 秒後に消去（0 で消去しない。既定は config の clipboard.clear_after）
+    #[arg(long, value_name = "SECS", value_parser = seconds, requires = "copy")]
+    pub clear_after: Option<u64>,
+    /// 先頭のレコードの1項目だけをそのまま出力（url / username / password / title / note / 任意フィールド名）
+    #[arg(long, value_name = "NAME", conflicts_with = "copy")]
+    pub field: Option<String>,
+    /// 先頭のレコードを shell / env / dotenv 形式、または {username}:{password} のようなテンプレートで出力
+    #[arg(long, value_name = "FORMAT", value_parser = output_format, conflicts_with = "copy")]
+    pub format: Option<output::Format>,
+    /// --field / --format の出力の末尾に改行を付けない
+    #[arg(short = 'n', long, requires = "extract")]
+    pub no_newline: bool,
+}
+
+#[derive(Args)]
+#[command(after_help = "参照:\n  \
+    url:<URL>#<項目>  get と同じ照合で見つかった先頭のレコードの項目（項目は get --field と同じ。省略時は password）\n\n\
+    例: tsupasswd run --env DB_PASS=url:db.internal#password -- ./deploy.sh")]
+pub struct RunArgs {
+    /// 子プロセスに渡す環境変数と値の参照（複数指定可）
+    #[arg(short, long, value_name = "NAME=REF", value_parser = env_reference, required = true)]
+    pub env: Vec<(String, run::Reference)>,
+    /// 照合方法 exact（入力どおり） / host（ホスト名） / domain（登録可能ドメイン）
+    #[arg(long = "match", value_name = "MODE", default_value = "host", value_parser = match_mode)]
+    pub mode: site::MatchMode,
+    /// 子プロセスの標準出力・標準エラーに現れた値を <redacted> に置き換える
+    #[arg(long)]
+    pub redact: bool,
+    /// 実行するコマンドと引数（-- の後に指定）
+    #[arg(required = true, last = true, value_name = "COMMAND", value_parser = clap::value_parser!(OsString))]
+    pub command: Vec<OsString>,
+}
+
+#[derive(Args)]
+#[command(after_help = "テンプレート:\n  \
+    {{ tsupasswd \"<URL>\" \"<項目>\" }}  get と同じ照合で見つかった先頭のレコードの項目に置き換える\n  \
+    （項目は get --field と同じ。省略時は password）。それ以外の {{ ... }} はそのまま残す\n\n\
+    例: tsupasswd inject -i config.tmpl -o config.yaml")]
+pub struct InjectArgs {
+    /// テンプレートのファイル（省略時は標準入力）
+    #[arg(short, long, value_name = "PATH", value_hint = clap::ValueHint::FilePath)]
+    pub input: Option<PathBuf>,
+    /// 書き出すファイル（パーミッション 0600。省略時は標準出力）
+    #[arg(short, long, value_name = "PATH", value_hint = clap::ValueHint::FilePath)]
+    pub output: Option<PathBuf>,
+    /// 照合方法 exact（入力どおり） / host（ホスト名） / domain（登録可能ドメイン）
+    #[arg(long = "match", value_name = "MODE", default_value = "host", value_parser = match_mode)]
+    pub mode: site::MatchMode,
+}
+
+#[derive(Args)]
+pub struct OtpArgs {
+    /// サイトURL
+    #[arg(add = ArgValueCandidates::new(url_candidates))]
+    pub url: String,
+}
+
+#[derive(Args)]
+pub struct SearchArgs {
+    /// 検索語（--tag 指定時は省略可）
+    pub keyword: Option<String>,
+    /// タグで絞り込み
+    #[arg(long, value_name = "TAG")]
+    pub tag: Option<String>,
+    /// あいまい検索（綴り間違いを許容し、タイトル・ドメインの一致や新しさで順位付け）
+    #[arg(long, conflicts_with = "fts")]
+    pub fuzzy: bool,
+    /// 全文検索索引で検索（AND / OR / NOT、"フレーズ"、前方一致 foo*、title:foo。3文字以上の語）
+    #[arg(long)]
+    pub fts: bool,
+    /// 最大 N 件を表示（--fuzzy の既定は 10）
+    #[arg(long, value_name = "N", value_parser = positive)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Args)]
+pub struct ListArgs {
+    /// フォルダ（配下のフォルダを含む）で絞り込み
+    #[arg(long, value_name = "F")]
+    pub folder: Option<String>,
+    /// タグで絞り込み
+    #[arg(long, value_name = "TAG")]
+    pub tag: Option<String>,
+    /// 並び順 created / updated / url / title（既定 created の昇順）
+    #[arg(long, value_name = "KEY", value_parser = sort_key)]
+    pub sort: Option<SortKey>,
+    /// 降順に並べる
+    #[arg(long)]
+    pub desc: bool,
+    /// 最大 N 件を表示
+    #[arg(long, value_name = "N", value_parser = non_negative)]
+    pub limit: Option<i64>,
+    /// 先頭 M 件を読み飛ばす
+    #[arg(long, value_name = "M", value_parser = non_negative)]
+    pub offset: Option<i64>,
+    /// 表示する列（id,url,username,title,note,folder,tags,created_at,updated_at,password）
+    #[arg(long, value_name = "C,...")]
+    pub columns: Option<String>,
+    /// パスワードを復号して表示
+    #[arg(long)]
+    pub show_passwords: bool,
+}
+
+#[derive(Args)]
+#[command(after_help = "対象の指定:\n  \
+    id は一意であれば先頭4文字以上で指定可能（例: 3f2a）。複数に一致する場合は候補を表示して中止\n  \
+    条件: url:example.com（部分一致） user:alice（完全一致） title:T（部分一致） folder:F（配下を含む） tag:T\n  \
+    複数の条件はすべてを満たすもの。2件以上に一致する場合は --all-matching が必要")]
+pub struct UpdateArgs {
+    /// 対象の id または条件
+    #[arg(required = true, value_name = "ID|条件")]
+    pub selectors: Vec<String>,
+    /// URL を更新
+    #[arg(long, value_name = "U")]
+    pub url: Option<String>,
+    /// ユーザ名を更新
+    #[arg(long = "user", value_name = "NAME")]
+    pub username: Option<String>,
+    /// パスワードをこの文字列に更新（ps や履歴に残るため --prompt 等を推奨）
+    #[arg(long, value_name = "PASS")]
+    pub password: Option<String>,
+    #[command(flatten)]
+    pub input: PasswordInput,
+    /// ランダムに N 文字のパスワードを生成して更新
+    #[arg(long, value_name = "N", value_parser = positive)]
+    pub length: Option<usize>,
+    /// タイトルを更新
+    #[arg(long, value_name = "T")]
+    pub title: Option<String>,
+    /// 備考を更新
+    #[arg(long, value_name = "N")]
+    pub note: Option<String>,
+    /// OTPシークレットを更新（base32、otpauth:// URI または steam://）
+    #[arg(long, value_name = "S")]
+    pub totp: Option<String>,
+    #[command(flatten)]
+    pub fields: FieldArgs,
+    /// 任意フィールドを削除
+    #[arg(long = "unset-field", value_name = "NAME")]
+    pub unset_fields: Vec<String>,
+    /// フォルダを変更（空文字で解除）
+    #[arg(long, value_name = "F")]
+    pub folder: Option<String>,
+    /// タグを追加
+    #[arg(long = "tag", value_name = "T")]
+    pub tags: Vec<String>,
+    /// タグを外す
+    #[arg(long = "untag", value_name = "T")]
+    pub untags: Vec<String>,
+    #[command(flatten)]
+    pub targets: TargetFlags,
+}
+
+#[derive(Args)]
+#[command(after_help = "対象の指定は update と同じ（tsupasswd update --help）")]
+pub struct DeleteArgs {
+    /// 対象の id または条件
+    #[arg(required = true, value_name = "ID|条件")]
+    pub selectors: Vec<String>,
+    #[command(flatten)]
+    pub targets: TargetFlags,
+}
+
+#[derive(Args)]
+pub struct TargetFlags {
+    /// 条件に一致したすべてのレコードを対象にする（対象を表示して確認）
+    #[arg(long)]
+    pub all_matching: bool,
+    /// 対象を表示するだけで変更しない
+    #[arg(long)]
+    pub dry_run: bool,
+    /// 確認を省略
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+#[derive(Subcommand)]
+pub enum TrashCommand {
+    /// ゴミ箱の一覧
+    List,
+    /// ゴミ箱から復元
+    Restore {
+        /// レコードの id
+        id: String,
+    },
+    /// ゴミ箱のレコードを完全削除
+    Purge {
+        /// 削除から指定期間が過ぎたものだけを完全削除（例: 30d・24h・90m）
+        #[arg(long, value_name = "AGE", value_parser = age)]
+        older_than: Option<chrono::Duration>,
+    },
+}
+
+#[derive(Args)]
+pub struct TransferArgs {
+    /// ファイルのパス
+    #[arg(value_hint = clap::ValueHint::FilePath)]
+    pub path: String,
+}
+
+#[derive(Args)]
+pub struct AuthArgs {
+    /// 認証シークレット（ps や履歴に残るため --password-stdin 等を推奨）
+    pub secret: Option<String>,
+    /// シークレットを標準入力から読む
+    #[arg(long)]
+    pub password_stdin: bool,
+    /// シークレットをファイルディスクリプタ N から読む
+    #[arg(long, value_name = "N", value_parser = fd)]
+    pub secret_fd: Option<u32>,
+    /// セッション有効期限（分）。既定は config の session.ttl（30）
+    #[arg(long, value_name = "MINUTES", value_parser = minutes)]
+    pub ttl: Option<i64>,
+}
+
+impl AuthArgs {
+    pub fn source(&self) -> Result<Option<SecretSource>, String> {
+        let input = PasswordInput { prompt: false, password_stdin: self.password_stdin, secret_fd: self.secret_fd };
+        input.source(self.secret.clone())
+    }
+}
+
+#[derive(Args)]
+pub struct LogArgs {
+    /// 指定日時以降のみ（2024-01-31 / RFC3339 / 30m・24h・7d）
+    #[arg(long, value_name = "WHEN", value_parser = since)]
+    pub since: Option<chrono::DateTime<Utc>>,
+    /// 操作名で絞り込み（例: get）
+    #[arg(long, value_name = "OP")]
+    pub op: Option<String>,
+    /// ハッシュ連鎖を検証し、書き換え・削除があれば終了コード 10
+    #[arg(long)]
+    pub verify: bool,
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommand {
+    /// 設定値を表示
+    Get {
+        /// 設定キー（例: generate.length）
+        #[arg(add = ArgValueCandidates::new(config_key_candidates))]
+        key: String,
+    },
+    /// 設定値を変更
+    Set {
+        /// 設定キー（例: generate.length）
+        #[arg(add = ArgValueCandidates::new(config_key_candidates))]
+        key: String,
+        /// 値
+        value: String,
+    },
+    /// すべての設定値を表示
+    List,
+}
+
+// 見出し・ヘルプを表示言語に合わせた定義（補完もこれを使う）
+pub fn command() -> clap::Command {
+    localize(Cli::command())
+}
+
+// 説明はドキュメントコメント（日本語）に書き、英語のときはカタログの訳に置き換える
+fn localize(cmd: clap::Command) -> clap::Command {
+    // 見出しはテンプレートで付ける（help_heading を使うと位置引数の並びが崩れるため）
+    let mut template = format!("{{about-with-newline}}\n{} {{usage}}\n", t!("使い方:"));
+    if cmd.has_subcommands() { template.push_str(&format!("\n{}\n{{subcommands}}\n", t!("コマンド:"))); }
+    if cmd.get_positionals().next().is_some() { template.push_str(&format!("\n{}\n{{positionals}}\n", t!("引数:"))); }
+    template.push_str(&format!("\n{}\n{{options}}{{after-help}}", t!("オプション:")));
+    let about = cmd.get_about().and_then(|s| i18n::lookup(&s.to_string()));
+    let after_help = cmd.get_after_help().and_then(|s| i18n::lookup(&s.to_string()));
+    let mut cmd = cmd
+        .help_template(template)
+        .subcommand_value_name(i18n::tr("コマンド"))
+        .disable_help_flag(true)
+        .arg(Arg::new("help").short('h').long("help").help(t!("このヘルプを表示")).action(ArgAction::Help).display_order(1000))
+        .mut_args(localize_arg)
+        .mut_subcommands(localize);
+    if let Some(about) = about { cmd = cmd.about(about); }
+    if let Some(after_help) = after_help { cmd = cmd.after_help(after_help); }
+    cmd
+}
+
+fn localize_arg(mut arg: Arg) -> Arg {
+    if let Some(help) = arg.get_help().and_then(|s| i18n::lookup(&s.to_string())) { arg = arg.help(help); }
+    let names: Vec<Str> = arg.get_value_names().unwrap_or_default().iter().map(|n| i18n::lookup(n).map_or_else(|| n.clone(), Str::from)).collect();
+    if names.is_empty() { arg } else { arg.value_names(names) }
+}
+
+// 引数を解析する。不正な引数はヘルプへの案内を付けて終了する（終了コード 2）。
+// `tsupasswd help [コマンド]` は `--help` と同じ
+pub fn parse() -> Cli {
+    let mut args: Vec<OsString> = std::env::args_os().collect();
+    if args.get(1).is_some_and(|a| a == "help") {
+        args.remove(1);
+        args.push("--help".into());
+    }
+    // 解析に失敗したときもエラーを JSON で出せるよう、先に --json の有無だけ見ておく
+    let json = args.iter().skip(1).take_while(|a| *a != "--").any(|a| a == "--json");
+    exit::set_json_errors(json || crate::settings().json);
+    let mut cmd = command();
+    let matches = cmd.try_get_matches_from_mut(args).unwrap_or_else(|e| usage_error(e));
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| usage_error(e));
+    // args_conflicts_with_subcommands は全体オプション --vault まで拒否するため自前で確かめる
+    if cli.length.is_some() && cli.command.is_some() {
+        usage_error(cmd.error(clap::error::ErrorKind::ArgumentConflict, t!("長さとコマンドは同時に指定できません")));
+    }
+    let (name, marked) = match &mut cli.command {
+        Some(Command::Add(a)) => ("add", mark_secret_fields(matches.subcommand_matches("add"), &mut a.fields.fields)),
+        Some(Command::Update(u)) => ("update", mark_secret_fields(matches.subcommand_matches("update"), &mut u.fields.fields)),
+        _ => ("", Ok(())),
+    };
+    if let Err(msg) = marked {
+        let sub = cmd.find_subcommand_mut(name).expect("subcommand is defined");
+        usage_error(sub.error(clap::error::ErrorKind::ArgumentConflict, msg));
+    }
+    cli
+}
+
+// --help はそのまま表示する。JSON モードでは clap の案内文を除いた1行目だけを usage エラーとして出す
+fn usage_error(e: clap::Error) -> ! {
+    if !e.use_stderr() || !exit::json_errors() { e.exit() }
+    let text = e.render().to_string();
+    let line = text.lines().next().unwrap_or_default();
+    crate::fail(Failure::Usage, line.strip_prefix("error: ").unwrap_or(line))
+}
+
+// --secret を直前の --field に対応づける（--field name=value --secret の順で指定）
+fn mark_secret_fields(matches: Option<&ArgMatches>, fields: &mut [CustomField]) -> Result<(), String> {
+    // Count は未指定でも既定値 0 の位置を返すため、指定されたときだけ見る
+    let Some(m) = matches.filter(|m| m.value_source("secret") == Some(ValueSource::CommandLine)) else { return Ok(()) };
+    let field_at: Vec<usize> = m.indices_of("fields").map(|i| i.collect()).unwrap_or_default();
+    for secret_at in m.indices_of("secret").into_iter().flatten() {
+        match field_at.iter().rposition(|&f| f < secret_at) {
+            Some(i) => fields[i].secret = true,
+            None => return Err(t!("--secret は --field の後に指定してください")),
+        }
+    }
+    Ok(())
+}
+
+// completions <shell>: 実行ファイルを補完エンジンとして呼び出す登録スクリプト
+pub fn write_completions(shell: &str, out: &mut dyn io::Write) -> io::Result<()> {
+    let shells = Shells::builtins();
+    let Some(completer) = shells.completer(shell) else {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, t!("未対応のシェルです: {}", shell)));
+    };
+    let exe = std::env::current_exe()?;
+    completer.write_registration(COMPLETE_ENV, "tsupasswd", "tsupasswd", &exe.to_string_lossy(), out)
+}
+
+fn url_candidates() -> Vec<CompletionCandidate> {
+    crate::completion_urls().into_iter().map(CompletionCandidate::new).collect()
+}
+
+fn config_key_candidates() -> Vec<CompletionCandidate> {
+    config::KEYS.iter().map(|k| CompletionCandidate::new(k.name).help(Some(i18n::tr(k.help).into()))).collect()
+}
+
+// `name=value` 形式の指定を解析
+fn parse_field(arg: &str) -> Result<CustomField, String> {
+    match arg.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() => Ok(CustomField {
+            name: name.trim().to_string(),
+            value: value.to_string(),
+            secret: false,
+        }),
+        _ => Err(t!("name=value の形式で指定してください")),
+    }
+}
+
+fn positive(s: &str) -> Result<usize, String> {
+    s.parse::<usize>().ok().filter(|n| *n > 0).ok_or_else(|| t!("1以上の整数を指定してください"))
+}
+
+fn non_negative(s: &str) -> Result<i64, String> {
+    s.parse::<i64>().ok().filter(|n| *n >= 0).ok_or_else(|| t!("0以上の整数を指定してください"))
+}
+
+fn minutes(s: &str) -> Result<i64, String> {
+    s.parse::<i64>()
+        .ok()
+        .filter(|n| (1..=config::TTL_MAX_MINUTES).contains(n))
+        .ok_or_else(|| t!("1〜{} の分数を指定してください", config::TTL_MAX_MINUTES))
+}
+
+fn seconds(s: &str) -> Result<u64, String> {
+    s.parse::<u64>().map_err(|_| t!("秒数を指定してください"))
+}
+
+fn fd(s: &str) -> Result<u32, String> {
+    s.parse::<u32>().map_err(|_| t!("数値を指定してください"))
+}
+
+fn match_mode(s: &str) -> Result<site::MatchMode, String> {
+    site::MatchMode::parse(s).map_err(|_| t!("exact / host / domain のいずれかを指定してください"))
+}
+
+fn env_reference(s: &str) -> Result<(String, run::Reference), String> {
+    let (name, reference) = s.split_once('=').ok_or_else(|| t!("NAME=url:<URL>#<項目> の形式で指定してください"))?;
+    let mut chars = name.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_') && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        return Err(t!("環境変数名は英字または _ で始まる英数字と _ にしてください: {}", name));
+    }
+    Ok((name.to_string(), run::Reference::parse(reference)?))
+}
+
+fn output_format(s: &str) -> Result<output::Format, String> {
+    output::Format::parse(s)
+}
+
+fn sort_key(s: &str) -> Result<SortKey, String> {
+    SortKey::parse(s).ok_or_else(|| t!("created / updated / url / title のいずれかを指定してください"))
+}
+
+fn since(s: &str) -> Result<chrono::DateTime<Utc>, String> {
+    crate::parse_since(s).ok_or_else(|| t!("2024-01-31 / RFC3339 / 30m・24h・7d のいずれかで指定してください"))
+}
+
+fn age(s: &str) -> Result<chrono::Duration, String> {
+    crate::parse_age(s).ok_or_else(|| t!("30d・24h・90m などで指定してください"))
+}
@@ -0,0 +1,147 @@
+// クリップボードへのコピーと一定時間後の消去
+//
+// バックエンドは環境変数 TSUPASSWD_CLIPBOARD で選ぶ（既定 auto）:
+//   auto      Wayland（wl-copy）→ X11（xclip / xsel）→ OSC 52 の順に利用可能なもの
+//   wayland / x11 / osc52
+//   mock:PATH ファイルをクリップボードとみなす（テスト用）
+
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use base64::{engine::general_purpose::STANDARD as B64, Engine as _};
+use sha2::{Digest, Sha256};
+
+pub const BACKEND_ENV: &str = "TSUPASSWD_CLIPBOARD";
+
+type Error = Box<dyn std::error::Error + Send + Sync>;
+
+pub trait Clipboard {
+    fn set(&mut self, text: &str) -> Result<(), Error>;
+    // 読み取れないバックエンド（OSC 52）は None
+    fn get(&mut self) -> Result<Option<String>, Error>;
+    fn clear(&mut self) -> Result<(), Error> {
+        self.set("")
+    }
+}
+
+// 外部コマンド経由（wl-copy / xclip / xsel）
+struct CommandClipboard {
+    copy: &'static [&'static str],
+    paste: &'static [&'static str],
+}
+
+const WAYLAND: CommandClipboard = CommandClipboard { copy: &["wl-copy"], paste: &["wl-paste", "--no-newline"] };
+const XCLIP: CommandClipboard = CommandClipboard {
+    copy: &["xclip", "-selection", "clipboard", "-in"],
+    paste: &["xclip", "-selection", "clipboard", "-out"],
+};
+const XSEL: CommandClipboard = CommandClipboard { copy: &["xsel", "--clipboard", "--input"], paste: &["xsel", "--clipboard", "--output"] };
+
+impl Clipboard for CommandClipboard {
+    fn set(&mut self, text: &str) -> Result<(), Error> {
+        let mut child = Command::new(self.copy[0])
+            .args(&self.copy[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        let status = child.wait()?;
+        if !status.success() {
+            return Err(t!("{} が失敗しました", self.copy[0]).into());
+        }
+        Ok(())
+    }
+
+    fn get(&mut self) -> Result<Option<String>, Error> {
+        let out = Command::new(self.paste[0]).args(&self.paste[1..]).stderr(Stdio::null()).output()?;
+        // 空のクリップボードで失敗扱いになる実装があるため、失敗は空とみなす
+        if !out.status.success() {
+            return Ok(Some(String::new()));
+        }
+        Ok(Some(String::from_utf8_lossy(&out.stdout).into_owned()))
+    }
+}
+
+// 端末経由（SSH 先でも手元のクリップボードに届く）。内容は読み取れない
+struct Osc52;
+
+impl Clipboard for Osc52 {
+    fn set(&mut self, text: &str) -> Result<(), Error> {
+        let seq = format!("\x1b]52;c;{}\x07", B64.encode(text));
+        // 標準出力がパイプでも端末に届くよう /dev/tty に書く
+        match fs::OpenOptions::new().write(true).open("/dev/tty") {
+            Ok(mut tty) => tty.write_all(seq.as_bytes())?,
+            Err(_) => std::io::stderr().write_all(seq.as_bytes())?,
+        }
+        Ok(())
+    }
+
+    fn get(&mut self) -> Result<Option<String>, Error> {
+        Ok(None)
+    }
+}
+
+struct MockClipboard {
+    path: PathBuf,
+}
+
+impl Clipboard for MockClipboard {
+    fn set(&mut self, text: &str) -> Result<(), Error> {
+        fs::write(&self.path, text)?;
+        Ok(())
+    }
+
+    fn get(&mut self) -> Result<Option<String>, Error> {
+        match fs::read_to_string(&self.path) {
+            Ok(s) => Ok(Some(s)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Some(String::new())),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+fn has_command(name: &str) -> bool {
+    std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).any(|dir| dir.join(name).is_file()))
+        .unwrap_or(false)
+}
+
+pub fn backend() -> Result<Box<dyn Clipboard>, Error> {
+    let name = std::env::var(BACKEND_ENV).unwrap_or_else(|_| "auto".to_string());
+    if let Some(path) = name.strip_prefix("mock:") {
+        return Ok(Box::new(MockClipboard { path: PathBuf::from(path) }));
+    }
+    let wayland = std::env::var_os("WAYLAND_DISPLAY").is_some() && has_command("wl-copy");
+    let x11 = std::env::var_os("DISPLAY").is_some();
+    match name.as_str() {
+        "wayland" => Ok(Box::new(WAYLAND)),
+        "x11" if has_command("xclip") => Ok(Box::new(XCLIP)),
+        "x11" => Ok(Box::new(XSEL)),
+        "osc52" => Ok(Box::new(Osc52)),
+        "auto" if wayland => Ok(Box::new(WAYLAND)),
+        "auto" if x11 && has_command("xclip") => Ok(Box::new(XCLIP)),
+        "auto" if x11 && has_command("xsel") => Ok(Box::new(XSEL)),
+        "auto" => Ok(Box::new(Osc52)),
+        _ => Err(t!("{} は auto / wayland / x11 / osc52 / mock:PATH のいずれかを指定してください: {}", BACKEND_ENV, name).into()),
+    }
+}
+
+// 消去用の子プロセスには値そのものではなくハッシュを渡す
+pub fn fingerprint(text: &str) -> String {
+    Sha256::digest(text.as_bytes()).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// 自分が書いた値がまだ残っている場合のみ消去する（読み取れないバックエンドは無条件に消去）
+pub fn clear_if_unchanged(clip: &mut dyn Clipboard, expected_fingerprint: &str) -> Result<bool, Error> {
+    match clip.get()? {
+        Some(current) if fingerprint(&current) != expected_fingerprint => Ok(false),
+        _ => {
+            clip.clear()?;
+            Ok(true)
+        }
+    }
+}
@@ -0,0 +1,170 @@
+// 設定ファイル（TOML）
+//
+//   [generate] length = 16, policy = "alnum"   パスワード生成の既定値
+//   [session]  ttl = 30                        auth のセッション有効期限（分）
+//   [output]   format = "text", encoding = "auto"
+//   [clipboard] clear_after = 30               コピー後に消去するまでの秒数（0 で消去しない）
+//   [vault]    path = "work"                   --vault / TSUPASSWD_VAULT を省略したときの保管庫
+//   [ui]       language = "auto"               表示言語（auto は LC_ALL / LC_MESSAGES / LANG から決める）
+//
+// 値が不正なキーは警告して既定値を使う（設定ファイルの誤りでコマンドが使えなくならないように）
+
+use std::fs;
+use std::path::Path;
+use tsupasswd::Policy;
+
+use crate::i18n;
+use crate::location;
+
+pub struct Key {
+    pub name: &'static str,
+    pub default: &'static str,
+    pub help: &'static str,
+    kind: Kind,
+}
+
+enum Kind {
+    Int { min: i64, max: i64 },
+    Choice(&'static [&'static str]),
+    Text,
+}
+
+// セッションの有効期限の上限（分、1年）。auth --ttl も同じ範囲
+pub const TTL_MAX_MINUTES: i64 = 525_600;
+
+pub const KEYS: &[Key] = &[
+    Key { name: "generate.length", default: "16", help: "生成するパスワードの文字数", kind: Kind::Int { min: 1, max: 4096 } },
+    Key { name: "generate.policy", default: "alnum", help: "使う文字の種類（alnum / symbols / digits）", kind: Kind::Choice(Policy::NAMES) },
+    Key { name: "session.ttl", default: "30", help: "auth のセッション有効期限（分）", kind: Kind::Int { min: 1, max: TTL_MAX_MINUTES } },
+    Key { name: "output.format", default: "text", help: "--json 対応コマンドの既定の出力形式（text / json）", kind: Kind::Choice(&["text", "json"]) },
+    Key { name: "output.encoding", default: "auto", help: "出力エンコーディング（auto / utf8 / sjis）。TSUPASSWD_ENCODING が優先", kind: Kind::Choice(&["auto", "utf8", "sjis"]) },
+    Key { name: "clipboard.clear_after", default: "30", help: "コピーしたパスワードを消去するまでの秒数（0 で消去しない）", kind: Kind::Int { min: 0, max: 86_400 } },
+    Key { name: "vault.path", default: "default", help: "既定の保管庫（名前またはパス）", kind: Kind::Text },
+    Key { name: "ui.language", default: "auto", help: "表示言語（auto / ja / en）。auto は LC_ALL / LC_MESSAGES / LANG に従う", kind: Kind::Choice(i18n::CHOICES) },
+];
+
+// 起動時に読み込んだ設定
+pub struct Settings {
+    pub length: usize,
+    pub policy: Policy,
+    pub ttl: i64,
+    pub json: bool,
+    pub encoding: Option<String>, // auto は None
+    pub clear_after: u64,
+    pub vault: Option<String>,    // default は None
+    pub warnings: Vec<String>,
+}
+
+fn find(name: &str) -> Result<&'static Key, String> {
+    KEYS.iter().find(|k| k.name == name).ok_or_else(|| {
+        let names: Vec<&str> = KEYS.iter().map(|k| k.name).collect();
+        t!("不明な設定キーです: {}（{}）", name, names.join(" / "))
+    })
+}
+
+impl Key {
+    fn parse(&self, text: &str) -> Result<toml::Value, String> {
+        match &self.kind {
+            Kind::Int { min, max } => match text.trim().parse::<i64>() {
+                Ok(n) if (*min..=*max).contains(&n) => Ok(toml::Value::Integer(n)),
+                _ => Err(t!("{} は {}〜{} の整数で指定してください: {}", self.name, min, max, text)),
+            },
+            Kind::Choice(choices) if choices.contains(&text) => Ok(toml::Value::String(text.to_string())),
+            Kind::Choice(choices) => Err(t!("{} は {} のいずれかで指定してください: {}", self.name, choices.join(" / "), text)),
+            Kind::Text if text.is_empty() => Err(t!("{} に空文字は指定できません", self.name)),
+            Kind::Text => Ok(toml::Value::String(text.to_string())),
+        }
+    }
+}
+
+fn value_text(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn read_table(path: &Path) -> Result<toml::Table, String> {
+    if !path.exists() { return Ok(toml::Table::new()); }
+    let text = fs::read_to_string(path).map_err(|e| t!("設定ファイルを読み込めません: {}: {}", path.display(), e))?;
+    text.parse::<toml::Table>().map_err(|e| t!("設定ファイルを解釈できません: {}: {}", path.display(), e))
+}
+
+fn lookup<'a>(table: &'a toml::Table, name: &str) -> Option<&'a toml::Value> {
+    let (section, key) = name.split_once('.')?;
+    table.get(section)?.as_table()?.get(key)
+}
+
+// 設定ファイルの値（検証済み）。未設定や不正な値は None
+fn configured(table: &toml::Table, key: &Key, warnings: &mut Vec<String>) -> Option<String> {
+    let text = value_text(lookup(table, key.name)?);
+    match key.parse(&text) {
+        Ok(v) => Some(value_text(&v)),
+        Err(e) => {
+            warnings.push(t!("設定を無視します: {}", e));
+            None
+        }
+    }
+}
+
+pub fn load() -> Settings {
+    let mut warnings = Vec::new();
+    let table = read_table(&location::config_path()).unwrap_or_else(|e| {
+        warnings.push(e);
+        toml::Table::new()
+    });
+    let mut get = |name: &str| {
+        let key = find(name).expect("known config key");
+        configured(&table, key, &mut warnings).unwrap_or_else(|| key.default.to_string())
+    };
+    // 以降の警告を選んだ言語で出すため最初に決める
+    i18n::select(&get("ui.language"));
+    let length = get("generate.length").parse().unwrap_or(16);
+    let policy = Policy::parse(&get("generate.policy")).unwrap_or_default();
+    let ttl = get("session.ttl").parse().unwrap_or(30);
+    let json = get("output.format") == "json";
+    let encoding = Some(get("output.encoding")).filter(|e| e != "auto");
+    let clear_after = get("clipboard.clear_after").parse().unwrap_or(30);
+    let vault = Some(get("vault.path")).filter(|v| v != location::DEFAULT_NAME);
+    Settings { length, policy, ttl, json, encoding, clear_after, vault, warnings }
+}
+
+// (キー, 値, 設定ファイルで指定されているか)
+pub fn get(name: &str) -> Result<(String, bool), String> {
+    let key = find(name)?;
+    let table = read_table(&location::config_path())?;
+    let mut warnings = Vec::new();
+    Ok(match configured(&table, key, &mut warnings) {
+        Some(v) => (v, true),
+        None => (key.default.to_string(), false),
+    })
+}
+
+pub fn list() -> Result<Vec<(&'static Key, String, bool)>, String> {
+    let table = read_table(&location::config_path())?;
+    let mut warnings = Vec::new();
+    Ok(KEYS
+        .iter()
+        .map(|key| match configured(&table, key, &mut warnings) {
+            Some(v) => (key, v, true),
+            None => (key, key.default.to_string(), false),
+        })
+        .collect())
+}
+
+// 検証してから書き込む。ほかのキー（未知のものを含む）はそのまま残す
+pub fn set(name: &str, text: &str) -> Result<(), String> {
+    let key = find(name)?;
+    let value = key.parse(text)?;
+    let path = location::config_path();
+    let mut table = read_table(&path)?;
+    let (section_name, field) = name.split_once('.').expect("config keys are section.key");
+    let section = table.entry(section_name).or_insert_with(|| toml::Value::Table(toml::Table::new()));
+    match section.as_table_mut() {
+        Some(t) => { t.insert(field.to_string(), value); }
+        None => return Err(t!("設定ファイルの [{}] がテーブルではありません: {}", section_name, path.display())),
+    }
+    let text = toml::to_string_pretty(&table).map_err(|e| t!("設定を書き込めません: {}", e))?;
+    if let Some(dir) = path.parent() { fs::create_dir_all(dir).map_err(|e| t!("設定を書き込めません: {}", e))?; }
+    fs::write(&path, text).map_err(|e| t!("設定を書き込めません: {}: {}", path.display(), e))
+}
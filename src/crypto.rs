@@ -0,0 +1,64 @@
+// レコード単位の暗号化（鍵はシークレットとレコード id から HKDF-SHA256 で導出、ChaCha20-Poly1305）
+
+use base64::{engine::general_purpose::STANDARD as B64, Engine as _};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::ChaCha20Poly1305;
+use hkdf::Hkdf;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sha2::Sha256;
+
+use crate::error::{Error, Result};
+
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+
+#[derive(Clone)]
+pub struct Cipher {
+    secret: String,
+}
+
+impl Cipher {
+    pub fn new(secret: &str) -> Self {
+        Cipher { secret: secret.to_string() }
+    }
+
+    fn key_for_id(&self, id: &str) -> Result<[u8; 32]> {
+        let hk = Hkdf::<Sha256>::new(Some(id.as_bytes()), self.secret.as_bytes());
+        let mut okm = [0u8; 32];
+        hk.expand(b"password-at-rest", &mut okm).map_err(|_| Error::Crypto(t!("鍵導出に失敗しました")))?;
+        Ok(okm)
+    }
+
+    // 12Bランダムノンス + 暗号文 + 認証タグを Base64 で返す
+    pub fn encrypt(&self, id: &str, plaintext: &str) -> Result<String> {
+        let cipher = ChaCha20Poly1305::new_from_slice(&self.key_for_id(id)?)
+            .map_err(|e| Error::Crypto(format!("cipher init error: {}", e)))?;
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+        let ct = cipher
+            .encrypt((&nonce).into(), plaintext.as_bytes())
+            .map_err(|e| Error::Crypto(format!("encrypt error: {}", e)))?;
+        let mut buf = Vec::with_capacity(NONCE_LEN + ct.len());
+        buf.extend_from_slice(&nonce);
+        buf.extend_from_slice(&ct);
+        Ok(B64.encode(buf))
+    }
+
+    pub fn decrypt(&self, id: &str, b64: &str) -> Result<String> {
+        let data = B64.decode(b64).map_err(|e| Error::Crypto(t!("Base64 が不正です: {}", e)))?;
+        if data.len() < NONCE_LEN { return Err(Error::Crypto(t!("データ長が不正です"))); }
+        let (nonce, ct) = data.split_at(NONCE_LEN);
+        let cipher = ChaCha20Poly1305::new_from_slice(&self.key_for_id(id)?)
+            .map_err(|e| Error::Crypto(format!("cipher init error: {}", e)))?;
+        let pt = cipher
+            .decrypt(nonce.into(), ct)
+            .map_err(|e| Error::Crypto(format!("decrypt error: {}", e)))?;
+        Ok(String::from_utf8(pt).unwrap_or_default())
+    }
+}
+
+// 暗号化前のバージョンで平文のまま保存された値と区別する（ノンスと認証タグを含む長さの Base64）
+pub fn looks_encrypted(value: &str) -> bool {
+    B64.decode(value).is_ok_and(|d| d.len() >= NONCE_LEN + TAG_LEN)
+}
@@ -0,0 +1,84 @@
+// ライブラリ全体のエラー型
+
+use std::fmt;
+
+use crate::vault::Target;
+
+#[derive(Debug)]
+pub enum Error {
+    // unlock 前に暗号化・復号が必要な操作を呼んだ
+    Locked,
+    // unlock のシークレットが保存済みの鍵確認値と一致しない
+    WrongSecret,
+    NotFound(String),
+    // id の先頭部分や条件に複数のレコードが一致した（all_matching なし）
+    Ambiguous { query: String, candidates: Vec<Target> },
+    InvalidInput(String),
+    Crypto(String),
+    Database(rusqlite::Error),
+    Io(std::io::Error),
+    Format(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Locked => f.write_str(&t!("保管庫がロックされています（unlock が必要です）")),
+            Error::WrongSecret => f.write_str(&t!("シークレットが保管庫の鍵と一致しません")),
+            Error::NotFound(what) => f.write_str(&t!("{} が見つかりません", what)),
+            Error::Ambiguous { query, candidates } => {
+                f.write_str(&t!("{} に一致するレコードが複数あります:", query))?;
+                for c in candidates {
+                    write!(f, "\n  {}", c)?;
+                }
+                Ok(())
+            }
+            Error::InvalidInput(msg) | Error::Crypto(msg) | Error::Format(msg) => write!(f, "{}", msg),
+            Error::Database(e) => f.write_str(&t!("データベースエラー: {}", e)),
+            Error::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Database(e) => Some(e),
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(e: rusqlite::Error) -> Self {
+        Error::Database(e)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<csv::Error> for Error {
+    fn from(e: csv::Error) -> Self {
+        Error::Format(t!("CSVエラー: {}", e))
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Format(t!("JSONエラー: {}", e))
+    }
+}
+
+// otp / selector などの解析エラー（String）は入力エラーとして扱う
+impl From<String> for Error {
+    fn from(msg: String) -> Self {
+        Error::InvalidInput(msg)
+    }
+}
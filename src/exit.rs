@@ -0,0 +1,92 @@
+// 終了コードと、--json 指定時にエラーとして出す種別
+//
+//   0  成功
+//   1  other            その他の失敗
+//   2  usage            引数の誤り（未知のオプション、不正な値、必須の引数の不足）
+//   3  unauthenticated  未認証・セッション期限切れ・保管庫のロックを解除できない
+//   4  auth_failed      シークレットが一致しない
+//   5  locked_out       認証の失敗が続いたため待ち時間中
+//   6  not_found        対象が見つからない
+//   7  ambiguous        対象が複数に一致した
+//   8  aborted          確認で中止した（端末でない場合を含む）
+//   9  storage          保管庫・ファイルの読み書きの失敗
+//   10 tampered         監査ログの改ざんを検出した
+//   11 invalid_input    入力値の誤り（OTPシークレット、設定値、テンプレート、env 形式で表せない値など）
+//
+// スクリプトから参照されるため、番号と名前は変えない（追加するときは新しい番号を使う）
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tsupasswd::Error;
+
+// --json（または設定 output.json）が有効なら、エラーも JSON で出す
+static JSON_ERRORS: AtomicBool = AtomicBool::new(false);
+
+pub fn set_json_errors(on: bool) {
+    JSON_ERRORS.store(on, Ordering::Relaxed);
+}
+
+pub fn json_errors() -> bool {
+    JSON_ERRORS.load(Ordering::Relaxed)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+    Other,
+    Usage,
+    InvalidInput,
+    Unauthenticated,
+    AuthFailed,
+    LockedOut,
+    NotFound,
+    Ambiguous,
+    Aborted,
+    Storage,
+    Tampered,
+}
+
+impl Failure {
+    pub fn code(self) -> i32 {
+        match self {
+            Failure::Other => 1,
+            Failure::Usage => 2,
+            Failure::Unauthenticated => 3,
+            Failure::AuthFailed => 4,
+            Failure::LockedOut => 5,
+            Failure::NotFound => 6,
+            Failure::Ambiguous => 7,
+            Failure::Aborted => 8,
+            Failure::Storage => 9,
+            Failure::Tampered => 10,
+            Failure::InvalidInput => 11,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Failure::Other => "other",
+            Failure::Usage => "usage",
+            Failure::InvalidInput => "invalid_input",
+            Failure::Unauthenticated => "unauthenticated",
+            Failure::AuthFailed => "auth_failed",
+            Failure::LockedOut => "locked_out",
+            Failure::NotFound => "not_found",
+            Failure::Ambiguous => "ambiguous",
+            Failure::Aborted => "aborted",
+            Failure::Storage => "storage",
+            Failure::Tampered => "tampered",
+        }
+    }
+
+    // ライブラリのエラーの分類
+    pub fn of(e: &Error) -> Failure {
+        match e {
+            Error::Locked => Failure::Unauthenticated,
+            Error::WrongSecret => Failure::AuthFailed,
+            Error::NotFound(_) => Failure::NotFound,
+            Error::Ambiguous { .. } => Failure::Ambiguous,
+            Error::InvalidInput(_) => Failure::InvalidInput,
+            Error::Crypto(_) | Error::Database(_) | Error::Io(_) | Error::Format(_) => Failure::Storage,
+        }
+    }
+}
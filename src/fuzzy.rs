@@ -0,0 +1,118 @@
+// あいまい検索のスコアリング（部分一致・部分列・タイプミス許容）
+
+// 1語と1項目の一致度（0.0〜1.0）。一致しなければ None
+pub fn score_term(term: &str, text: &str) -> Option<f64> {
+    let q: Vec<char> = term.to_lowercase().chars().collect();
+    let t: Vec<char> = text.to_lowercase().chars().collect();
+    if q.is_empty() || t.is_empty() { return None; }
+    if q == t { return Some(1.0); }
+    if t.starts_with(&q) { return Some(0.9); }
+    if let Some(pos) = find(&t, &q) {
+        // 単語の先頭での一致を優先
+        let at_boundary = pos == 0 || !t[pos - 1].is_alphanumeric();
+        return Some(if at_boundary { 0.8 } else { 0.7 });
+    }
+    let typo = typo_score(&q, &t);
+    let subseq = subsequence_score(&q, &t);
+    match (typo, subseq) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+fn find(hay: &[char], needle: &[char]) -> Option<usize> {
+    if needle.len() > hay.len() { return None; }
+    (0..=hay.len() - needle.len()).find(|&i| hay[i..i + needle.len()] == *needle)
+}
+
+// 文字が順番通りに現れるか（間が詰まっているほど高い）
+fn subsequence_score(q: &[char], t: &[char]) -> Option<f64> {
+    if q.len() < 2 { return None; }
+    let mut first = None;
+    let mut last = 0;
+    let mut qi = 0;
+    for (i, c) in t.iter().enumerate() {
+        if qi < q.len() && *c == q[qi] {
+            if first.is_none() { first = Some(i); }
+            last = i;
+            qi += 1;
+        }
+    }
+    if qi < q.len() { return None; }
+    let span = (last - first.unwrap_or(0) + 1) as f64;
+    // 詰まっているほど 0.6 に近づき、散らばるほど 0.3 に近づく
+    Some(0.3 + 0.3 * (q.len() as f64 / span))
+}
+
+// 単語単位の編集距離で綴り間違いを許容（4文字ごとに1文字、最大2文字）
+fn typo_score(q: &[char], t: &[char]) -> Option<f64> {
+    if q.len() < 3 { return None; }
+    let allowed = (q.len() / 4).clamp(1, 2);
+    let mut best: Option<usize> = None;
+    let words = t.split(|c| !c.is_alphanumeric()).filter(|w| !w.is_empty());
+    for w in words {
+        // 長い単語は先頭の同じ長さ程度だけを比較（前方一致の綴り間違い）
+        let candidates = [w, &w[..w.len().min(q.len())], &w[..w.len().min(q.len() + 1)]];
+        for cand in candidates {
+            let d = damerau_levenshtein(q, cand);
+            if d <= allowed && best.is_none_or(|b| d < b) { best = Some(d); }
+        }
+    }
+    best.map(|d| 0.55 - 0.15 * d as f64)
+}
+
+// 隣接文字の入れ替えを1操作とみなす編集距離
+fn damerau_levenshtein(a: &[char], b: &[char]) -> usize {
+    let (n, m) = (a.len(), b.len());
+    let mut d = vec![vec![0usize; m + 1]; n + 1];
+    for (i, row) in d.iter_mut().enumerate() { row[0] = i; }
+    for (j, cell) in d[0].iter_mut().enumerate() { *cell = j; }
+    for i in 1..=n {
+        for j in 1..=m {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[n][m]
+}
+
+// スキーム・パス・ポート・先頭の www. を除いたホスト名
+pub fn host_of(url: &str) -> &str {
+    let rest = url.split_once("://").map(|(_, r)| r).unwrap_or(url);
+    let rest = rest.rsplit_once('@').map(|(_, r)| r).unwrap_or(rest);
+    let host = rest.split(['/', '?', '#', ':']).next().unwrap_or(rest);
+    host.strip_prefix("www.").unwrap_or(host)
+}
+
+// 重み付きの項目。タイトルとドメインを備考より優先する
+pub struct Field<'a> {
+    pub text: &'a str,
+    pub weight: f64,
+}
+
+pub const WEIGHT_TITLE: f64 = 3.0;
+pub const WEIGHT_DOMAIN: f64 = 3.0;
+pub const WEIGHT_USERNAME: f64 = 2.0;
+pub const WEIGHT_URL: f64 = 1.5;
+pub const WEIGHT_NOTE: f64 = 1.0;
+
+// 空白区切りの各語がいずれかの項目に一致した場合のみスコアを返す。
+// age_days は最終更新からの経過日数で、新しいほど少し加点する
+pub fn score_record(query: &str, fields: &[Field], age_days: f64) -> Option<f64> {
+    let mut total = 0.0;
+    let mut terms = 0;
+    for term in query.split_whitespace() {
+        let best = fields
+            .iter()
+            .filter_map(|f| score_term(term, f.text).map(|s| s * f.weight))
+            .fold(None, |acc: Option<f64>, s| Some(acc.map_or(s, |a| a.max(s))))?;
+        total += best;
+        terms += 1;
+    }
+    if terms == 0 { return None; }
+    let recency = 0.3 * (-age_days.max(0.0) / 90.0).exp();
+    Some(total / terms as f64 + recency)
+}
@@ -0,0 +1,94 @@
+// パスワード生成（OsRng によるリジェクションサンプリングと Fisher-Yates シャッフル）
+
+use rand::rngs::OsRng;
+use rand::RngCore;
+
+// 記号を含む安全な文字集合
+const UPPER: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const LOWER: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+const DIGIT: &[u8] = b"0123456789";
+const SYMBOL: &[u8] = b"!@#$%^&*()-_=+[]{};:,.?/"; // スペースやバックスラッシュ、`'"` は除外
+
+// 使う文字の種類
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Policy {
+    #[default]
+    Alnum,   // 英大文字・英小文字・数字
+    Symbols, // Alnum に記号を加える
+    Digits,  // 数字のみ（PIN 用）
+}
+
+impl Policy {
+    pub const NAMES: &[&str] = &["alnum", "symbols", "digits"];
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "alnum" => Some(Policy::Alnum),
+            "symbols" => Some(Policy::Symbols),
+            "digits" => Some(Policy::Digits),
+            _ => None,
+        }
+    }
+
+    fn categories(&self) -> &'static [&'static [u8]] {
+        match self {
+            Policy::Alnum => &[UPPER, LOWER, DIGIT],
+            Policy::Symbols => &[UPPER, LOWER, DIGIT, SYMBOL],
+            Policy::Digits => &[DIGIT],
+        }
+    }
+}
+
+pub fn generate_password(len: usize) -> String {
+    generate_password_with(len, Policy::Alnum)
+}
+
+pub fn generate_password_with(len: usize, policy: Policy) -> String {
+    // 総合アルファベット
+    let categories = policy.categories();
+    let alphabet: Vec<u8> = categories.concat();
+
+    if len == 0 {
+        return String::new();
+    }
+
+    // 少なくとも各カテゴリから1文字ずつ確保（ただし必要な長さを超えない）
+    let mut bytes: Vec<u8> = Vec::with_capacity(len);
+    for cat in categories {
+        if bytes.len() >= len { break; }
+        let idx = rand_index(cat.len());
+        bytes.push(cat[idx]);
+    }
+
+    // 残りは全アルファベットからランダムに
+    while bytes.len() < len {
+        let idx = rand_index(alphabet.len());
+        bytes.push(alphabet[idx]);
+    }
+
+    // シャッフルして先頭にカテゴリ固定が来ないようにする
+    fisher_yates_shuffle(&mut bytes);
+
+    String::from_utf8(bytes).unwrap_or_default()
+}
+
+fn rand_index(len: usize) -> usize {
+    // OsRngからu64を取り出し、範囲に収まるようにリジェクションサンプリング
+    if len <= 1 { return 0; }
+    let bound = len as u64;
+    let zone = u64::MAX - (u64::MAX % bound);
+    loop {
+        let v = OsRng.next_u64();
+        if v < zone {
+            return (v % bound) as usize;
+        }
+    }
+}
+
+fn fisher_yates_shuffle(data: &mut [u8]) {
+    if data.len() <= 1 { return; }
+    for i in (1..data.len()).rev() {
+        let j = rand_index(i + 1);
+        data.swap(i, j);
+    }
+}
@@ -0,0 +1,597 @@
+// 表示言語とメッセージカタログ
+//
+// メッセージは日本語の文をそのままキーにして書き（t!("見つかりませんでした: url={}", url)）、
+// 英語のときは CATALOG の訳を使う。訳のない文や {} の数が合わない文は t! がコンパイルエラーにする。
+// clap のヘルプ（cli.rs のドキュメントコメント）と設定キーの説明は実行時に lookup / tr で引く。
+// ライブラリのエラー文もこのカタログで訳し、バイナリ側は #[macro_use] extern crate tsupasswd で t! を使う
+//
+// 言語は設定 ui.language（auto / ja / en）で選ぶ。auto では LC_ALL → LC_MESSAGES → LANG の
+// 最初に空でない値を見て、ja_* と C / POSIX（未設定を含む）は日本語、それ以外は英語にする
+
+use std::fmt::{Display, Write};
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lang {
+    Ja,
+    En,
+}
+
+pub const CHOICES: &[&str] = &["auto", "ja", "en"];
+
+// 0 は未決定（最初に使うときに環境変数から決める）
+static SELECTED: AtomicU8 = AtomicU8::new(0);
+
+// 設定 ui.language の値で言語を決める
+pub fn select(setting: &str) {
+    let lang = match setting {
+        "ja" => Lang::Ja,
+        "en" => Lang::En,
+        _ => from_env(),
+    };
+    SELECTED.store(lang as u8 + 1, Ordering::Relaxed);
+}
+
+pub fn lang() -> Lang {
+    match SELECTED.load(Ordering::Relaxed) {
+        1 => Lang::Ja,
+        2 => Lang::En,
+        _ => {
+            let lang = from_env();
+            SELECTED.store(lang as u8 + 1, Ordering::Relaxed);
+            lang
+        }
+    }
+}
+
+fn from_env() -> Lang {
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|v| !v.is_empty())
+        .unwrap_or_default();
+    let source = locale.is_empty() || locale == "C" || locale.starts_with("C.") || locale == "POSIX";
+    if source || locale.starts_with("ja") { Lang::Ja } else { Lang::En }
+}
+
+// 日本語の文を書式として、引数を順に埋めた文字列（format! と同じ使い方）
+#[macro_export]
+macro_rules! t {
+    ($msg:literal $(, $arg:expr)* $(,)?) => {
+        $crate::i18n::message(
+            const { $crate::i18n::entry($msg, 0 $(+ $crate::i18n::one(stringify!($arg)))*) },
+            &[$(&$arg as &dyn ::std::fmt::Display),*],
+        )
+    };
+}
+
+#[doc(hidden)]
+pub const fn one(_: &str) -> usize {
+    1
+}
+
+// t! の展開先でコンパイル時に評価する。カタログでの位置を返す
+#[doc(hidden)]
+pub const fn entry(msg: &str, args: usize) -> usize {
+    let mut i = 0;
+    while i < CATALOG.len() {
+        if same(CATALOG[i].0, msg) {
+            assert!(placeholders(CATALOG[i].0) == args, "引数の数が {{}} の数と一致しません");
+            assert!(placeholders(CATALOG[i].1) == args, "訳の {{}} の数が原文と一致しません");
+            return i;
+        }
+        i += 1;
+    }
+    panic!("メッセージカタログに訳がありません")
+}
+
+const fn same(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() { return false; }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] { return false; }
+        i += 1;
+    }
+    true
+}
+
+const fn placeholders(s: &str) -> usize {
+    let s = s.as_bytes();
+    let (mut i, mut n) = (0, 0);
+    while i + 1 < s.len() {
+        if s[i] == b'{' && s[i + 1] == b'}' {
+            n += 1;
+            i += 1;
+        }
+        i += 1;
+    }
+    n
+}
+
+// 同じ原文が2回登録されていないこと
+const _: () = {
+    let mut i = 0;
+    while i < CATALOG.len() {
+        let mut j = i + 1;
+        while j < CATALOG.len() {
+            assert!(!same(CATALOG[i].0, CATALOG[j].0), "メッセージカタログに重複があります");
+            j += 1;
+        }
+        i += 1;
+    }
+};
+
+#[doc(hidden)]
+pub fn message(index: usize, args: &[&dyn Display]) -> String {
+    let (ja, en) = CATALOG[index];
+    let template = if lang() == Lang::En { en } else { ja };
+    let mut out = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut rest = template;
+    while let Some(at) = rest.find("{}") {
+        out.push_str(&rest[..at]);
+        if let Some(arg) = args.next() {
+            let _ = write!(out, "{}", arg);
+        }
+        rest = &rest[at + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+// 実行時に決まる文の英訳。日本語のとき・訳がないときは None
+pub fn lookup(text: &str) -> Option<&'static str> {
+    if lang() != Lang::En { return None; }
+    CATALOG.iter().find(|(ja, _)| *ja == text).map(|(_, en)| *en)
+}
+
+// 表示言語での文（訳がなければそのまま）
+pub fn tr(text: &'static str) -> &'static str {
+    lookup(text).unwrap_or(text)
+}
+
+// (日本語, 英語)
+const CATALOG: &[(&str, &str)] = &[
+    // error.rs
+    ("保管庫がロックされています（unlock が必要です）", "the vault is locked (run unlock)"),
+    ("シークレットが保管庫の鍵と一致しません", "the secret does not match the vault key"),
+    ("{} が見つかりません", "{} not found"),
+    ("{} に一致するレコードが複数あります:", "more than one record matches {}:"),
+    ("データベースエラー: {}", "database error: {}"),
+    ("CSVエラー: {}", "CSV error: {}"),
+    ("JSONエラー: {}", "JSON error: {}"),
+    // vault.rs
+    ("id={} が見つかりません（先頭部分で指定する場合は{}文字以上）", "id={} not found (an id prefix needs at least {} characters)"),
+    ("id の先頭 {}", "id prefix {}"),
+    ("条件（{}）に一致するレコード", "a record matching ({})"),
+    ("条件（{}）", "({})"),
+    ("ゴミ箱の id={}", "id={} in the trash"),
+    ("{} がありません", "{} is missing"),
+    // otp.rs
+    ("未対応のアルゴリズムです: {}", "unsupported algorithm: {}"),
+    ("otpauth URI の形式が不正です", "malformed otpauth URI"),
+    ("未対応の OTP 種別です: {}", "unsupported OTP type: {}"),
+    ("digits が不正です: {}", "invalid digits: {}"),
+    ("period が不正です: {}", "invalid period: {}"),
+    ("period は 1 以上で指定してください", "period must be 1 or more"),
+    ("counter が不正です: {}", "invalid counter: {}"),
+    ("未対応のエンコーダです: {}", "unsupported encoder: {}"),
+    ("otpauth URI に secret がありません", "the otpauth URI has no secret"),
+    ("hotp の URI には counter が必要です", "hotp URIs need a counter"),
+    ("Steam 形式は TOTP のみ対応しています", "the Steam format supports TOTP only"),
+    ("digits は 6〜8 で指定してください: {}", "digits must be from 6 to 8: {}"),
+    ("base32 として不正な文字です: {}", "invalid base32 character: {}"),
+    ("シークレットが空です", "the secret is empty"),
+    ("URI のエスケープが不正です", "invalid escape in the URI"),
+    ("URI が UTF-8 ではありません", "the URI is not UTF-8"),
+    // selector.rs
+    ("条件の値が空です: {}", "empty condition value: {}"),
+    ("未知の条件です: {}（url: / user: / title: / folder: / tag: のいずれか）", "unknown condition: {} (one of url: / user: / title: / folder: / tag:)"),
+    ("対象の id または条件（url:… user:… など）を指定してください", "give the target id or conditions (url:... user:... etc.)"),
+    ("id は1つだけ指定し、条件（url:… user:… など）と組み合わせないでください", "give a single id and do not combine it with conditions (url:... user:... etc.)"),
+    // store.rs
+    ("この保存先は全文検索（--fts）に対応していません", "this storage does not support full-text search (--fts)"),
+    ("id={} の HOTP カウンタ", "HOTP counter of id={}"),
+    ("保管庫ファイルの形式が不正です: {}", "malformed vault file: {}"),
+    ("検索式が不正です: {}", "invalid search expression: {}"),
+    // auth.rs
+    ("verifier が壊れています: {}", "the verifier is corrupted: {}"),
+    ("環境変数 AUTH_SECRET が未設定です", "the AUTH_SECRET environment variable is not set"),
+    ("verifier を保存できません: {}", "cannot save the verifier: {}"),
+    ("試行回数を保存できません: {}", "cannot save the attempt count: {}"),
+    ("認証の状態をロックできません: {}", "cannot lock the authentication state: {}"),
+    // audit.rs
+    ("監査ログを読めません: {}", "cannot read the audit log: {}"),
+    ("{} 行目を解釈できません: {}", "cannot parse line {}: {}"),
+    ("{} 行目: 直前の記録との連鎖が一致しません（削除または挿入）", "line {}: the chain to the previous record does not match (deleted or inserted)"),
+    ("{} 行目: 内容がハッシュと一致しません（書き換え）", "line {}: the content does not match its hash (rewritten)"),
+    ("末尾の記録が失われています（記録 {} 件、ログ {} 件）", "records at the end are missing ({} recorded, {} in the log)"),
+    // site.rs
+    ("--match は exact / host / domain のいずれかを指定してください: {}", "--match must be one of exact / host / domain: {}"),
+    // private_file.rs
+    ("ファイル名がありません", "no file name"),
+    // crypto.rs
+    ("鍵導出に失敗しました", "key derivation failed"),
+    ("Base64 が不正です: {}", "invalid Base64: {}"),
+    ("データ長が不正です", "invalid data length"),
+    // main.rs
+    ("警告: {}", "warning: {}"),
+    ("補完スクリプトを出力できません: {}", "cannot write the completion script: {}"),
+    ("エクスポートに失敗しました: {}", "export failed: {}"),
+    ("エクスポート完了: {}", "Exported: {}"),
+    ("インポートに失敗しました: {}", "import failed: {}"),
+    ("インポート完了: {}", "Imported: {}"),
+    ("認証の失敗が続いたためロック中です。{} 秒後に再試行してください", "Locked out after repeated authentication failures. Try again in {} seconds"),
+    ("認証に失敗しました", "Authentication failed"),
+    ("認証シークレット", "Authentication secret"),
+    ("セッション開始に失敗しました: {}", "failed to start the session: {}"),
+    ("認証しました: 有効期限 {} 分", "Authenticated: session valid for {} minutes"),
+    ("ログアウトに失敗しました: {}", "failed to log out: {}"),
+    ("ログアウトしました", "Logged out"),
+    ("認証済み: 残り {} 秒", "Authenticated: {} seconds left"),
+    ("保管庫: {}（{}）", "Vault: {} ({})"),
+    ("未認証です（保管庫: {}）", "Not authenticated (vault: {})"),
+    ("状態取得に失敗しました: {}", "failed to read the session state: {}"),
+    ("{} = {}（{}）", "{} = {} ({})"),
+    ("（既定）", " (default)"),
+    ("OTPシークレットが不正です: {}", "invalid OTP secret: {}"),
+    (
+        "長さの指定と --prompt / --password-stdin / --secret-fd は同時に指定できません",
+        "a length cannot be combined with --prompt / --password-stdin / --secret-fd",
+    ),
+    ("パスワード", "Password"),
+    ("保存に失敗しました: {}", "failed to save: {}"),
+    ("保存しました: url={} username={}", "Saved: url={} username={}"),
+    ("--copy と --json は同時に指定できません", "--copy cannot be combined with --json"),
+    ("見つかりませんでした: url={}", "Not found: url={}"),
+    ("クリップボードへのコピーに失敗しました: {}", "failed to copy to the clipboard: {}"),
+    ("コピーしました: username={}", "Copied: username={}"),
+    ("--field / --format と --json は同時に指定できません", "--field / --format cannot be combined with --json"),
+    ("フィールドがありません: {}", "No such field: {}"),
+    (
+        "改行を含む値は env 形式で出力できません（shell / dotenv を使ってください）: {}",
+        "values containing newlines cannot be written in env format (use shell / dotenv): {}",
+    ),
+    (
+        "フィールド {} と {} がどちらも変数 {} になるため出力できません（--field で個別に取り出してください）",
+        "fields {} and {} would both become the variable {} (use --field to get them one at a time)",
+    ),
+    ("環境変数 {} が重複しています", "environment variable {} is given more than once"),
+    ("{} の参照を解決できません: {}", "cannot resolve the reference for {}: {}"),
+    ("{} の参照を解決できません: フィールドがありません: {}", "cannot resolve the reference for {}: no such field: {}"),
+    ("コマンドを実行できません: {}: {}", "cannot run the command: {}: {}"),
+    ("セッションの有効期限が大きすぎます: {} 分", "session lifetime is too long: {} minutes"),
+    ("テンプレートを読み込めません: {}: {}", "cannot read the template: {}: {}"),
+    ("{}行目: {}", "line {}: {}"),
+    ("{}行目: フィールドがありません: {}", "line {}: no such field: {}"),
+    ("解決できない参照があります:\n{}", "unresolved references:\n{}"),
+    ("書き出せません: {}: {}", "cannot write: {}: {}"),
+    ("書き出しました: {}", "Wrote {}"),
+    ("（{}秒後に消去）", " (cleared in {} seconds)"),
+    ("検索に失敗しました: {}", "search failed: {}"),
+    ("TUIの実行に失敗しました: {}", "TUI failed: {}"),
+    (
+        "検索語を指定してください（--tag のみで絞り込む場合は省略可。--fuzzy / --fts では必須）",
+        "specify a keyword (optional when filtering by --tag only; required with --fuzzy / --fts)",
+    ),
+    ("見つかりませんでした: keyword={}", "Not found: keyword={}"),
+    ("不明な列です: {}（指定可能: {}）", "unknown column: {} (available: {})"),
+    ("--columns に列を指定してください", "specify at least one column for --columns"),
+    ("password 列を表示するには --show-passwords を指定してください", "the password column requires --show-passwords"),
+    ("一覧の取得に失敗しました: {}", "failed to list entries: {}"),
+    ("JSONエンコードに失敗しました", "failed to encode JSON"),
+    ("OTPが登録されていません: url={}", "No OTP registered: url={}"),
+    ("OTPシークレットが不正です: username={} {}", "invalid OTP secret: username={} {}"),
+    ("カウンタの更新に失敗しました: {}", "failed to update the counter: {}"),
+    (
+        "--length と --password / --prompt / --password-stdin / --secret-fd は同時に指定できません",
+        "--length cannot be combined with --password / --prompt / --password-stdin / --secret-fd",
+    ),
+    ("更新内容が指定されていません", "nothing to update"),
+    ("更新", "update"),
+    (
+        "複数のレコードに同じパスワードは設定できません（1件ずつ更新してください）",
+        "cannot set the same password on several entries (update them one at a time)",
+    ),
+    ("更新する {} 件（{}）:\n{}", "Updating {} entries ({}):\n{}"),
+    ("{} 件を更新しますか？", "Update {} entries?"),
+    ("更新を中止しました（確認なしで更新するには --yes を指定してください）", "Update aborted (use --yes to update without confirmation)"),
+    ("更新に失敗しました: {}", "update failed: {}"),
+    ("更新しました: id={}", "Updated: id={}"),
+    ("監査ログは改ざんされていません: {} 件", "Audit log is intact: {} entries"),
+    ("監査ログの改ざんを検出しました: {}", "audit log tampering detected: {}"),
+    ("削除", "delete"),
+    ("ゴミ箱へ移動する {} 件:\n{}", "Moving {} entries to the trash:\n{}"),
+    ("url=\"{}\" username=\"{}\" をゴミ箱へ移動しますか？", "Move url=\"{}\" username=\"{}\" to the trash?"),
+    ("{} 件をゴミ箱へ移動しますか？", "Move {} entries to the trash?"),
+    ("削除を中止しました（確認なしで削除するには --yes を指定してください）", "Delete aborted (use --yes to delete without confirmation)"),
+    ("削除に失敗しました: {}", "delete failed: {}"),
+    (
+        "ゴミ箱へ移動しました: id={} url=\"{}\" username=\"{}\"（tsupasswd trash restore {} で復元）",
+        "Moved to the trash: id={} url=\"{}\" username=\"{}\" (restore with tsupasswd trash restore {})",
+    ),
+    ("ゴミ箱の取得に失敗しました: {}", "failed to read the trash: {}"),
+    ("ゴミ箱は空です", "The trash is empty"),
+    ("復元に失敗しました: {}", "restore failed: {}"),
+    ("復元しました: id={}", "Restored: id={}"),
+    ("完全削除に失敗しました: id={} {}", "purge failed: id={} {}"),
+    ("完全に削除しました: {} 件", "Purged: {} entries"),
+    ("監査ログに記録できません: {}", "cannot write the audit log: {}"),
+    ("セッションが期限切れです。`tsupasswd auth <secret>` を実行してください", "The session has expired. Run `tsupasswd auth <secret>`"),
+    ("未認証です。`tsupasswd auth <secret>` を実行してください", "Not authenticated. Run `tsupasswd auth <secret>`"),
+    ("認証状態の確認に失敗しました: {}", "failed to check the session: {}"),
+    ("JSONエンコードに失敗しました: {}", "failed to encode JSON: {}"),
+    ("保管庫ファイル（.{}）を開くには AUTH_SECRET が必要です", "AUTH_SECRET is required to open a vault file (.{})"),
+    ("DB初期化に失敗しました: {}", "failed to open the database: {}"),
+    ("保管庫のロックを解除できません: {}", "cannot unlock the vault: {}"),
+    (
+        "{}に失敗しました: 条件に {} 件が一致しました（すべてを対象にするには --all-matching を指定）:\n{}",
+        "cannot {}: {} entries match (use --all-matching to target all of them):\n{}",
+    ),
+    ("{}に失敗しました: {}", "{} failed: {}"),
+    // cli.rs
+    ("使い方:", "Usage:"),
+    ("コマンド:", "Commands:"),
+    ("引数:", "Arguments:"),
+    ("オプション:", "Options:"),
+    ("コマンド", "COMMAND"),
+    ("このヘルプを表示", "Print this help"),
+    ("長さとコマンドは同時に指定できません", "a length cannot be combined with a command"),
+    ("--secret は --field の後に指定してください", "--secret must follow a --field"),
+    ("未対応のシェルです: {}", "unsupported shell: {}"),
+    ("name=value の形式で指定してください", "expected name=value"),
+    ("1以上の整数を指定してください", "expected an integer of 1 or more"),
+    ("0以上の整数を指定してください", "expected an integer of 0 or more"),
+    ("1〜{} の分数を指定してください", "expected a number of minutes from 1 to {}"),
+    ("秒数を指定してください", "expected a number of seconds"),
+    ("数値を指定してください", "expected a number"),
+    ("exact / host / domain のいずれかを指定してください", "expected one of exact / host / domain"),
+    ("created / updated / url / title のいずれかを指定してください", "expected one of created / updated / url / title"),
+    ("2024-01-31 / RFC3339 / 30m・24h・7d のいずれかで指定してください", "expected 2024-01-31, RFC3339 or 30m / 24h / 7d"),
+    ("30d・24h・90m などで指定してください", "expected a duration such as 30d, 24h or 90m"),
+    ("パスワードの生成・保存・取得（コマンドを省略するとパスワードを生成して表示）", "Generate, store and retrieve passwords (without a command, print a generated password)"),
+    (
+        "環境変数:\n  \
+        AUTH_SECRET           認証用シークレット（tsupasswd auth で使用）\n  \
+        TSUPASSWD_VAULT       --vault を省略したときの保管庫（名前またはパス）\n  \
+        TSUPASSWD_CONFIG      設定ファイルのパス（既定 ~/.config/tsupasswd/config.toml）\n  \
+        XDG_DATA_HOME         保管庫の置き場所（既定 ~/.local/share/tsupasswd）\n  \
+        XDG_STATE_HOME        セッション・監査ログの置き場所（既定 ~/.local/state/tsupasswd）\n  \
+        TSUPASSWD_CLIPBOARD   クリップボードの種類（auto / wayland / x11 / osc52）\n  \
+        TSUPASSWD_ENCODING    出力エンコーディングを指定（utf8 / sjis）。Windowsでのリダイレクト時に有効\n  \
+        LANG / LC_MESSAGES    表示言語（設定 ui.language が auto のとき。LC_ALL が優先、ja_* で日本語）",
+        "Environment:\n  \
+        AUTH_SECRET           Authentication secret (used by tsupasswd auth)\n  \
+        TSUPASSWD_VAULT       Vault used when --vault is omitted (name or path)\n  \
+        TSUPASSWD_CONFIG      Config file path (default ~/.config/tsupasswd/config.toml)\n  \
+        XDG_DATA_HOME         Where vaults are stored (default ~/.local/share/tsupasswd)\n  \
+        XDG_STATE_HOME        Where sessions and audit logs are stored (default ~/.local/state/tsupasswd)\n  \
+        TSUPASSWD_CLIPBOARD   Clipboard backend (auto / wayland / x11 / osc52)\n  \
+        TSUPASSWD_ENCODING    Output encoding (utf8 / sjis), used when redirecting on Windows\n  \
+        LANG / LC_MESSAGES    Display language when the ui.language setting is auto (LC_ALL takes precedence; ja_* for Japanese)",
+    ),
+    ("名前|パス", "NAME|PATH"),
+    ("長さ", "LENGTH"),
+    ("ID|条件", "ID|CONDITION"),
+    (
+        "使用する保管庫（例: --vault work）。名前ごとにセッション・監査ログが分かれ、拡張子 .vault のパスは暗号化ファイル1つに保存する",
+        "Vault to use (e.g. --vault work). Each name has its own session and audit log; a path ending in .vault is stored as a single encrypted file",
+    ),
+    ("出力とエラーを JSON 形式にする（export / import ではファイル形式も JSON）", "Print output and errors as JSON (export / import also use JSON files)"),
+    ("生成するパスワードの文字数（省略時は config の generate.length、既定 16）", "Length of the generated password (defaults to generate.length in the config, 16)"),
+    ("レコードを保存（パスワード省略時は生成）", "Save an entry (generates a password if omitted)"),
+    ("URL でレコードを検索してユーザ名・パスワード等を表示", "Look up entries by URL and print the username, password and details"),
+    ("登録済みの OTP シークレットから現在のコードを表示（HOTP はカウンタを1進める）", "Print the current code from the stored OTP secret (HOTP advances the counter)"),
+    ("url / username / title / note を検索", "Search url / username / title / note"),
+    ("一覧を表示（--show-passwords を指定しない限りパスワードは表示しない）", "List entries (passwords are hidden unless --show-passwords is given)"),
+    ("レコードを更新", "Update entries"),
+    ("レコードをゴミ箱へ移動（確認あり。端末でない場合は --yes が必要）", "Move entries to the trash (asks for confirmation; --yes is required without a terminal)"),
+    ("ゴミ箱の一覧・復元・完全削除", "List, restore or purge trashed entries"),
+    ("全画面で一覧・絞り込み・編集・削除", "Browse, filter, edit and delete entries full-screen"),
+    ("CSV（--json では任意フィールドを含む JSON 配列）へ書き出す", "Export to CSV (with --json, a JSON array including custom fields)"),
+    ("CSV（--json では JSON 配列）から取り込む", "Import from CSV (with --json, a JSON array)"),
+    ("認証してセッションを開始（シークレット省略時は端末からエコーなしで入力）", "Authenticate and start a session (prompts without echo if the secret is omitted)"),
+    ("セッションを終了", "End the session"),
+    ("セッションの残り時間と使用中の保管庫を表示", "Show the remaining session time and the vault in use"),
+    ("監査ログを表示・検証", "Show or verify the audit log"),
+    ("設定ファイルの既定値を表示・変更", "Show or change defaults in the config file"),
+    ("シェルの補完スクリプトを出力（例: source <(tsupasswd completions bash)）", "Print a shell completion script (e.g. source <(tsupasswd completions bash))"),
+    ("対象のシェル", "Target shell"),
+    ("get --copy が起動する消去用の子プロセス（内部用）", "Clipboard clearing child process started by get --copy (internal)"),
+    ("サイトURL等の識別子", "Site URL or other identifier"),
+    ("ユーザ名", "Username"),
+    ("文字列を指定するとそのまま保存（警告あり）、数値を指定するとその長さで生成", "A string is stored as is (with a warning); a number generates a password of that length"),
+    ("タイトル", "Title"),
+    ("備考", "Note"),
+    ("OTPシークレット（base32、otpauth://totp|hotp/... または steam://<base32>）", "OTP secret (base32, otpauth://totp|hotp/... or steam://<base32>)"),
+    ("フォルダ（例: work/aws）", "Folder (e.g. work/aws)"),
+    ("タグ（複数指定可、カンマ区切り可）", "Tag (repeatable, comma-separated)"),
+    ("任意フィールド（複数指定可。PIN、秘密の質問、リカバリコード等）", "Custom field (repeatable; PINs, security questions, recovery codes, ...)"),
+    ("直前の --field を暗号化して保存", "Encrypt the preceding --field"),
+    ("パスワードを端末からエコーなしで入力（確認のため2回）", "Enter the password on the terminal without echo (twice to confirm)"),
+    ("パスワードを標準入力から読む", "Read the password from standard input"),
+    ("パスワードをファイルディスクリプタ N から読む", "Read the password from file descriptor N"),
+    ("サイトURL", "Site URL"),
+    ("照合方法 exact（入力どおり） / host（ホスト名） / domain（登録可能ドメイン）", "Matching: exact (as typed) / host (host name) / domain (registrable domain)"),
+    ("パスワードを表示せずクリップボードにコピー", "Copy the password to the clipboard instead of printing it"),
+    (
+        "コピーした値がまだ残っていれば SECS 秒後に消去（0 で消去しない。既定は config の clipboard.clear_after）",
+        "Clear the copied value after SECS seconds if still present (0 keeps it; defaults to clipboard.clear_after in the config)",
+    ),
+    (
+        "先頭のレコードの1項目だけをそのまま出力（url / username / password / title / note / 任意フィールド名）",
+        "Print just one item of the first entry, unquoted (url / username / password / title / note / a custom field name)",
+    ),
+    (
+        "先頭のレコードを shell / env / dotenv 形式、または {username}:{password} のようなテンプレートで出力",
+        "Print the first entry as shell / env / dotenv, or through a template such as {username}:{password}",
+    ),
+    ("--field / --format の出力の末尾に改行を付けない", "Do not print a trailing newline after --field / --format output"),
+    ("保管庫の値を環境変数に入れてコマンドを実行（値は表示しない）", "Run a command with vault values in its environment (values are never printed)"),
+    (
+        "参照:\n  \
+        url:<URL>#<項目>  get と同じ照合で見つかった先頭のレコードの項目（項目は get --field と同じ。省略時は password）\n\n\
+        例: tsupasswd run --env DB_PASS=url:db.internal#password -- ./deploy.sh",
+        "References:\n  \
+        url:<URL>#<ITEM>  an item of the first entry matched as in get (items as for get --field; password if omitted)\n\n\
+        Example: tsupasswd run --env DB_PASS=url:db.internal#password -- ./deploy.sh",
+    ),
+    ("子プロセスに渡す環境変数と値の参照（複数指定可）", "Environment variable for the child and the reference to its value (repeatable)"),
+    ("子プロセスの標準出力・標準エラーに現れた値を <redacted> に置き換える", "Replace the values with <redacted> in the child's stdout / stderr"),
+    ("実行するコマンドと引数（-- の後に指定）", "Command to run and its arguments (after --)"),
+    (
+        "テンプレートの {{ tsupasswd \"URL\" \"項目\" }} を保管庫の値に置き換えて書き出す",
+        "Render a template, replacing {{ tsupasswd \"URL\" \"ITEM\" }} with vault values",
+    ),
+    (
+        "テンプレート:\n  \
+        {{ tsupasswd \"<URL>\" \"<項目>\" }}  get と同じ照合で見つかった先頭のレコードの項目に置き換える\n  \
+        （項目は get --field と同じ。省略時は password）。それ以外の {{ ... }} はそのまま残す\n\n\
+        例: tsupasswd inject -i config.tmpl -o config.yaml",
+        "Template:\n  \
+        {{ tsupasswd \"<URL>\" \"<ITEM>\" }}  replaced with an item of the first entry matched as in get\n  \
+        (items as for get --field; password if omitted). Any other {{ ... }} is left as is\n\n\
+        Example: tsupasswd inject -i config.tmpl -o config.yaml",
+    ),
+    ("テンプレートのファイル（省略時は標準入力）", "Template file (standard input if omitted)"),
+    ("書き出すファイル（パーミッション 0600。省略時は標準出力）", "File to write (mode 0600; standard output if omitted)"),
+    ("NAME=url:<URL>#<項目> の形式で指定してください", "expected NAME=url:<URL>#<ITEM>"),
+    ("環境変数名は英字または _ で始まる英数字と _ にしてください: {}", "environment variable names must be letters, digits and _, not starting with a digit: {}"),
+    ("検索語（--tag 指定時は省略可）", "Keyword (optional with --tag)"),
+    ("タグで絞り込み", "Filter by tag"),
+    ("あいまい検索（綴り間違いを許容し、タイトル・ドメインの一致や新しさで順位付け）", "Fuzzy search (tolerates typos; ranks by title / domain matches and recency)"),
+    (
+        "全文検索索引で検索（AND / OR / NOT、\"フレーズ\"、前方一致 foo*、title:foo。3文字以上の語）",
+        "Search the full-text index (AND / OR / NOT, \"phrase\", prefix foo*, title:foo; terms of 3+ characters)",
+    ),
+    ("最大 N 件を表示（--fuzzy の既定は 10）", "Show at most N entries (default 10 with --fuzzy)"),
+    ("フォルダ（配下のフォルダを含む）で絞り込み", "Filter by folder (including subfolders)"),
+    ("並び順 created / updated / url / title（既定 created の昇順）", "Sort by created / updated / url / title (default: created, ascending)"),
+    ("降順に並べる", "Sort in descending order"),
+    ("最大 N 件を表示", "Show at most N entries"),
+    ("先頭 M 件を読み飛ばす", "Skip the first M entries"),
+    ("表示する列（id,url,username,title,note,folder,tags,created_at,updated_at,password）", "Columns to show (id,url,username,title,note,folder,tags,created_at,updated_at,password)"),
+    ("パスワードを復号して表示", "Decrypt and show passwords"),
+    (
+        "対象の指定:\n  \
+        id は一意であれば先頭4文字以上で指定可能（例: 3f2a）。複数に一致する場合は候補を表示して中止\n  \
+        条件: url:example.com（部分一致） user:alice（完全一致） title:T（部分一致） folder:F（配下を含む） tag:T\n  \
+        複数の条件はすべてを満たすもの。2件以上に一致する場合は --all-matching が必要",
+        "Targets:\n  \
+        An id may be shortened to a unique prefix of 4+ characters (e.g. 3f2a); ambiguous prefixes list candidates and abort\n  \
+        Conditions: url:example.com (substring) user:alice (exact) title:T (substring) folder:F (with subfolders) tag:T\n  \
+        Multiple conditions must all match. --all-matching is required when 2 or more entries match",
+    ),
+    ("対象の id または条件", "Target id or conditions"),
+    ("URL を更新", "New URL"),
+    ("ユーザ名を更新", "New username"),
+    ("パスワードをこの文字列に更新（ps や履歴に残るため --prompt 等を推奨）", "New password (visible in ps and shell history; prefer --prompt etc.)"),
+    ("ランダムに N 文字のパスワードを生成して更新", "Replace the password with a random one of N characters"),
+    ("タイトルを更新", "New title"),
+    ("備考を更新", "New note"),
+    ("OTPシークレットを更新（base32、otpauth:// URI または steam://）", "New OTP secret (base32, otpauth:// URI or steam://)"),
+    ("任意フィールドを削除", "Remove a custom field"),
+    ("フォルダを変更（空文字で解除）", "Move to a folder (empty string to clear)"),
+    ("タグを追加", "Add a tag"),
+    ("タグを外す", "Remove a tag"),
+    ("対象の指定は update と同じ（tsupasswd update --help）", "Targets are specified as for update (tsupasswd update --help)"),
+    ("条件に一致したすべてのレコードを対象にする（対象を表示して確認）", "Target every entry matching the conditions (lists them and asks)"),
+    ("対象を表示するだけで変更しない", "Only show the targets; change nothing"),
+    ("確認を省略", "Skip confirmation"),
+    ("ゴミ箱の一覧", "List trashed entries"),
+    ("ゴミ箱から復元", "Restore an entry from the trash"),
+    ("レコードの id", "Entry id"),
+    ("ゴミ箱のレコードを完全削除", "Permanently delete trashed entries"),
+    ("削除から指定期間が過ぎたものだけを完全削除（例: 30d・24h・90m）", "Only purge entries trashed longer ago than AGE (e.g. 30d, 24h, 90m)"),
+    ("ファイルのパス", "File path"),
+    ("認証シークレット（ps や履歴に残るため --password-stdin 等を推奨）", "Authentication secret (visible in ps and shell history; prefer --password-stdin etc.)"),
+    ("シークレットを標準入力から読む", "Read the secret from standard input"),
+    ("シークレットをファイルディスクリプタ N から読む", "Read the secret from file descriptor N"),
+    ("セッション有効期限（分）。既定は config の session.ttl（30）", "Session lifetime in minutes (defaults to session.ttl in the config, 30)"),
+    ("指定日時以降のみ（2024-01-31 / RFC3339 / 30m・24h・7d）", "Only records since WHEN (2024-01-31 / RFC3339 / 30m, 24h, 7d)"),
+    ("操作名で絞り込み（例: get）", "Filter by operation (e.g. get)"),
+    ("ハッシュ連鎖を検証し、書き換え・削除があれば終了コード 10", "Verify the hash chain; exit with code 10 if records were altered or removed"),
+    ("設定値を表示", "Show a setting"),
+    ("設定キー（例: generate.length）", "Setting key (e.g. generate.length)"),
+    ("設定値を変更", "Change a setting"),
+    ("値", "Value"),
+    ("すべての設定値を表示", "Show all settings"),
+    // config.rs
+    ("不明な設定キーです: {}（{}）", "unknown setting: {} ({})"),
+    ("{} は {}〜{} の整数で指定してください: {}", "{} must be an integer from {} to {}: {}"),
+    ("{} は {} のいずれかで指定してください: {}", "{} must be one of {}: {}"),
+    ("{} に空文字は指定できません", "{} cannot be empty"),
+    ("設定ファイルを読み込めません: {}: {}", "cannot read the config file: {}: {}"),
+    ("設定ファイルを解釈できません: {}: {}", "cannot parse the config file: {}: {}"),
+    ("設定を無視します: {}", "ignoring setting: {}"),
+    ("設定ファイルの [{}] がテーブルではありません: {}", "[{}] in the config file is not a table: {}"),
+    ("設定を書き込めません: {}", "cannot write the config: {}"),
+    ("設定を書き込めません: {}: {}", "cannot write the config: {}: {}"),
+    ("生成するパスワードの文字数", "Length of generated passwords"),
+    ("使う文字の種類（alnum / symbols / digits）", "Characters to use (alnum / symbols / digits)"),
+    ("auth のセッション有効期限（分）", "Session lifetime for auth (minutes)"),
+    ("--json 対応コマンドの既定の出力形式（text / json）", "Default output format of commands supporting --json (text / json)"),
+    ("出力エンコーディング（auto / utf8 / sjis）。TSUPASSWD_ENCODING が優先", "Output encoding (auto / utf8 / sjis); TSUPASSWD_ENCODING takes precedence"),
+    ("コピーしたパスワードを消去するまでの秒数（0 で消去しない）", "Seconds before a copied password is cleared (0 keeps it)"),
+    ("既定の保管庫（名前またはパス）", "Default vault (name or path)"),
+    ("表示言語（auto / ja / en）。auto は LC_ALL / LC_MESSAGES / LANG に従う", "Display language (auto / ja / en); auto follows LC_ALL / LC_MESSAGES / LANG"),
+    // location.rs
+    ("保管庫のパスを解釈できません: {}: {}", "invalid vault path: {}: {}"),
+    // output.rs
+    ("テンプレートに閉じていない { があります", "unclosed { in the template"),
+    (
+        "テンプレートに不明な項目があります: {}（url / username / password / title / note / field.<名前>）",
+        "unknown item in the template: {} (url / username / password / title / note / field.<name>)",
+    ),
+    ("テンプレートに対応する { のない } があります", "unmatched } in the template"),
+    // inject.rs
+    (
+        "{}行目: {{ tsupasswd \"<URL>\" \"<項目>\" }} の形式で指定してください",
+        "line {}: expected {{ tsupasswd \"<URL>\" \"<ITEM>\" }}",
+    ),
+    // run.rs
+    ("参照は url:<URL>#<項目> の形式で指定してください: {}", "references must look like url:<URL>#<ITEM>: {}"),
+    // secret_input.rs
+    (
+        "パスワードの指定は --prompt / --password-stdin / --secret-fd / 引数のいずれか1つにしてください",
+        "give the password with only one of --prompt / --password-stdin / --secret-fd / an argument",
+    ),
+    (
+        "警告: {}をコマンドライン引数で渡すと ps やシェル履歴から見える可能性があります（--prompt / --password-stdin / --secret-fd を推奨）",
+        "warning: {} passed as a command-line argument may be visible in ps or shell history (prefer --prompt / --password-stdin / --secret-fd)",
+    ),
+    ("{}を入力できません: {}", "{} could not be read: {}"),
+    ("{}（確認）: ", "{} (again): "),
+    ("{}が一致しません", "{} entries do not match"),
+    ("標準入力を読めません: {}", "cannot read standard input: {}"),
+    ("{}が空です", "{} is empty"),
+    ("ファイルディスクリプタ {} を読めません: {}", "cannot read file descriptor {}: {}"),
+    ("--secret-fd はこのOSでは使えません（--password-stdin を使ってください）", "--secret-fd is not available on this OS (use --password-stdin)"),
+    // clipboard.rs
+    ("{} が失敗しました", "{} failed"),
+    ("{} は auto / wayland / x11 / osc52 / mock:PATH のいずれかを指定してください: {}", "{} must be one of auto / wayland / x11 / osc52 / mock:PATH: {}"),
+    // tui.rs
+    ("端末（TTY）で実行してください", "run this in a terminal (TTY)"),
+    ("ロックを解除しました", "Unlocked"),
+    ("パスワードをコピーしました（{}秒後に消去）", "Password copied (cleared in {} seconds)"),
+    ("パスワードをコピーしました", "Password copied"),
+    ("コピーに失敗しました: {}", "Copy failed: {}"),
+    ("ゴミ箱へ移動しました: id={}", "Moved to the trash: id={}"),
+    ("変更はありません", "No changes"),
+    (
+        "セッションの有効期限が切れたためロックしました。\n別の端末で `tsupasswd auth <secret>` を実行してから Enter（q で終了）",
+        "Locked because the session expired.\nRun `tsupasswd auth <secret>` in another terminal, then press Enter (q to quit)",
+    ),
+    (" ロック中 ", " Locked "),
+    (" 絞り込み（入力中、Enter で確定） ", " Filter (typing, Enter to apply) "),
+    (" 絞り込み（/） ", " Filter (/) "),
+    (" エントリ ({}) ", " Entries ({}) "),
+    ("エントリがありません", "No entries"),
+    (" 詳細 ", " Details "),
+    ("↑↓/jk 移動  / 絞り込み  r 表示切替  c コピー  e 編集  d 削除  q 終了", "↑↓/jk move  / filter  r reveal  c copy  e edit  d delete  q quit"),
+    ("「{}」をゴミ箱へ移動しますか？ (y/N)", "Move \"{}\" to the trash? (y/N)"),
+    (" 削除 ", " Delete "),
+    ("Tab 次の項目  Enter 保存  Esc 取消", "Tab next field  Enter save  Esc cancel"),
+    (" 編集 ", " Edit "),
+];
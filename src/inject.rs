@@ -0,0 +1,72 @@
+// inject: テンプレートの {{ tsupasswd "<URL>" "<項目>" }} を保管庫の値に置き換える
+//
+// 引数は二重引用符で囲む（中の \" と \\ は " と \）。項目は get --field と同じで、省略時は password。
+// tsupasswd で始まらない {{ ... }}（Helm・Go テンプレートなど）はそのまま残す。
+// 書き出すファイルは他のユーザから読めないよう 0600 にする（private_file::write）
+
+use crate::run::Reference;
+
+pub enum Piece {
+    Text(String),
+    Ref { line: usize, reference: Reference },
+}
+
+pub fn parse(text: &str) -> Result<Vec<Piece>, String> {
+    let mut pieces = Vec::new();
+    let mut plain = String::new();
+    let mut rest = text;
+    while let Some(at) = rest.find("{{") {
+        plain.push_str(&rest[..at]);
+        let line = text[..text.len() - rest.len() + at].matches('\n').count() + 1;
+        let inner = rest[at + 2..].trim_start();
+        let Some(args) = inner.strip_prefix("tsupasswd").filter(|a| a.starts_with(|c: char| c.is_whitespace() || c == '}')) else {
+            plain.push_str("{{");
+            rest = &rest[at + 2..];
+            continue;
+        };
+        let (reference, after) = placeholder(args, line)?;
+        if !plain.is_empty() { pieces.push(Piece::Text(std::mem::take(&mut plain))); }
+        pieces.push(Piece::Ref { line, reference });
+        rest = after;
+    }
+    plain.push_str(rest);
+    if !plain.is_empty() { pieces.push(Piece::Text(plain)); }
+    Ok(pieces)
+}
+
+// tsupasswd の後の引数を }} まで読み、参照と残りの文字列を返す
+fn placeholder(mut s: &str, line: usize) -> Result<(Reference, &str), String> {
+    let invalid = || t!("{}行目: {{ tsupasswd \"<URL>\" \"<項目>\" }} の形式で指定してください", line);
+    let mut args = Vec::new();
+    loop {
+        s = s.trim_start();
+        if let Some(after) = s.strip_prefix("}}") {
+            s = after;
+            break;
+        }
+        let body = s.strip_prefix('"').ok_or_else(invalid)?;
+        let mut arg = String::new();
+        let mut chars = body.char_indices();
+        let end = loop {
+            match chars.next() {
+                Some((i, '"')) => break i,
+                Some((_, '\\')) => match chars.next() {
+                    Some((_, c @ ('"' | '\\'))) => arg.push(c),
+                    Some((_, c)) => { arg.push('\\'); arg.push(c); }
+                    None => return Err(invalid()),
+                },
+                Some((_, '\n')) | None => return Err(invalid()),
+                Some((_, c)) => arg.push(c),
+            }
+        };
+        args.push(arg);
+        s = &body[end + 1..];
+    }
+    let mut args = args.into_iter();
+    match (args.next(), args.next(), args.next()) {
+        (Some(url), field, None) if !url.is_empty() && field.as_ref().is_none_or(|f| !f.is_empty()) => {
+            Ok((Reference { url, field: field.unwrap_or_else(|| "password".to_string()) }, s))
+        }
+        _ => Err(invalid()),
+    }
+}
@@ -0,0 +1,31 @@
+// tsupasswd の保管庫をプログラムから扱うためのライブラリ
+//
+//     let mut vault = tsupasswd::Vault::open(path)?;
+//     vault.unlock(&secret)?;
+//     let saved = vault.add(&tsupasswd::NewEntry { url, username, password, ..Default::default() })?;
+//     let entry = vault.get(&saved.id)?;
+//
+// CLI（src/main.rs）はこの上に引数解析・セッション・出力整形を載せたもの
+// エラーの文は i18n の表示言語（日本語・英語）で作る
+
+#[macro_use]
+pub mod i18n;
+pub mod audit;
+pub mod auth;
+pub mod crypto;
+pub mod error;
+pub mod fuzzy;
+pub mod generator;
+pub mod otp;
+pub mod private_file;
+pub mod selector;
+pub mod site;
+pub mod store;
+pub mod vault;
+
+pub use error::{Error, Result};
+pub use generator::{generate_password, generate_password_with, Policy};
+pub use store::{FileStore, MemoryStore, SqliteStore, StoredRecord, VaultStore};
+pub use vault::{
+    CustomField, Entry, EntryChanges, ExportRecord, ListEntry, ListOptions, NewEntry, OtpSecret, SortKey, Target, TrashEntry, Vault,
+};
@@ -0,0 +1,125 @@
+// 保管庫とセッション等の置き場所
+//
+//   保管庫      $XDG_DATA_HOME/tsupasswd/passwords.db（未設定なら ~/.local/share/tsupasswd）
+//   セッション等 $XDG_STATE_HOME/tsupasswd（未設定なら ~/.local/state/tsupasswd）
+//
+// Windows はどちらも %LOCALAPPDATA%\tsupasswd。旧バージョンの ~/.tsupasswd_db / ~/.password_cli があればそちらを使い続ける。
+// --vault / TSUPASSWD_VAULT で名前（vaults/<名前>.db）かパスを指定でき、保管庫ごとにセッション・verifier・監査ログが分かれる
+
+use std::env;
+use std::path::{Path, PathBuf};
+use sha2::{Digest, Sha256};
+
+pub const VAULT_ENV: &str = "TSUPASSWD_VAULT";
+pub const CONFIG_ENV: &str = "TSUPASSWD_CONFIG";
+pub const DEFAULT_NAME: &str = "default";
+// この拡張子のパスは SQLite ではなく暗号化ファイル（FileStore）として開く
+pub const FILE_VAULT_EXT: &str = "vault";
+
+pub struct Location {
+    pub name: String, // 名前付きなら名前、パス指定ならパス
+    pub db_path: PathBuf,
+    pub state_dir: PathBuf,
+}
+
+impl Location {
+    pub fn is_file_vault(&self) -> bool {
+        self.db_path.extension().is_some_and(|e| e == FILE_VAULT_EXT)
+    }
+}
+
+// --vault の指定、TSUPASSWD_VAULT、設定ファイルの vault.path、既定の保管庫の順に決める
+pub fn resolve(flag: Option<&str>, configured: Option<&str>) -> Result<Location, String> {
+    let env_value = env::var(VAULT_ENV).ok().filter(|v| !v.is_empty());
+    let spec = flag.map(str::to_string).or(env_value).or(configured.map(str::to_string));
+    match spec.as_deref() {
+        None | Some(DEFAULT_NAME) => Ok(Location {
+            name: DEFAULT_NAME.to_string(),
+            db_path: data_dir().join("passwords.db"),
+            state_dir: state_dir(),
+        }),
+        Some(name) if is_name(name) => Ok(Location {
+            name: name.to_string(),
+            db_path: data_dir().join("vaults").join(format!("{}.db", name)),
+            state_dir: state_dir().join("vaults").join(name),
+        }),
+        Some(path) => {
+            let db_path = std::path::absolute(path).map_err(|e| t!("保管庫のパスを解釈できません: {}: {}", path, e))?;
+            // パス指定の保管庫のセッション等はパスのハッシュで分ける
+            let digest = Sha256::digest(db_path.to_string_lossy().as_bytes());
+            let key: String = digest.iter().take(8).map(|b| format!("{:02x}", b)).collect();
+            Ok(Location { name: path.to_string(), state_dir: state_dir().join("vaults").join(format!("path-{}", key)), db_path })
+        }
+    }
+}
+
+// 英数字・`-`・`_` だけなら名前、それ以外（`/` や `.` を含む）はパスとして扱う
+fn is_name(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn home_dir() -> PathBuf {
+    let home = env::var("HOME").or_else(|_| env::var("USERPROFILE")).unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home)
+}
+
+// XDG の変数は絶対パスのときだけ使う（仕様どおり相対パスは無視）
+fn xdg_dir(var: &str, fallback: &[&str]) -> PathBuf {
+    match env::var(var).ok().map(PathBuf::from).filter(|p| p.is_absolute()) {
+        Some(dir) => dir.join("tsupasswd"),
+        None => fallback.iter().fold(home_dir(), |p, c| p.join(c)).join("tsupasswd"),
+    }
+}
+
+#[cfg(windows)]
+fn local_app_data() -> PathBuf {
+    match env::var("LOCALAPPDATA") {
+        Ok(dir) => PathBuf::from(dir).join("tsupasswd"),
+        Err(_) => home_dir().join("AppData").join("Local").join("tsupasswd"),
+    }
+}
+
+// TSUPASSWD_CONFIG、$XDG_CONFIG_HOME/tsupasswd/config.toml（未設定なら ~/.config/tsupasswd）の順。
+// Windows は %APPDATA%\tsupasswd\config.toml
+pub fn config_path() -> PathBuf {
+    if let Some(path) = env::var(CONFIG_ENV).ok().filter(|v| !v.is_empty()) { return PathBuf::from(path); }
+    #[cfg(windows)]
+    {
+        match env::var("APPDATA") {
+            Ok(dir) => PathBuf::from(dir).join("tsupasswd").join("config.toml"),
+            Err(_) => home_dir().join("AppData").join("Roaming").join("tsupasswd").join("config.toml"),
+        }
+    }
+    #[cfg(not(windows))]
+    { xdg_dir("XDG_CONFIG_HOME", &[".config"]).join("config.toml") }
+}
+
+fn legacy(dir: &str) -> Option<PathBuf> {
+    Some(home_dir().join(dir)).filter(|p| p.is_dir())
+}
+
+fn data_dir() -> PathBuf {
+    if let Some(dir) = legacy(".tsupasswd_db") { return dir; }
+    #[cfg(windows)]
+    { local_app_data() }
+    #[cfg(not(windows))]
+    { xdg_dir("XDG_DATA_HOME", &[".local", "share"]) }
+}
+
+fn state_dir() -> PathBuf {
+    #[cfg(windows)]
+    { local_app_data() }
+    #[cfg(not(windows))]
+    {
+        if let Some(dir) = legacy(".password_cli") { return dir; }
+        xdg_dir("XDG_STATE_HOME", &[".local", "state"])
+    }
+}
+
+// 表示用（ホームディレクトリは ~ に置き換える）
+pub fn display(path: &Path) -> String {
+    match path.strip_prefix(home_dir()) {
+        Ok(rest) => format!("~/{}", rest.display()),
+        Err(_) => path.display().to_string(),
+    }
+}
@@ -1,18 +1,31 @@
+// t! をバイナリ側のすべてのモジュールで使う
+#[macro_use]
+extern crate tsupasswd;
+
 use std::env;
 use std::fs;
 use std::io::Write;
-use std::path::PathBuf;
-use rand::rngs::OsRng;
-use rand::RngCore;
-use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 use chrono::Utc;
-use rusqlite::{params, Connection, OptionalExtension};
-use chacha20poly1305::aead::{Aead, KeyInit};
-use chacha20poly1305::ChaCha20Poly1305;
-use hkdf::Hkdf;
-use sha2::Sha256;
-use base64::{engine::general_purpose::STANDARD as B64, Engine as _};
-use csv::{ReaderBuilder, WriterBuilder};
+use cli::{Command, ConfigCommand, TrashCommand};
+use exit::Failure;
+use secret_input::SecretSource;
+use tsupasswd::vault::{normalize_folder, parse_tags_arg};
+use tsupasswd::{
+    audit, auth, generate_password_with, i18n, otp, private_file, selector, Entry, EntryChanges, Error, FileStore, ListOptions, NewEntry, OtpSecret,
+    Target, Vault,
+};
+
+mod cli;
+mod clipboard;
+mod config;
+mod exit;
+mod inject;
+mod location;
+mod output;
+mod run;
+mod secret_input;
+mod tui;
 
 // WindowsのみShift-JISで出力するためにprintln!/eprintln!をローカルでラップ
 #[derive(Copy, Clone, PartialEq, Eq)]
@@ -28,6 +41,11 @@ fn decide_encoding() -> OutEnc {
             _ => {}
         }
     }
+    match settings().encoding.as_deref() {
+        Some("sjis") => return OutEnc::Sjis,
+        Some("utf8") => return OutEnc::Utf8,
+        _ => {}
+    }
     #[cfg(windows)]
     {
         if std::env::var("WT_SESSION").is_ok() { return OutEnc::Utf8; }
@@ -104,72 +122,6 @@ macro_rules! eprintln {
     }};
 }
 
-fn print_usage() {
-    println!("使い方:");
-    println!("  tsupasswd [長さ]");
-    println!("  tsupasswd add <url> <username> [password|length] [--title <title>] [--note <note>]");
-    println!("  tsupasswd get <url> [--json]");
-    println!("  tsupasswd search <keyword> [--json]");
-    println!("  tsupasswd update <id> [--url U] [--user NAME] [--password PASS | --length N] [--title T] [--note N]");
-    println!("  tsupasswd delete <id>");
-    println!("  tsupasswd export <csv_path>");
-    println!("  tsupasswd import <csv_path>");
-    println!("  tsupasswd auth <secret> [--ttl MINUTES]");
-    println!("  tsupasswd logout");
-    println!("  tsupasswd status [--json]");
-        println!("");
-    println!("共通オプション:");
-    println!("  -h, --help    このヘルプを表示");
-    println!("");
-    println!("コマンド詳細:");
-    println!("  tsupasswd [長さ]");
-    println!("    引数:");
-    println!("      長さ              生成するパスワードの文字数（省略時 16）");
-    println!("");
-    println!("  tsupasswd add <url> <username> [password|length] [--title <title>] [--note <note>]");
-    println!("    引数:");
-    println!("      url               サイトURL等の識別子");
-    println!("      username          ユーザ名");
-    println!("      password|length   文字列を指定するとそのまま保存、数値を指定するとその長さで生成");
-    println!("    オプション:");
-    println!("      --title <title>   タイトル");
-    println!("      --note <note>     備考");
-    println!("");
-    println!("  tsupasswd get <url> [--json]");
-    println!("    オプション:");
-    println!("      --json            JSON形式で出力");
-    println!("");
-    println!("  tsupasswd search <keyword> [--json]");
-    println!("    オプション:");
-    println!("      --json            JSON形式で出力");
-    println!("");
-    println!("  tsupasswd update <id> [--url U] [--user NAME] [--password PASS | --length N] [--title T] [--note N]");
-    println!("    オプション:");
-    println!("      --url U           URL を更新");
-    println!("      --user NAME       ユーザ名を更新");
-    println!("      --password PASS   パスワードをこの文字列に更新");
-    println!("      --length N        ランダムに N 文字のパスワードを生成して更新");
-    println!("      --title T         タイトルを更新");
-    println!("      --note N          備考を更新");
-    println!("");
-    println!("  tsupasswd delete <id>");
-    println!("");
-    println!("  tsupasswd export <csv_path>");
-    println!("");
-    println!("  tsupasswd import <csv_path>");
-    println!("");
-    println!("  tsupasswd auth <secret> [--ttl MINUTES]");
-    println!("    オプション:");
-    println!("      --ttl MINUTES     セッション有効期限（分） デフォルト 30");
-    println!("");
-    println!("  tsupasswd logout");
-    println!("  tsupasswd status");
-    println!("");
-        println!("");
-    println!("環境変数:");
-    println!("  AUTH_SECRET           認証用シークレット（tsupasswd auth で使用）");
-    println!("  TSUPASSWD_ENCODING    出力エンコーディングを指定（utf8 / sjis）。Windowsでのリダイレクト時に有効");
-}
 #[tokio::main]
 async fn main() {
     // 端末のコードページは実行時に検出して出力側で切替
@@ -195,357 +147,777 @@ async fn main() {
     // - `tsupasswd` -> デフォルト16文字のパスワードを出力
     // - `tsupasswd 24` -> 指定長のパスワードを出力
     // - `tsupasswd add <url> <username> [password|length] [--title <title>] [--note <note>]` -> DBに保存
-    // - `tsupasswd get <url>` -> URLで検索してユーザID/パスワード/タイトル/備考を取得
-    // - `tsupasswd search <keyword>` -> 部分一致で検索（url/username/title/note）しID付きで一覧
-    // - `tsupasswd update <id> [--url U] [--user NAME] [--password PASS | --length N] [--title T] [--note N]` -> レコード更新（idはFirestoreのドキュメントID）
-    // - `tsupasswd delete <id>` -> レコード削除（idはFirestoreのドキュメントID）
+    // - `tsupasswd get <url> [--match exact|host|domain]` -> URL（既定はホスト単位）で検索してユーザID/パスワード/タイトル/備考を取得
+    // - `tsupasswd get <url> --copy [--clear-after SECS]` -> パスワードをクリップボードにコピーし、一定時間後に消去
+    // - `tsupasswd otp <url>` -> 登録済みOTPシークレットから現在のコードを表示（HOTPはカウンタを進める）
+    // - `tsupasswd search <keyword> [--fuzzy]` -> 部分一致（またはあいまい検索）で url/username/title/note を検索しID付きで一覧
+    // - `tsupasswd list [--folder F] [--tag T] [--sort K] [--limit N]` -> 並び替え・ページングして一覧（パスワードは既定で非表示）
+    // - `tsupasswd update <id|条件...> [--url U] [--user NAME] [--password PASS | --length N] [--title T] [--note N]` -> レコード更新（id は一意な先頭部分でも可）
+    // - `tsupasswd delete <id|条件...> [--all-matching] [--yes]` -> レコードをゴミ箱へ移動（確認あり）
+    // - `tsupasswd trash list|restore <id>|purge [--older-than 30d]` -> ゴミ箱の一覧・復元・完全削除
+    // - `tsupasswd tui` -> 全画面の対話モードで一覧・絞り込み・編集・削除
+    // - `tsupasswd log [--since WHEN] [--op OP] [--verify]` -> ハッシュ連鎖付き監査ログの表示・検証
     // Rustls 0.23+: 明示的に CryptoProvider をインストール（結果は無視）
     let _ = rustls::crypto::ring::default_provider().install_default();
 
-    // 引数を収集してログに追記
-    let all_args: Vec<String> = env::args().collect();
-    let mut args = all_args.clone().into_iter();
-    let _prog = args.next();
-    let first = args.next();
-    if matches!(first.as_deref(), Some("--help") | Some("-h") | Some("help")) {
-        print_usage();
-        return;
+    // 表示言語は設定ファイルでも選べるため、ヘルプや補完より先に読み込む
+    settings();
+    // 補完の要求（COMPLETE=<shell>）ならここで候補を出力して終了する
+    clap_complete::CompleteEnv::with_factory(cli::command).var(cli::COMPLETE_ENV).complete();
+    let cli = cli::parse();
+    for warning in &settings().warnings { eprintln!("{}", t!("警告: {}", warning)); }
+    match location::resolve(cli.vault.as_deref(), settings().vault.as_deref()) {
+        Ok(loc) => { let _ = LOCATION.set(loc); }
+        Err(e) => fail(Failure::InvalidInput, e),
     }
-    match first.as_deref() {
-        Some("export") => {
-            if let Err(msg) = ensure_authenticated() { eprintln!("{}", msg); std::process::exit(1); }
-            let path = match args.next() { Some(v) => v, None => { eprintln!("使い方: tsupasswd export <csv_path>"); std::process::exit(1);} };
-            let db = match init_db().await { Ok(db) => db, Err(e) => { eprintln!("DB初期化に失敗しました: {}", e); std::process::exit(1);} };
-            if let Err(e) = export_csv(&db, &path) {
-                eprintln!("エクスポートに失敗しました: {}", e);
-                std::process::exit(1);
+    match cli.command {
+        // get --copy が起動する消去用の子プロセス（内部用）
+        Some(Command::ClearClipboard { secs }) => {
+            let mut fingerprint = String::new();
+            if std::io::stdin().read_line(&mut fingerprint).is_err() { return; }
+            std::thread::sleep(std::time::Duration::from_secs(secs));
+            if let Ok(mut clip) = clipboard::backend() { let _ = clipboard::clear_if_unchanged(clip.as_mut(), fingerprint.trim_end()); }
+        }
+        Some(Command::Completions { shell }) => {
+            if let Err(e) = cli::write_completions(&shell, &mut std::io::stdout()) {
+                fail(Failure::Other, t!("補完スクリプトを出力できません: {}", e));
+            }
+        }
+        Some(Command::Export(cli::TransferArgs { path })) => {
+            require_session();
+            let db = open_vault();
+            let result = if cli.json { db.export_json(Path::new(&path)) } else { db.export_csv(Path::new(&path)) };
+            audit_event("export", None, result.is_ok(), Some(if cli.json { "format=json" } else { "format=csv" }));
+            if let Err(e) = result {
+                fail(Failure::of(&e), t!("エクスポートに失敗しました: {}", e));
             } else {
-                println!("エクスポート完了: {}", path);
+                println!("{}", t!("エクスポート完了: {}", path));
             }
         }
-        Some("import") => {
-            if let Err(msg) = ensure_authenticated() { eprintln!("{}", msg); std::process::exit(1); }
-            let path = match args.next() { Some(v) => v, None => { eprintln!("使い方: tsupasswd import <csv_path>"); std::process::exit(1);} };
-            let db = match init_db().await { Ok(db) => db, Err(e) => { eprintln!("DB初期化に失敗しました: {}", e); std::process::exit(1);} };
-            if let Err(e) = import_csv(&db, &path).await {
-                eprintln!("インポートに失敗しました: {}", e);
-                std::process::exit(1);
+        Some(Command::Import(cli::TransferArgs { path })) => {
+            require_session();
+            let db = open_vault();
+            let result = if cli.json { db.import_json(Path::new(&path)) } else { db.import_csv(Path::new(&path)) };
+            audit_event("import", None, result.is_ok(), Some(if cli.json { "format=json" } else { "format=csv" }));
+            if let Err(e) = result {
+                fail(Failure::of(&e), t!("インポートに失敗しました: {}", e));
             } else {
-                println!("インポート完了: {}", path);
+                println!("{}", t!("インポート完了: {}", path));
             }
         }
-        Some("auth") => {
-            let secret = match args.next() { Some(v) => v, None => { eprintln!("使い方: tsupasswd auth <secret> [--ttl MINUTES]"); std::process::exit(1);} };
-            let mut ttl: i64 = 30;
-            while let Some(flag) = args.next() {
-                match flag.as_str() {
-                    "--ttl" => {
-                        if let Some(n) = args.next().and_then(|s| s.parse::<i64>().ok()) { ttl = n.max(1); }
-                    }
-                    _ => {}
+        Some(Command::Auth(args)) => {
+            // シークレット省略時は端末からエコーなしで入力
+            let source = match args.source() {
+                Ok(v) => v,
+                Err(e) => fail(Failure::InvalidInput, e),
+            };
+            let ttl = args.ttl.unwrap_or(settings().ttl);
+            let report = |e: auth::AuthError| -> ! {
+                match e {
+                    auth::AuthError::Locked(rem) => fail(Failure::LockedOut, t!("認証の失敗が続いたためロック中です。{} 秒後に再試行してください", rem)),
+                    auth::AuthError::Rejected => fail(Failure::AuthFailed, t!("認証に失敗しました")),
+                    auth::AuthError::Other(msg) => fail(Failure::Other, msg),
                 }
-            }
-            let expected = match env::var("AUTH_SECRET") { Ok(v) => v, Err(_) => { eprintln!("環境変数 AUTH_SECRET が未設定です"); std::process::exit(1)} };
-            if secret != expected { eprintln!("認証に失敗しました"); std::process::exit(1); }
+            };
+            // 待ち時間中はシークレットを入力させない
+            if let Err(e) = auth::check_lockout(&state_dir()) { report(e); }
+            let secret = match secret_input::read(source.unwrap_or(SecretSource::Prompt), &t!("認証シークレット"), false) {
+                Ok(v) => v,
+                Err(e) => fail(Failure::InvalidInput, e),
+            };
+            if let Err(e) = auth::verify(&state_dir(), &secret) { report(e); }
             if let Err(e) = start_session(ttl) {
-                eprintln!("セッション開始に失敗しました: {}", e);
-                std::process::exit(1);
+                fail(Failure::Storage, t!("セッション開始に失敗しました: {}", e));
             } else {
-                println!("認証しました: 有効期限 {} 分", ttl);
+                println!("{}", t!("認証しました: 有効期限 {} 分", ttl));
             }
         }
-        Some("logout") => {
+        Some(Command::Logout) => {
             if let Err(e) = end_session() {
-                eprintln!("ログアウトに失敗しました: {}", e);
-                std::process::exit(1);
+                fail(Failure::Storage, t!("ログアウトに失敗しました: {}", e));
             } else {
-                println!("ログアウトしました");
+                println!("{}", t!("ログアウトしました"));
             }
         }
-        Some("status") => {
-            let mut json_out = false;
-            while let Some(flag) = args.next() { if flag == "--json" { json_out = true; } }
+        Some(Command::Status) => {
+            let json_out = cli.json || settings().json;
+            let loc = vault_location();
             match session_status() {
                 Ok(Some(rem)) => {
                     if json_out {
                         let obj = serde_json::json!({
                             "authenticated": true,
                             "remaining_seconds": rem,
+                            "vault": loc.name,
+                            "vault_path": loc.db_path,
                         });
-                        match serde_json::to_string_pretty(&obj) { Ok(s) => println!("{}", s), Err(e) => { eprintln!("JSONエンコードに失敗しました: {}", e); std::process::exit(1); } }
+                        print_json(&obj);
                     } else {
-                        println!("認証済み: 残り {} 秒", rem)
+                        println!("{}", t!("認証済み: 残り {} 秒", rem));
+                        println!("{}", t!("保管庫: {}（{}）", loc.name, location::display(&loc.db_path)));
                     }
                 }
                 Ok(None) => {
                     if json_out {
                         let obj = serde_json::json!({
-                            "authenticated": false
+                            "authenticated": false,
+                            "vault": loc.name,
+                            "vault_path": loc.db_path,
                         });
-                        match serde_json::to_string_pretty(&obj) { Ok(s) => println!("{}", s), Err(e) => { eprintln!("JSONエンコードに失敗しました: {}", e); std::process::exit(1); } }
+                        print_json(&obj);
+                        std::process::exit(Failure::Unauthenticated.code());
+                    }
+                    fail(Failure::Unauthenticated, t!("未認証です（保管庫: {}）", loc.name));
+                }
+                Err(e) => fail(Failure::Storage, t!("状態取得に失敗しました: {}", e)),
+            }
+        }
+        Some(Command::Config(sub)) => {
+            match sub {
+                ConfigCommand::Get { key } => {
+                    match config::get(&key) {
+                        Ok((value, _)) => println!("{}", value),
+                        Err(e) => fail(Failure::InvalidInput, e),
+                    }
+                }
+                ConfigCommand::Set { key, value } => {
+                    if let Err(e) = config::set(&key, &value) { fail(Failure::InvalidInput, e); }
+                    println!("{}", t!("{} = {}（{}）", key, value, location::display(&location::config_path())));
+                }
+                ConfigCommand::List => {
+                    let json_out = cli.json || settings().json;
+                    let entries = match config::list() { Ok(v) => v, Err(e) => fail(Failure::InvalidInput, e) };
+                    if json_out {
+                        let obj: serde_json::Map<String, serde_json::Value> = entries
+                            .iter()
+                            .map(|(key, value, set)| (key.name.to_string(), serde_json::json!({ "value": value, "default": !set })))
+                            .collect();
+                        print_json(&obj);
                     } else {
-                        eprintln!("未認証です");
+                        println!("# {}", location::display(&location::config_path()));
+                        for (key, value, set) in entries {
+                            println!("{} = {}{}    # {}", key.name, value, if set { String::new() } else { t!("（既定）") }, i18n::tr(key.help));
+                        }
                     }
-                    std::process::exit(1);
                 }
-                Err(e) => { eprintln!("状態取得に失敗しました: {}", e); std::process::exit(1); }
             }
         }
-        Some("add") => {
-            if let Err(msg) = ensure_authenticated() { eprintln!("{}", msg); std::process::exit(1); }
-            let url = match args.next() { Some(v) => v, None => return print_add_usage_and_exit() };
-            let username = match args.next() { Some(v) => v, None => return print_add_usage_and_exit() };
-            let maybe_pw_or_len = args.next();
-
-            let password = match maybe_pw_or_len {
-                None => generate_password(16),
+        Some(Command::Add(args)) => {
+            require_session();
+            let cli::AddArgs { url, username, password_or_length, title, note, totp, fields, folder, tags, input } = args;
+            // 3番目の位置引数（省略可）: 数値なら生成する長さ、それ以外はパスワード
+            let (length, argv) = match password_or_length {
                 Some(s) => match s.parse::<usize>() {
-                    Ok(n) => generate_password(n.max(1)),
-                    Err(_) => s, // 文字列が数値でなければ、そのままパスワードとして扱う
+                    Ok(n) => (Some(n.max(1)), None),
+                    Err(_) => (None, Some(s)),
                 },
+                None => (None, None),
+            };
+            let pw_source = match input.source(argv) {
+                Ok(v) => v,
+                Err(e) => fail(Failure::Usage, e),
             };
+            let folder = folder.as_deref().and_then(normalize_folder);
+            let tags: Vec<String> = tags.iter().flat_map(|t| parse_tags_arg(t)).collect();
+            let fields = fields.fields;
 
-            // 追加オプションの解析: --title <title> --note <note>
-            let mut title: Option<String> = None;
-            let mut note: Option<String> = None;
-            loop {
-                match args.next() {
-                    Some(flag) if flag == "--title" => {
-                        title = args.next();
-                    }
-                    Some(flag) if flag == "--note" => {
-                        note = args.next();
-                    }
-                    Some(_) => {
-                        // 未知の引数は無視（簡易実装）
-                        continue;
-                    }
-                    None => break,
-                }
+            if let Some(Err(e)) = totp.as_deref().map(otp::OtpParams::parse) {
+                fail(Failure::InvalidInput, t!("OTPシークレットが不正です: {}", e));
             }
-
-            let db = match init_db().await {
-                Ok(db) => db,
-                Err(e) => { eprintln!("DB初期化に失敗しました: {}", e); std::process::exit(1); }
+            if length.is_some() && pw_source.is_some() {
+                fail(Failure::Usage, t!("長さの指定と --prompt / --password-stdin / --secret-fd は同時に指定できません"));
+            }
+            let password = match pw_source {
+                Some(src) => match secret_input::read(src, &t!("パスワード"), true) {
+                    Ok(v) => v,
+                    Err(e) => fail(Failure::InvalidInput, e),
+                },
+                None => generate_password_with(length.unwrap_or(settings().length), settings().policy),
             };
-            if let Err(e) = insert_password(&db, &url, &username, &password, title.as_deref(), note.as_deref()).await {
-                eprintln!("保存に失敗しました: {}", e);
-                std::process::exit(1);
+
+            let db = open_vault();
+            let saved = db.add(&NewEntry { url: url.clone(), username: username.clone(), password, title, note, totp, fields, folder, tags });
+            audit_event("add", saved.as_ref().ok().map(|e| e.id.as_str()), saved.is_ok(), None);
+            if let Err(e) = saved {
+                fail(Failure::of(&e), t!("保存に失敗しました: {}", e));
             } else {
-                println!("保存しました: url={} username={}", url, username);
+                println!("{}", t!("保存しました: url={} username={}", url, username));
             }
         }
-        Some("get") => {
-            if let Err(msg) = ensure_authenticated() { eprintln!("{}", msg); std::process::exit(1); }
-            let url = match args.next() { Some(v) => v, None => {
-                eprintln!("使い方: tsupasswd get <url>");
-                std::process::exit(1);
-            }};
-            let mut json_out = false;
-            while let Some(flag) = args.next() { if flag == "--json" { json_out = true; } }
-            let db = match init_db().await { Ok(db) => db, Err(e) => { eprintln!("DB初期化に失敗しました: {}", e); std::process::exit(1);} };
-            match fetch_by_url(&db, &url).await {
+        Some(Command::Get(cli::GetArgs { url, mode, copy, clear_after, field, format, no_newline })) => {
+            require_session();
+            let json_out = cli.json || settings().json;
+            let clear_after = clear_after.unwrap_or(settings().clear_after);
+            // --copy / --field / --format は設定の output.format より優先し、明示した --json とだけ衝突する
+            if copy && cli.json {
+                fail(Failure::Usage, t!("--copy と --json は同時に指定できません"));
+            }
+            if cli.json && (field.is_some() || format.is_some()) {
+                fail(Failure::Usage, t!("--field / --format と --json は同時に指定できません"));
+            }
+            let db = open_vault();
+            match db.find_by_url(&url, mode) {
                 Ok(entries) => {
-                        if entries.is_empty() {
-                            eprintln!("見つかりませんでした: url={}", url);
-                            std::process::exit(1);
+                    if entries.is_empty() {
+                        audit_event("get", None, false, Some("not_found"));
+                        fail(Failure::NotFound, t!("見つかりませんでした: url={}", url));
+                    } else if copy {
+                        // 先頭（入力どおりの URL、なければ最新）のパスワードのみコピーし、画面には出さない
+                        let entry = &entries[0];
+                        audit_event("get", Some(&entry.id), true, Some("copy"));
+                        if let Err(e) = copy_to_clipboard(&entry.password, clear_after) {
+                            fail(Failure::Other, t!("クリップボードへのコピーに失敗しました: {}", e));
+                        }
+                        let mut line = t!("コピーしました: username={}", output::quote(&entry.username));
+                        if entry.url != url { line.push_str(&format!(" url={}", output::quote(&entry.url))); }
+                        if clear_after > 0 { line.push_str(&t!("（{}秒後に消去）", clear_after)); }
+                        println!("{}", line);
+                    } else if field.is_some() || format.is_some() {
+                        // --copy と同じく先頭のレコードだけを出す
+                        let entry = &entries[0];
+                        let text = match (&field, &format) {
+                            (Some(name), _) => match output::field(entry, name) {
+                                Some(value) => value.to_string(),
+                                None => fail(Failure::NotFound, t!("フィールドがありません: {}", name)),
+                            },
+                            (None, Some(format)) => match format.render(entry) {
+                                Ok(text) => text,
+                                Err(output::RenderError::MissingField(name)) => fail(Failure::NotFound, t!("フィールドがありません: {}", name)),
+                                Err(output::RenderError::Multiline(name)) => {
+                                    fail(Failure::InvalidInput, t!("改行を含む値は env 形式で出力できません（shell / dotenv を使ってください）: {}", name))
+                                }
+                                Err(output::RenderError::Collision(name, first, second)) => fail(
+                                    Failure::InvalidInput,
+                                    t!("フィールド {} と {} がどちらも変数 {} になるため出力できません（--field で個別に取り出してください）", first, second, name),
+                                ),
+                            },
+                            (None, None) => unreachable!(),
+                        };
+                        audit_event("get", Some(&entry.id), true, Some(if field.is_some() { "field" } else { "format" }));
+                        if no_newline {
+                            let mut out = std::io::stdout();
+                            let _ = out.write_all(text.as_bytes()).and_then(|_| out.flush());
                         } else {
-                            if json_out {
-                                let data: Vec<_> = entries.into_iter().map(|(username, password, title, note)| {
-                                    serde_json::json!({
-                                        "username": username,
-                                        "password": password,
-                                        "title": title,
-                                        "note": note,
-                                    })
-                                }).collect();
-                                match serde_json::to_string_pretty(&data) { Ok(s) => println!("{}", s), Err(e) => { eprintln!("JSONエンコードに失敗しました: {}", e); std::process::exit(1); } }
-                            } else {
-                                for (username, password, title, note) in entries {
-                                    match (title.as_deref(), note.as_deref()) {
-                                        (Some(t), Some(n)) => println!("username=\"{}\" password=\"{}\" title=\"{}\" note=\"{}\"", username, password, t, n),
-                                        (Some(t), None) => println!("username=\"{}\" password=\"{}\" title=\"{}\"", username, password, t),
-                                        (None, Some(n)) => println!("username=\"{}\" password=\"{}\" note=\"{}\"", username, password, n),
-                                        (None, None) => println!("username=\"{}\" password=\"{}\"", username, password),
-                                    }
+                            println!("{}", text);
+                        }
+                    } else {
+                        for entry in &entries { audit_event("get", Some(&entry.id), true, None); }
+                        if json_out {
+                            let data: Vec<_> = entries.into_iter().map(|e| {
+                                serde_json::json!({
+                                    "url": e.url,
+                                    "username": e.username,
+                                    "password": e.password,
+                                    "title": e.title,
+                                    "note": e.note,
+                                    "fields": e.fields,
+                                })
+                            }).collect();
+                            print_json(&data);
+                        } else {
+                            for Entry { url: entry_url, username, password, title, note, fields, .. } in entries {
+                                // 値は引用符・改行をエスケープして1レコード1行にする
+                                let mut line = format!("username={} password={}", output::quote(&username), output::quote(&password));
+                                if let Some(t) = title { line.push_str(&format!(" title={}", output::quote(&t))); }
+                                if let Some(n) = note { line.push_str(&format!(" note={}", output::quote(&n))); }
+                                for f in &fields {
+                                    line.push_str(&format!(" field.{}={}", f.name, output::quote(&f.value)));
                                 }
+                                // 別の URL で保存されたレコードがどれか分かるようにする
+                                if entry_url != url { line.push_str(&format!(" url={}", output::quote(&entry_url))); }
+                                println!("{}", line);
                             }
                         }
+                    }
+                }
+                Err(e) => fail(Failure::of(&e), t!("検索に失敗しました: {}", e)),
+            }
+        }
+        Some(Command::Run(cli::RunArgs { env: references, mode, redact, command })) => {
+            require_session();
+            let db = open_vault();
+            let mut vars: Vec<(String, String)> = Vec::new();
+            for (name, reference) in references {
+                if vars.iter().any(|(n, _)| *n == name) {
+                    fail(Failure::Usage, t!("環境変数 {} が重複しています", name));
+                }
+                let entry = match db.fetch_by_url(&reference.url, mode) {
+                    Ok(entry) => entry,
+                    Err(e) => {
+                        audit_event("run", None, false, Some("not_found"));
+                        fail(Failure::of(&e), t!("{} の参照を解決できません: {}", name, e));
+                    }
+                };
+                let Some(value) = output::field(&entry, &reference.field) else {
+                    audit_event("run", Some(&entry.id), false, Some("not_found"));
+                    fail(Failure::NotFound, t!("{} の参照を解決できません: フィールドがありません: {}", name, reference.field));
+                };
+                // 監査ログには値・URL ではなく環境変数名を残す
+                audit_event("run", Some(&entry.id), true, Some(&name));
+                vars.push((name, value.to_string()));
+            }
+            drop(db);
+            match run::exec(&command, &vars, redact) {
+                Ok(code) => std::process::exit(code),
+                Err(e) => fail(Failure::Other, t!("コマンドを実行できません: {}: {}", command[0].to_string_lossy(), e)),
+            }
+        }
+        Some(Command::Inject(cli::InjectArgs { input, output: out_path, mode })) => {
+            require_session();
+            let text = match &input {
+                Some(path) => fs::read_to_string(path),
+                None => std::io::read_to_string(std::io::stdin()),
+            };
+            let name = input.as_deref().map_or("-".into(), |p| p.display().to_string());
+            let text = text.unwrap_or_else(|e| fail(Failure::Storage, t!("テンプレートを読み込めません: {}: {}", name, e)));
+            let pieces = inject::parse(&text).unwrap_or_else(|e| fail(Failure::InvalidInput, format!("{}: {}", name, e)));
+            let db = open_vault();
+            let mut rendered = String::with_capacity(text.len());
+            let mut ids: Vec<String> = Vec::new();
+            // 解決できない参照はまとめて報告し、何も書き出さない
+            let mut unresolved = Vec::new();
+            for piece in &pieces {
+                let (line, reference) = match piece {
+                    inject::Piece::Text(t) => { rendered.push_str(t); continue; }
+                    inject::Piece::Ref { line, reference } => (line, reference),
+                };
+                let entry = match db.fetch_by_url(&reference.url, mode) {
+                    Ok(entry) => entry,
+                    Err(e @ tsupasswd::Error::NotFound(_)) => { unresolved.push(t!("{}行目: {}", line, e)); continue; }
+                    Err(e) => fail(Failure::of(&e), t!("検索に失敗しました: {}", e)),
+                };
+                match output::field(&entry, &reference.field) {
+                    Some(value) => rendered.push_str(value),
+                    None => { unresolved.push(t!("{}行目: フィールドがありません: {}", line, reference.field)); continue; }
                 }
-                Err(e) => { eprintln!("検索に失敗しました: {}", e); std::process::exit(1); }
+                if !ids.contains(&entry.id) { ids.push(entry.id); }
+            }
+            drop(db);
+            if !unresolved.is_empty() {
+                audit_event("inject", None, false, Some("not_found"));
+                fail(Failure::NotFound, t!("解決できない参照があります:\n{}", unresolved.join("\n")));
+            }
+            for id in &ids { audit_event("inject", Some(id), true, None); }
+            match &out_path {
+                Some(path) => {
+                    if let Err(e) = private_file::write(path, rendered.as_bytes()) {
+                        fail(Failure::Storage, t!("書き出せません: {}: {}", path.display(), e));
+                    }
+                    println!("{}", t!("書き出しました: {}", path.display()));
+                }
+                None => {
+                    let mut stdout = std::io::stdout();
+                    let _ = stdout.write_all(rendered.as_bytes()).and_then(|_| stdout.flush());
+                }
+            }
+        }
+        Some(Command::Tui) => {
+            require_session();
+            let db = open_vault();
+            if let Err(e) = tui::run(&db) {
+                fail(Failure::Other, t!("TUIの実行に失敗しました: {}", e));
             }
         }
-        Some("search") => {
-            if let Err(msg) = ensure_authenticated() { eprintln!("{}", msg); std::process::exit(1); }
-            let keyword = match args.next() { Some(v) => v, None => {
-                eprintln!("使い方: tsupasswd search <keyword>");
-                std::process::exit(1);
-            }};
-            let mut json_out = false;
-            while let Some(flag) = args.next() { if flag == "--json" { json_out = true; } }
-            let db = match init_db().await { Ok(db) => db, Err(e) => { eprintln!("DB初期化に失敗しました: {}", e); std::process::exit(1);} };
-            match search_entries(&db, &keyword).await {
+        Some(Command::Search(cli::SearchArgs { keyword, tag, fuzzy: fuzzy_mode, fts: fts_mode, limit })) => {
+            require_session();
+            let json_out = cli.json || settings().json;
+            // --tag 指定時はキーワード省略可（タグのみで絞り込み）。あいまい検索・全文検索はキーワード必須
+            if keyword.is_none() && (tag.is_none() || fuzzy_mode || fts_mode) {
+                fail(Failure::Usage, t!("検索語を指定してください（--tag のみで絞り込む場合は省略可。--fuzzy / --fts では必須）"));
+            }
+            let keyword = keyword.unwrap_or_default();
+            let db = open_vault();
+            let result = if fuzzy_mode {
+                db.search_fuzzy(&keyword, tag.as_deref(), limit.unwrap_or(10))
+                    .map(|v| v.into_iter().map(|(score, row)| (Some(score), row)).collect::<Vec<_>>())
+            } else if fts_mode {
+                db.search_fts(&keyword, tag.as_deref())
+                    .map(|v| v.into_iter().take(limit.unwrap_or(usize::MAX)).map(|row| (None, row)).collect::<Vec<_>>())
+            } else {
+                db.search(&keyword, tag.as_deref())
+                    .map(|v| v.into_iter().take(limit.unwrap_or(usize::MAX)).map(|row| (None, row)).collect::<Vec<_>>())
+            };
+            match result {
                 Ok(entries) => {
-                        if entries.is_empty() {
-                            eprintln!("見つかりませんでした: keyword={}", keyword);
-                            std::process::exit(1);
+                    if entries.is_empty() {
+                        fail(Failure::NotFound, t!("見つかりませんでした: keyword={}", keyword));
+                    } else {
+                        if json_out {
+                            let data: Vec<_> = entries.into_iter().map(|(score, Entry { id, url, username, password, title, note, folder, tags, .. })| {
+                                let mut obj = serde_json::json!({
+                                    "id": id,
+                                    "url": url,
+                                    "username": username,
+                                    "password": password,
+                                    "title": title,
+                                    "note": note,
+                                    "folder": folder,
+                                    "tags": tags,
+                                });
+                                if let Some(score) = score { obj["score"] = serde_json::json!((score * 1000.0).round() / 1000.0); }
+                                obj
+                            }).collect();
+                            print_json(&data);
                         } else {
-                            if json_out {
-                                let data: Vec<_> = entries.into_iter().map(|(id, url, username, password, title, note)| {
-                                    serde_json::json!({
-                                        "id": id,
-                                        "url": url,
-                                        "username": username,
-                                        "password": password,
-                                        "title": title,
-                                        "note": note,
-                                    })
-                                }).collect();
-                                match serde_json::to_string_pretty(&data) { Ok(s) => println!("{}", s), Err(e) => { eprintln!("JSONエンコードに失敗しました: {}", e); std::process::exit(1); } }
-                            } else {
-                                for (id, url, username, password, title, note) in entries {
-                                    match (title.as_deref(), note.as_deref()) {
-                                        (Some(t), Some(n)) => println!("id={} url=\"{}\" username=\"{}\" password=\"{}\" title=\"{}\" note=\"{}\"", id, url, username, password, t, n),
-                                        (Some(t), None) => println!("id={} url=\"{}\" username=\"{}\" password=\"{}\" title=\"{}\"", id, url, username, password, t),
-                                        (None, Some(n)) => println!("id={} url=\"{}\" username=\"{}\" password=\"{}\" note=\"{}\"", id, url, username, password, n),
-                                        (None, None) => println!("id={} url=\"{}\" username=\"{}\" password=\"{}\"", id, url, username, password),
-                                    }
-                                }
+                            for (score, Entry { id, url, username, password, title, note, folder, tags, .. }) in entries {
+                                let mut line = format!("id={} url={} username={} password={}", id, output::quote(&url), output::quote(&username), output::quote(&password));
+                                if let Some(t) = &title { line.push_str(&format!(" title={}", output::quote(t))); }
+                                if let Some(n) = &note { line.push_str(&format!(" note={}", output::quote(n))); }
+                                push_folder_and_tags(&mut line, folder.as_deref(), &tags);
+                                if let Some(score) = score { line.push_str(&format!(" score={:.3}", score)); }
+                                println!("{}", line);
                             }
                         }
+                    }
                 }
-                Err(e) => { eprintln!("検索に失敗しました: {}", e); std::process::exit(1); }
+                Err(e) => fail(Failure::of(&e), t!("検索に失敗しました: {}", e)),
             }
         }
-        Some("update") => {
-            if let Err(msg) = ensure_authenticated() { eprintln!("{}", msg); std::process::exit(1); }
-            let id: String = match args.next() { Some(v) => v, None => { eprintln!("使い方: password update <id> [--url U] [--user NAME] [--password PASS | --length N] [--title T] [--note N]"); std::process::exit(1);} };
-            let mut new_url: Option<String> = None;
-            let mut new_user: Option<String> = None;
-            let mut new_password: Option<String> = None;
-            let mut title: Option<String> = None;
-            let mut note: Option<String> = None;
-            while let Some(flag) = args.next() {
-                match flag.as_str() {
-                    "--url" => new_url = args.next(),
-                    "--user" => new_user = args.next(),
-                    "--password" => new_password = args.next(),
-                    "--length" => {
-                        if let Some(n) = args.next().and_then(|s| s.parse::<usize>().ok()) {
-                            new_password = Some(generate_password(n.max(1)));
+        Some(Command::List(args)) => {
+            require_session();
+            let json_out = cli.json || settings().json;
+            let opts = ListOptions {
+                folder: args.folder.as_deref().and_then(normalize_folder),
+                tag: args.tag,
+                sort: args.sort.unwrap_or_default(),
+                desc: args.desc,
+                limit: args.limit,
+                offset: args.offset.unwrap_or(0),
+                show_passwords: args.show_passwords,
+            };
+            let columns: Option<Vec<String>> = args.columns.map(|c| {
+                c.split(',').map(str::trim).filter(|c| !c.is_empty()).map(|c| c.to_string()).collect()
+            });
+            if let Some(cols) = &columns {
+                if let Some(bad) = cols.iter().find(|c| !LIST_COLUMNS.contains(&c.as_str())) {
+                    fail(Failure::Usage, t!("不明な列です: {}（指定可能: {}）", bad, LIST_COLUMNS.join(",")));
+                }
+                if cols.is_empty() { fail(Failure::Usage, t!("--columns に列を指定してください")); }
+            }
+            // パスワード列は --show-passwords 指定時のみ
+            let columns = match columns {
+                Some(cols) if cols.iter().any(|c| c == "password") && !opts.show_passwords => {
+                    fail(Failure::Usage, t!("password 列を表示するには --show-passwords を指定してください"));
+                }
+                Some(cols) => cols,
+                None => {
+                    let mut cols: Vec<String> = LIST_DEFAULT_COLUMNS.iter().map(|c| c.to_string()).collect();
+                    if opts.show_passwords { cols.push("password".to_string()); }
+                    cols
+                }
+            };
+            let db = open_vault();
+            let entries = match db.list(&opts) {
+                Ok(v) => v,
+                Err(e) => fail(Failure::of(&e), t!("一覧の取得に失敗しました: {}", e)),
+            };
+            let mut data = Vec::new();
+            for entry in entries {
+                let obj = match serde_json::to_value(&entry) {
+                    Ok(serde_json::Value::Object(m)) => m,
+                    _ => fail(Failure::Other, t!("JSONエンコードに失敗しました")),
+                };
+                let picked: serde_json::Map<String, serde_json::Value> = columns
+                    .iter()
+                    .map(|c| (c.clone(), obj.get(c).cloned().unwrap_or(serde_json::Value::Null)))
+                    .collect();
+                if json_out {
+                    data.push(serde_json::Value::Object(picked));
+                } else {
+                    println!("{}", format_columns(&picked));
+                }
+            }
+            if json_out {
+                print_json(&data);
+            }
+        }
+        Some(Command::Otp(cli::OtpArgs { url })) => {
+            require_session();
+            let json_out = cli.json || settings().json;
+            let db = open_vault();
+            let entries = match db.otp_secrets(&url) {
+                Ok(v) => v,
+                Err(e) => fail(Failure::of(&e), t!("検索に失敗しました: {}", e)),
+            };
+            if entries.is_empty() {
+                fail(Failure::NotFound, t!("OTPが登録されていません: url={}", url));
+            }
+            let now = Utc::now().timestamp().max(0) as u64;
+            let mut data = Vec::new();
+            for OtpSecret { id, username, uri } in entries {
+                let params = match otp::OtpParams::parse(&uri) {
+                    Ok(p) => p,
+                    Err(e) => fail(Failure::InvalidInput, t!("OTPシークレットが不正です: username={} {}", username, e)),
+                };
+                audit_event("otp", Some(&id), true, None);
+                match params.kind {
+                    otp::Kind::Totp => {
+                        let (code, remaining) = params.totp_at(now);
+                        if json_out {
+                            data.push(serde_json::json!({
+                                "username": username,
+                                "type": "totp",
+                                "code": code,
+                                "remaining_seconds": remaining,
+                            }));
+                        } else {
+                            println!("username=\"{}\" code=\"{}\" remaining={}", username, code, remaining);
+                        }
+                    }
+                    otp::Kind::Hotp => {
+                        // HOTP は呼び出しごとにカウンタを消費する
+                        let counter = match db.next_hotp_counter(&id) {
+                            Ok(c) => c,
+                            Err(e) => fail(Failure::of(&e), t!("カウンタの更新に失敗しました: {}", e)),
+                        };
+                        let code = params.code_for_counter(counter);
+                        if json_out {
+                            data.push(serde_json::json!({
+                                "username": username,
+                                "type": "hotp",
+                                "code": code,
+                                "counter": counter,
+                            }));
+                        } else {
+                            println!("username=\"{}\" code=\"{}\" counter={}", username, code, counter);
                         }
                     }
-                    "--title" => title = args.next(),
-                    "--note" => note = args.next(),
-                    _ => {}
                 }
             }
-            if new_url.is_none() && new_user.is_none() && new_password.is_none() && title.is_none() && note.is_none() {
-                eprintln!("更新内容が指定されていません");
-                std::process::exit(1);
+            if json_out {
+                print_json(&data);
             }
-            let db = match init_db().await { Ok(db) => db, Err(e) => { eprintln!("DB初期化に失敗しました: {}", e); std::process::exit(1);} };
-            if let Err(e) = update_entry(&db, &id, new_url.as_deref(), new_user.as_deref(), new_password.as_deref(), title.as_deref(), note.as_deref()).await {
-                eprintln!("更新に失敗しました: {}", e);
-                std::process::exit(1);
-            } else {
-                println!("更新しました: id={}", id);
+        }
+        Some(Command::Update(args)) => {
+            require_session();
+            let cli::UpdateArgs { selectors, url, username, password, input, length, title, note, totp, fields, unset_fields, folder, tags, untags, targets } = args;
+            let cli::TargetFlags { all_matching, dry_run, yes } = targets;
+            let pw_source = match input.source(password) {
+                Ok(v) => v,
+                Err(e) => fail(Failure::Usage, e),
+            };
+            let mut changes = EntryChanges {
+                url,
+                username,
+                password: length.map(|n| generate_password_with(n, settings().policy)),
+                title,
+                note,
+                totp,
+                fields: fields.fields,
+                unset_fields,
+                folder,
+                tags: tags.iter().flat_map(|t| parse_tags_arg(t)).collect(),
+                untags: untags.iter().flat_map(|t| parse_tags_arg(t)).collect(),
+            };
+            if let Some(src) = pw_source {
+                if changes.password.is_some() {
+                    fail(Failure::Usage, t!("--length と --password / --prompt / --password-stdin / --secret-fd は同時に指定できません"));
+                }
+                changes.password = match secret_input::read(src, &t!("パスワード"), true) {
+                    Ok(v) => Some(v),
+                    Err(e) => fail(Failure::InvalidInput, e),
+                };
+            }
+            if changes.is_empty() {
+                fail(Failure::Usage, t!("更新内容が指定されていません"));
+            }
+            if let Some(Err(e)) = changes.totp.as_deref().map(otp::OtpParams::parse) {
+                fail(Failure::InvalidInput, t!("OTPシークレットが不正です: {}", e));
+            }
+            let db = open_vault();
+            let targets = resolve_or_exit(&db, &selectors, all_matching, &t!("更新"));
+            let changed = changes.changed_names().join(",");
+            if targets.len() > 1 && changes.password.is_some() {
+                fail(Failure::Usage, t!("複数のレコードに同じパスワードは設定できません（1件ずつ更新してください）"));
+            }
+            if dry_run || targets.len() > 1 {
+                println!("{}", t!("更新する {} 件（{}）:\n{}", targets.len(), changed, format_targets(&targets)));
+                if dry_run { return; }
+                if !yes && !confirm(&t!("{} 件を更新しますか？", targets.len())) {
+                    fail(Failure::Aborted, t!("更新を中止しました（確認なしで更新するには --yes を指定してください）"));
+                }
+            }
+            for Target { id, .. } in &targets {
+                let result = db.update(id, &changes);
+                audit_event("update", Some(id), result.is_ok(), Some(&format!("changed={}", changed)));
+                if let Err(e) = result {
+                    fail(Failure::of(&e), t!("更新に失敗しました: {}", e));
+                }
+                println!("{}", t!("更新しました: id={}", id));
             }
         }
-        Some("delete") => {
-            if let Err(msg) = ensure_authenticated() { eprintln!("{}", msg); std::process::exit(1); }
-            let id: String = match args.next() { Some(v) => v, None => { eprintln!("使い方: password delete <id>"); std::process::exit(1);} };
-            let db = match init_db().await { Ok(db) => db, Err(e) => { eprintln!("DB初期化に失敗しました: {}", e); std::process::exit(1);} };
-            if let Err(e) = delete_entry(&db, &id).await {
-                eprintln!("削除に失敗しました: {}", e);
-                std::process::exit(1);
+        Some(Command::Log(cli::LogArgs { since, op, verify })) => {
+            require_session();
+            let json_out = cli.json || settings().json;
+            let dir = state_dir();
+            if verify {
+                match audit::verify(&dir) {
+                    Ok(n) => println!("{}", t!("監査ログは改ざんされていません: {} 件", n)),
+                    Err(e) => fail(Failure::Tampered, t!("監査ログの改ざんを検出しました: {}", e)),
+                }
+                return;
+            }
+            let entries = match audit::read_entries(&dir) {
+                Ok(v) => v,
+                Err(e) => fail(Failure::Storage, e),
+            };
+            let entries: Vec<_> = entries
+                .into_iter()
+                .filter(|e| op.as_deref().is_none_or(|o| e.op == o))
+                .filter(|e| since.is_none_or(|t| chrono::DateTime::parse_from_rfc3339(&e.ts).is_ok_and(|ts| ts >= t)))
+                .collect();
+            if json_out {
+                print_json(&entries);
             } else {
-                println!("削除しました: id={}", id);
+                for e in entries {
+                    let mut line = format!("ts=\"{}\" op=\"{}\" result=\"{}\"", e.ts, e.op, if e.ok { "ok" } else { "fail" });
+                    if let Some(id) = &e.id { line.push_str(&format!(" id={}", id)); }
+                    if let Some(d) = &e.detail { line.push_str(&format!(" detail=\"{}\"", d)); }
+                    println!("{}", line);
+                }
+            }
+        }
+        Some(Command::Delete(cli::DeleteArgs { selectors, targets })) => {
+            require_session();
+            let cli::TargetFlags { all_matching, dry_run, yes } = targets;
+            let db = open_vault();
+            let targets = resolve_or_exit(&db, &selectors, all_matching, &t!("削除"));
+            if dry_run || targets.len() > 1 {
+                println!("{}", t!("ゴミ箱へ移動する {} 件:\n{}", targets.len(), format_targets(&targets)));
+                if dry_run { return; }
+            }
+            let question = match targets.as_slice() {
+                [Target { url, username, .. }] => t!("url=\"{}\" username=\"{}\" をゴミ箱へ移動しますか？", url, username),
+                _ => t!("{} 件をゴミ箱へ移動しますか？", targets.len()),
+            };
+            if !yes && !confirm(&question) {
+                fail(Failure::Aborted, t!("削除を中止しました（確認なしで削除するには --yes を指定してください）"));
+            }
+            for Target { id, url, username } in &targets {
+                let result = db.delete(id);
+                audit_event("delete", Some(id), result.is_ok(), None);
+                if let Err(e) = result {
+                    fail(Failure::of(&e), t!("削除に失敗しました: {}", e));
+                }
+                println!("{}", t!("ゴミ箱へ移動しました: id={} url=\"{}\" username=\"{}\"（tsupasswd trash restore {} で復元）", id, url, username, id));
             }
         }
-        Some(s) => {
-            // 数値なら長さとして解釈。それ以外はヘルプ代わりに16文字生成。
-            let len = s.parse::<usize>().unwrap_or(16);
-            println!("{}", generate_password(len));
+        Some(Command::Trash(sub)) => {
+            require_session();
+            let db = open_vault();
+            match sub {
+                TrashCommand::List => {
+                    let json_out = cli.json || settings().json;
+                    let entries = match db.trash() { Ok(v) => v, Err(e) => fail(Failure::of(&e), t!("ゴミ箱の取得に失敗しました: {}", e)) };
+                    if json_out {
+                        print_json(&entries);
+                    } else if entries.is_empty() {
+                        println!("{}", t!("ゴミ箱は空です"));
+                    } else {
+                        for e in entries {
+                            let mut line = format!("id={} url={} username={}", e.id, output::quote(&e.url), output::quote(&e.username));
+                            if let Some(t) = &e.title { line.push_str(&format!(" title={}", output::quote(t))); }
+                            line.push_str(&format!(" deleted_at={}", output::quote(&e.deleted_at)));
+                            println!("{}", line);
+                        }
+                    }
+                }
+                TrashCommand::Restore { id } => {
+                    let result = db.restore(&id);
+                    audit_event("restore", Some(&id), result.is_ok(), None);
+                    if let Err(e) = result { fail(Failure::of(&e), t!("復元に失敗しました: {}", e)); }
+                    println!("{}", t!("復元しました: id={}", id));
+                }
+                TrashCommand::Purge { older_than } => {
+                    let entries = match db.trash() { Ok(v) => v, Err(e) => fail(Failure::of(&e), t!("ゴミ箱の取得に失敗しました: {}", e)) };
+                    let cutoff = older_than.map(|d| ago(d).unwrap_or_else(|| fail(Failure::Usage, t!("30d・24h・90m などで指定してください"))));
+                    let mut purged = 0;
+                    for e in entries {
+                        // 削除日時を解釈できないものは期間指定時は残す
+                        let deleted_at = chrono::DateTime::parse_from_rfc3339(&e.deleted_at).ok();
+                        if cutoff.is_some_and(|c| deleted_at.is_none_or(|t| t > c)) { continue; }
+                        let result = db.purge(&e.id);
+                        audit_event("purge", Some(&e.id), result.is_ok(), None);
+                        if let Err(err) = result { fail(Failure::of(&err), t!("完全削除に失敗しました: id={} {}", e.id, err)); }
+                        purged += 1;
+                    }
+                    println!("{}", t!("完全に削除しました: {} 件", purged));
+                }
+            }
         }
         None => {
-            println!("{}", generate_password(16));
+            println!("{}", generate_password_with(cli.length.unwrap_or(settings().length), settings().policy));
         }
     }
 }
 
-// 記号を含む安全な文字集合
-const UPPER: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
-const LOWER: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
-const DIGIT: &[u8] = b"0123456789";
-#[allow(dead_code)]
-const SYMBOL: &[u8] = b"!@#$%^&*()-_=+[]{};:,.?/"; // スペースやバックスラッシュ、`'"` は除外
-
-fn generate_password(len: usize) -> String {
-    // 総合アルファベット
-    let mut alphabet: Vec<u8> = Vec::with_capacity(UPPER.len() + LOWER.len() + DIGIT.len());
-    alphabet.extend_from_slice(UPPER);
-    alphabet.extend_from_slice(LOWER);
-    alphabet.extend_from_slice(DIGIT);
+// 設定ファイル（最初に使うときに読み込む）
+static SETTINGS: std::sync::OnceLock<config::Settings> = std::sync::OnceLock::new();
 
-    if len == 0 {
-        return String::new();
-    }
+fn settings() -> &'static config::Settings {
+    SETTINGS.get_or_init(config::load)
+}
 
-    // 少なくとも各カテゴリから1文字ずつ確保（ただし必要な長さを超えない）
-    let mut bytes: Vec<u8> = Vec::with_capacity(len);
-    for cat in [UPPER, LOWER, DIGIT] {
-        if bytes.len() >= len { break; }
-        let idx = rand_index(cat.len());
-        bytes.push(cat[idx]);
-    }
+// main の最初に --vault / TSUPASSWD_VAULT / 設定ファイルから決める
+static LOCATION: std::sync::OnceLock<location::Location> = std::sync::OnceLock::new();
 
-    // 残りは全アルファベットからランダムに
-    while bytes.len() < len {
-        let idx = rand_index(alphabet.len());
-        bytes.push(alphabet[idx]);
-    }
+fn vault_location() -> &'static location::Location {
+    LOCATION.get().expect("vault location is resolved at startup")
+}
 
-    // シャッフルして先頭にカテゴリ固定が来ないようにする
-    fisher_yates_shuffle(&mut bytes);
+fn session_file_path() -> PathBuf {
+    state_dir().join("session")
+}
 
-    String::from_utf8(bytes).unwrap_or_default()
+// log --since: 日付（その日の0時 UTC）、RFC3339、または現在からの相対時間（30m / 24h / 7d）
+fn parse_since(s: &str) -> Option<chrono::DateTime<Utc>> {
+    if let Ok(t) = chrono::DateTime::parse_from_rfc3339(s) { return Some(t.with_timezone(&Utc)); }
+    if let Ok(d) = chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d") { return d.and_hms_opt(0, 0, 0).map(|t| t.and_utc()); }
+    parse_age(s).and_then(ago)
 }
 
-fn rand_index(len: usize) -> usize {
-    // OsRngからu64を取り出し、範囲に収まるようにリジェクションサンプリング
-    if len <= 1 { return 0; }
-    let bound = len as u64;
-    let zone = u64::MAX - (u64::MAX % bound);
-    loop {
-        let v = OsRng.next_u64();
-        if v < zone {
-            return (v % bound) as usize;
-        }
-    }
+// 30s / 30m / 24h / 7d 形式の期間。現在から遡れない長さ（日時の範囲外）は None
+fn parse_age(s: &str) -> Option<chrono::Duration> {
+    // 単位は末尾の1文字（多バイト文字でも文字の境界で分ける）
+    let (at, unit) = s.char_indices().last()?;
+    let n: i64 = s[..at].parse().ok().filter(|n| *n >= 0)?;
+    let secs = match unit {
+        's' => n,
+        'm' => n.checked_mul(60)?,
+        'h' => n.checked_mul(3600)?,
+        'd' => n.checked_mul(86400)?,
+        _ => return None,
+    };
+    let age = chrono::TimeDelta::try_seconds(secs)?;
+    ago(age).map(|_| age)
 }
 
-fn fisher_yates_shuffle(data: &mut [u8]) {
-    if data.len() <= 1 { return; }
-    for i in (1..data.len()).rev() {
-        let j = rand_index(i + 1);
-        data.swap(i, j);
-    }
+// 現在から age だけ前の日時
+fn ago(age: chrono::Duration) -> Option<chrono::DateTime<Utc>> {
+    Utc::now().checked_sub_signed(age)
 }
 
-const COLLECTION: &str = "passwords"; // SQLiteのテーブル名としても使用
+// 端末から y/N を尋ねる。端末でなければ確認できないため false
+fn confirm(prompt: &str) -> bool {
+    use std::io::{BufRead, IsTerminal};
+    if !std::io::stdin().is_terminal() { return false; }
+    eprint!("{} [y/N]: ", prompt);
+    let _ = std::io::stderr().flush();
+    let mut line = String::new();
+    if std::io::stdin().lock().read_line(&mut line).is_err() { return false; }
+    matches!(line.trim(), "y" | "Y" | "yes")
+}
 
-fn session_file_path() -> PathBuf {
-    if cfg!(windows) {
-        if let Ok(dir) = env::var("LOCALAPPDATA") {
-            return PathBuf::from(dir).join("tsupasswd").join("session");
-        }
-        if let Ok(up) = env::var("USERPROFILE") {
-            return PathBuf::from(up).join("AppData").join("Local").join("tsupasswd").join("session");
-        }
+// 監査ログへの記録に失敗しても操作自体は止めない
+fn audit_event(op: &str, id: Option<&str>, ok: bool, detail: Option<&str>) {
+    if let Err(e) = audit::record(&state_dir(), op, id, ok, detail) {
+        eprintln!("{}", t!("監査ログに記録できません: {}", e));
     }
-    let home = env::var("HOME").or_else(|_| env::var("USERPROFILE")).unwrap_or_else(|_| ".".to_string());
-    PathBuf::from(home).join(".password_cli").join("session")
+}
+
+// セッション・verifier・監査ログなどを置くディレクトリ（保管庫ごと）
+fn state_dir() -> PathBuf {
+    vault_location().state_dir.clone()
 }
 
 // 引数や標準出力をファイルへ記録する機能は削除済み
@@ -553,17 +925,42 @@ fn session_file_path() -> PathBuf {
 fn ensure_authenticated() -> Result<(), String> {
     match session_status() {
         Ok(Some(rem)) => {
-            if rem <= 0 { Err("セッションが期限切れです。`tsupasswd auth <secret>` を実行してください".to_string()) } else { Ok(()) }
+            if rem <= 0 { Err(t!("セッションが期限切れです。`tsupasswd auth <secret>` を実行してください")) } else { Ok(()) }
         }
-        Ok(None) => Err("未認証です。`tsupasswd auth <secret>` を実行してください".to_string()),
-        Err(e) => Err(format!("認証状態の確認に失敗しました: {}", e)),
+        Ok(None) => Err(t!("未認証です。`tsupasswd auth <secret>` を実行してください")),
+        Err(e) => Err(t!("認証状態の確認に失敗しました: {}", e)),
+    }
+}
+
+// 認証が必要なコマンドの入口（未認証・期限切れなら終了コード 3）
+fn require_session() {
+    if let Err(msg) = ensure_authenticated() { fail(Failure::Unauthenticated, msg); }
+}
+
+// エラーを表示して種別ごとの終了コードで終了する（--json では {"error","message"} を1行で出す）
+fn fail(kind: Failure, message: impl std::fmt::Display) -> ! {
+    if exit::json_errors() {
+        eprintln!("{}", serde_json::json!({ "error": kind.name(), "message": message.to_string() }));
+    } else {
+        eprintln!("{}", message);
+    }
+    std::process::exit(kind.code())
+}
+
+fn print_json<T: serde::Serialize + ?Sized>(value: &T) {
+    match serde_json::to_string_pretty(value) {
+        Ok(s) => println!("{}", s),
+        Err(e) => fail(Failure::Other, t!("JSONエンコードに失敗しました: {}", e)),
     }
 }
 
 fn start_session(ttl_minutes: i64) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let path = session_file_path();
     if let Some(dir) = path.parent() { fs::create_dir_all(dir)?; }
-    let expiry = Utc::now().timestamp() + ttl_minutes * 60;
+    let expiry = ttl_minutes
+        .checked_mul(60)
+        .and_then(|secs| Utc::now().timestamp().checked_add(secs))
+        .ok_or_else(|| t!("セッションの有効期限が大きすぎます: {} 分", ttl_minutes))?;
     let mut f = fs::File::create(path)?;
     write!(f, "{}", expiry)?;
     Ok(())
@@ -584,301 +981,121 @@ fn session_status() -> Result<Option<i64>, Box<dyn std::error::Error + Send + Sy
     Ok(Some(expiry - now))
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct PasswordRecord {
-    id: String,
-    url: String,
-    username: String,
-    password: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    title: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    note: Option<String>,
-    created_at: String,
-}
-
-async fn init_db() -> Result<Connection, Box<dyn std::error::Error + Send + Sync>> {
-    let path = db_file_path();
-    if let Some(dir) = path.parent() { fs::create_dir_all(dir)?; }
-    let conn = Connection::open(path)?;
-    conn.execute(
-        &format!(
-            "CREATE TABLE IF NOT EXISTS {} (
-                id TEXT PRIMARY KEY,
-                url TEXT NOT NULL,
-                username TEXT NOT NULL,
-                password TEXT NOT NULL,
-                title TEXT,
-                note TEXT,
-                created_at TEXT NOT NULL
-            )",
-            COLLECTION
-        ),
-        [],
-    )?;
-    Ok(conn)
-}
+const LIST_COLUMNS: &[&str] = &["id", "url", "username", "title", "note", "folder", "tags", "created_at", "updated_at", "password"];
+const LIST_DEFAULT_COLUMNS: &[&str] = &["id", "url", "username", "title", "folder", "tags"];
 
-async fn insert_password(
-    db: &Connection,
-    url: &str,
-    username: &str,
-    password: &str,
-    title: Option<&str>,
-    note: Option<&str>,
-) -> Result<PasswordRecord, Box<dyn std::error::Error + Send + Sync>> {
-    // 既存URLの有無を確認（最新の1件）
-    if let Some((existing_id, existing_title, existing_note, created_at)) = {
-        let mut stmt = db.prepare(&format!(
-            "SELECT id, title, note, created_at FROM {} WHERE url = ?1 ORDER BY created_at DESC LIMIT 1",
-            COLLECTION
-        ))?;
-        stmt
-            .query_row(params![url], |row| {
-                Ok((
-                    row.get::<_, String>(0)?,
-                    row.get::<_, Option<String>>(1)?,
-                    row.get::<_, Option<String>>(2)?,
-                    row.get::<_, String>(3)?,
-                ))
-            })
-            .optional()?
-    } {
-        // 更新：username/passwordは上書き、title/noteは新規指定があれば上書き、未指定は既存維持
-        let new_title = title.map(|s| s.to_string()).or(existing_title);
-        let new_note = note.map(|s| s.to_string()).or(existing_note);
-        let enc_pw = encrypt_for_id(&existing_id, password)?;
-        db.execute(
-            &format!("UPDATE {} SET username=?1, password=?2, title=?3, note=?4 WHERE id=?5", COLLECTION),
-            params![username, enc_pw, new_title, new_note, existing_id],
-        )?;
-        return Ok(PasswordRecord {
-            id: existing_id,
-            url: url.to_string(),
-            username: username.to_string(),
-            password: enc_pw,
-            title: new_title,
-            note: new_note,
-            created_at,
-        });
+// `key="value"` 形式で1行にする（id はクォートなし、値は output::quote でエスケープ、null は省略、配列はカンマ区切り）
+fn format_columns(obj: &serde_json::Map<String, serde_json::Value>) -> String {
+    let mut parts = Vec::new();
+    for (k, v) in obj {
+        let text = match v {
+            serde_json::Value::Null => continue,
+            serde_json::Value::String(s) => s.clone(),
+            serde_json::Value::Array(items) => {
+                if items.is_empty() { continue; }
+                items.iter().map(|i| i.as_str().map(|s| s.to_string()).unwrap_or_else(|| i.to_string())).collect::<Vec<_>>().join(",")
+            }
+            other => other.to_string(),
+        };
+        if k == "id" { parts.push(format!("id={}", text)); } else { parts.push(format!("{}={}", k, output::quote(&text))); }
     }
-
-    // 新規挿入
-    let id = uuid::Uuid::new_v4().to_string();
-    let rec = PasswordRecord {
-        id: id.clone(),
-        url: url.to_string(),
-        username: username.to_string(),
-        password: encrypt_for_id(&id, password)?,
-        title: title.map(|s| s.to_string()),
-        note: note.map(|s| s.to_string()),
-        created_at: Utc::now().to_rfc3339(),
-    };
-    db.execute(
-        &format!(
-            "INSERT INTO {} (id, url, username, password, title, note, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-            COLLECTION
-        ),
-        params![rec.id, rec.url, rec.username, rec.password, rec.title, rec.note, rec.created_at],
-    )?;
-    Ok(rec)
-}
-
-async fn fetch_by_url(db: &Connection, url: &str) -> Result<Vec<(String, String, Option<String>, Option<String>)>, Box<dyn std::error::Error + Send + Sync>> {
-    let mut stmt = db.prepare(&format!("SELECT id, username, password, title, note FROM {} WHERE url = ?1", COLLECTION))?;
-    let rows = stmt.query_map(params![url], |row| {
-        let id: String = row.get(0)?;
-        let username: String = row.get(1)?;
-        let enc_pw: String = row.get(2)?;
-        let title: Option<String> = row.get(3)?;
-        let note: Option<String> = row.get(4)?;
-        let pw = decrypt_for_id(&id, &enc_pw).unwrap_or(enc_pw);
-        Ok((username, pw, title, note))
-    })?;
-    let mut out = Vec::new();
-    for r in rows { out.push(r?); }
-    Ok(out)
+    parts.join(" ")
 }
 
-async fn search_entries(db: &Connection, keyword: &str) -> Result<Vec<(String, String, String, String, Option<String>, Option<String>)>, Box<dyn std::error::Error + Send + Sync>> {
-    let like = format!("%{}%", keyword);
-    let mut stmt = db.prepare(&format!(
-        "SELECT id, url, username, password, title, note FROM {} WHERE 
-            id LIKE ?1 OR url LIKE ?1 OR username LIKE ?1 OR IFNULL(title,'') LIKE ?1 OR IFNULL(note,'') LIKE ?1 ",
-        COLLECTION
-    ))?;
-    let rows = stmt.query_map(params![like], |row| {
-        let id: String = row.get(0)?;
-        let url: String = row.get(1)?;
-        let username: String = row.get(2)?;
-        let enc_pw: String = row.get(3)?;
-        let title: Option<String> = row.get(4)?;
-        let note: Option<String> = row.get(5)?;
-        let pw = decrypt_for_id(&id, &enc_pw).unwrap_or(enc_pw);
-        Ok((id, url, username, pw, title, note))
-    })?;
-    let mut out: Vec<(String, String, String, String, Option<String>, Option<String>)> = Vec::new();
-    for r in rows { out.push(r?); }
-    // created_at降順の代わりに id 降順で簡易並び替え
-    out.sort_by(|a, b| b.0.cmp(&a.0));
-    Ok(out)
+fn push_folder_and_tags(line: &mut String, folder: Option<&str>, tags: &[String]) {
+    if let Some(f) = folder { line.push_str(&format!(" folder={}", output::quote(f))); }
+    if !tags.is_empty() { line.push_str(&format!(" tags={}", output::quote(&tags.join(",")))); }
 }
 
-async fn update_entry(
-    db: &Connection,
-    id: &str,
-    url: Option<&str>,
-    username: Option<&str>,
-    password: Option<&str>,
-    title: Option<&str>,
-    note: Option<&str>,
-) -> Result<PasswordRecord, Box<dyn std::error::Error + Send + Sync>> {
-    // 現在のレコードを取得
-    let mut stmt = db.prepare(&format!("SELECT id, url, username, password, title, note, created_at FROM {} WHERE id = ?1", COLLECTION))?;
-    let mut current: PasswordRecord = stmt
-        .query_row(params![id], |row| {
-            Ok(PasswordRecord {
-                id: row.get(0)?,
-                url: row.get(1)?,
-                username: row.get(2)?,
-                password: row.get(3)?,
-                title: row.get(4)?,
-                note: row.get(5)?,
-                created_at: row.get(6)?,
-            })
-        })
-        .optional()? // Option<PasswordRecord>
-        .ok_or_else(|| format!("id={} が見つかりません", id))?;
-    if let Some(v) = url { current.url = v.to_string(); }
-    if let Some(v) = username { current.username = v.to_string(); }
-    if let Some(v) = password { current.password = encrypt_for_id(&current.id, v)?; }
-    if let Some(v) = title { current.title = Some(v.to_string()); }
-    if let Some(v) = note { current.note = Some(v.to_string()); }
-
-    db.execute(
-        &format!(
-            "UPDATE {} SET url=?1, username=?2, password=?3, title=?4, note=?5 WHERE id=?6",
-            COLLECTION
-        ),
-        params![current.url, current.username, current.password, current.title, current.note, id],
-    )?;
-    Ok(current)
-}
+// cli::Command::ClearClipboard のコマンド名
+const CLIPBOARD_CLEAR_CMD: &str = "__clear-clipboard";
 
-async fn delete_entry(db: &Connection, id: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    db.execute(&format!("DELETE FROM {} WHERE id = ?1", COLLECTION), params![id])?;
+// コピー後、消去用に自分自身を切り離して起動する。値のハッシュは ps などで見える
+// 引数ではなくパイプ（子の標準入力）で渡す
+fn copy_to_clipboard(text: &str, clear_after: u64) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut clip = clipboard::backend()?;
+    clip.set(text)?;
+    if clear_after > 0 {
+        let mut child = std::process::Command::new(env::current_exe()?)
+            .args([CLIPBOARD_CLEAR_CMD, &clear_after.to_string()])
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn()?;
+        if let Some(mut pipe) = child.stdin.take() { writeln!(pipe, "{}", clipboard::fingerprint(text))?; }
+    }
     Ok(())
 }
 
-fn print_add_usage_and_exit() {
-    eprintln!(
-        "使い方: tsupasswd add <url> <username> [password|length] [--title <title>] [--note <note>]"
-    );
-    std::process::exit(1);
+// 補完候補の URL。--vault は補完中のコマンドラインから拾い、有効なセッションがあるときだけ保管庫を読む
+fn completion_urls() -> Vec<String> {
+    let words: Vec<String> = env::args().skip_while(|a| a != "--").collect();
+    let flag = words
+        .iter()
+        .position(|w| w == "--vault")
+        .and_then(|i| words.get(i + 1).cloned())
+        .or_else(|| words.iter().find_map(|w| w.strip_prefix("--vault=").map(str::to_string)));
+    let Ok(loc) = location::resolve(flag.as_deref(), settings().vault.as_deref()) else { return Vec::new() };
+    if !loc.db_path.exists() || LOCATION.set(loc).is_err() || ensure_authenticated().is_err() { return Vec::new(); }
+    let opened = if vault_location().is_file_vault() {
+        env::var("AUTH_SECRET").map_err(|_| ()).and_then(|secret| FileStore::open(&db_file_path(), &secret).map(|store| Vault::with_store(Box::new(store))).map_err(|_| ()))
+    } else {
+        Vault::open(&db_file_path()).map_err(|_| ())
+    };
+    let mut urls: Vec<String> = match opened.map(|db| db.list(&ListOptions::default())) {
+        Ok(Ok(entries)) => entries.into_iter().map(|e| e.url).collect(),
+        _ => Vec::new(),
+    };
+    urls.sort();
+    urls.dedup();
+    urls
 }
 
 fn db_file_path() -> PathBuf {
-    let home = env::var("HOME").unwrap_or_else(|_| ".".to_string());
-    PathBuf::from(home).join(".tsupasswd_db").join("passwords.db")
+    vault_location().db_path.clone()
 }
 
-fn export_csv(db: &Connection, path: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let mut wtr = WriterBuilder::new().from_path(path)?;
-    // ヘッダー: id,url,username,password,title,note,created_at（passwordは平文で出力）
-    wtr.write_record(["id", "url", "username", "password", "title", "note", "created_at"])?;
-    let mut stmt = db.prepare(&format!(
-        "SELECT id, url, username, password, title, note, created_at FROM {} ORDER BY created_at DESC",
-        COLLECTION
-    ))?;
-    let rows = stmt.query_map([], |row| {
-        Ok((
-            row.get::<_, String>(0)?,
-            row.get::<_, String>(1)?,
-            row.get::<_, String>(2)?,
-            row.get::<_, String>(3)?,
-            row.get::<_, Option<String>>(4)?,
-            row.get::<_, Option<String>>(5)?,
-            row.get::<_, String>(6)?,
-        ))
-    })?;
-    for r in rows {
-        let (id, url, username, enc_pw, title, note, created_at) = r?;
-        let pw = decrypt_for_id(&id, &enc_pw).unwrap_or(enc_pw);
-        wtr.write_record([
-            id,
-            url,
-            username,
-            pw,
-            title.unwrap_or_default(),
-            note.unwrap_or_default(),
-            created_at,
-        ])?;
-    }
-    wtr.flush()?;
-    Ok(())
-}
 
-async fn import_csv(db: &Connection, path: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let mut rdr = ReaderBuilder::new().has_headers(true).from_path(path)?;
-    let headers = rdr.headers()?.clone();
-    for result in rdr.records() {
-        let rec = result?; // CSVレコード
-        // ヘッダー名で取得（存在しない場合は位置依存でフォールバック）
-        let get = |name: &str| headers.iter().position(|h| h == name).and_then(|i| rec.get(i).map(|s| s.to_string()));
-        let url = get("url").or_else(|| rec.get(0).map(|s| s.to_string())).ok_or("url がありません")?;
-        let username = get("username").or_else(|| rec.get(1).map(|s| s.to_string())).ok_or("username がありません")?;
-        let password = get("password").or_else(|| rec.get(2).map(|s| s.to_string())).ok_or("password がありません")?;
-        let title = get("title").or_else(|| rec.get(3).map(|s| s.to_string()));
-        let note = get("note").or_else(|| rec.get(4).map(|s| s.to_string()));
-        // created_at は無視して現在時刻を使用
-        let _ = insert_password(db, &url, &username, &password, title.as_deref(), note.as_deref()).await?;
+// 保管庫を開き、AUTH_SECRET でロックを解除する（未設定ならロックしたまま。復号が必要な操作は失敗する）。
+// .vault のパスは暗号化ファイルとして開くため、開く時点で AUTH_SECRET が必要
+fn open_vault() -> Vault {
+    let opened = if vault_location().is_file_vault() {
+        match env::var("AUTH_SECRET") {
+            Ok(secret) => FileStore::open(&db_file_path(), &secret).map(|store| Vault::with_store(Box::new(store))),
+            Err(_) => fail(Failure::Unauthenticated, t!("保管庫ファイル（.{}）を開くには AUTH_SECRET が必要です", location::FILE_VAULT_EXT)),
+        }
+    } else {
+        Vault::open(&db_file_path())
+    };
+    let mut vault = match opened {
+        Ok(v) => v,
+        Err(e) => fail(Failure::of(&e), t!("DB初期化に失敗しました: {}", e)),
+    };
+    if let Ok(secret) = env::var("AUTH_SECRET")
+        && let Err(e) = vault.unlock(&secret)
+    {
+        fail(Failure::of(&e), t!("保管庫のロックを解除できません: {}", e));
     }
-    Ok(())
+    vault
 }
 
-
-
-
-
-
-
-
-fn derive_key_for_id(id: &str) -> Result<[u8; 32], String> {
-    let secret = env::var("AUTH_SECRET").map_err(|_| "環境変数 AUTH_SECRET が未設定です".to_string())?;
-    let hk = Hkdf::<Sha256>::new(Some(id.as_bytes()), secret.as_bytes());
-    let mut okm = [0u8; 32];
-    hk.expand(b"password-at-rest", &mut okm).map_err(|_| "鍵導出に失敗しました".to_string())?;
-    Ok(okm)
-}
-
-fn encrypt_for_id(id: &str, plaintext: &str) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-    let key_bytes = derive_key_for_id(id)?;
-    let cipher = ChaCha20Poly1305::new_from_slice(&key_bytes)
-        .map_err(|e| format!("cipher init error: {}", e))?;
-    let mut nonce = [0u8; 12];
-    // ランダムノンス
-    let rnd = OsRng.next_u64();
-    // 12バイトに充填（u64 + u32）
-    nonce[..8].copy_from_slice(&rnd.to_le_bytes());
-    nonce[8..].copy_from_slice(&(OsRng.next_u32()).to_le_bytes());
-    let ct = cipher
-        .encrypt((&nonce).into(), plaintext.as_bytes())
-        .map_err(|e| format!("encrypt error: {}", e))?;
-    let mut buf = Vec::with_capacity(12 + ct.len());
-    buf.extend_from_slice(&nonce);
-    buf.extend_from_slice(&ct);
-    Ok(B64.encode(buf))
+// update / delete の対象を決める。条件に複数件が一致した場合は --all-matching を案内する
+fn resolve_or_exit(db: &Vault, selectors: &[String], all_matching: bool, action: &str) -> Vec<Target> {
+    match db.resolve(selectors, all_matching) {
+        Ok(v) => v,
+        Err(Error::Ambiguous { candidates, .. }) if matches!(selector::parse(selectors), Ok(selector::Target::Terms(_))) => {
+            let message = t!(
+                "{}に失敗しました: 条件に {} 件が一致しました（すべてを対象にするには --all-matching を指定）:\n{}",
+                action,
+                candidates.len(),
+                format_targets(&candidates)
+            );
+            fail(Failure::Ambiguous, message)
+        }
+        Err(e) => fail(Failure::of(&e), t!("{}に失敗しました: {}", action, e)),
+    }
 }
 
-fn decrypt_for_id(id: &str, b64: &str) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-    let data = B64.decode(b64)?;
-    if data.len() < 12 { return Err("データ長が不正です".into()); }
-    let (nonce, ct) = data.split_at(12);
-    let key_bytes = derive_key_for_id(id)?;
-    let cipher = ChaCha20Poly1305::new_from_slice(&key_bytes)
-        .map_err(|e| format!("cipher init error: {}", e))?;
-    let pt = cipher
-        .decrypt(nonce.into(), ct)
-        .map_err(|e| format!("decrypt error: {}", e))?;
-    Ok(String::from_utf8(pt).unwrap_or_default())
+fn format_targets(targets: &[Target]) -> String {
+    targets.iter().map(|t| format!("  {}", t)).collect::<Vec<_>>().join("\n")
 }
@@ -0,0 +1,289 @@
+// ワンタイムパスワード（RFC 6238 TOTP / RFC 4226 HOTP / Steam Guard）の計算と otpauth:// URI の解析
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+use sha2::{Sha256, Sha512};
+
+const BASE32_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm { Sha1, Sha256, Sha512 }
+
+impl Algorithm {
+    fn parse(s: &str) -> Result<Self, String> {
+        match s.to_ascii_uppercase().replace('-', "").as_str() {
+            "SHA1" => Ok(Algorithm::Sha1),
+            "SHA256" => Ok(Algorithm::Sha256),
+            "SHA512" => Ok(Algorithm::Sha512),
+            _ => Err(t!("未対応のアルゴリズムです: {}", s)),
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Algorithm::Sha1 => "SHA1",
+            Algorithm::Sha256 => "SHA256",
+            Algorithm::Sha512 => "SHA512",
+        }
+    }
+}
+
+// TOTP は時刻、HOTP は DB に保存したカウンタを移動因子に使う
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind { Totp, Hotp }
+
+// コードの表記。Steam Guard は独自の5文字アルファベットを使う
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding { Decimal, Steam }
+
+const STEAM_ALPHABET: &[u8] = b"23456789BCDFGHJKMNPQRTVWXY";
+const STEAM_DIGITS: u32 = 5;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OtpParams {
+    pub kind: Kind,
+    pub encoding: Encoding,
+    pub secret: Vec<u8>,
+    pub algorithm: Algorithm,
+    pub digits: u32,
+    pub period: u64,
+    pub counter: u64,
+    pub label: Option<String>,
+    pub issuer: Option<String>,
+}
+
+impl OtpParams {
+    fn with_secret(secret: Vec<u8>) -> Self {
+        OtpParams {
+            kind: Kind::Totp,
+            encoding: Encoding::Decimal,
+            secret,
+            algorithm: Algorithm::Sha1,
+            digits: 6,
+            period: 30,
+            counter: 0,
+            label: None,
+            issuer: None,
+        }
+    }
+
+    // base32 のシード、otpauth://totp|hotp/... URI、steam://<base32> を解析
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let spec = spec.trim();
+        if let Some(rest) = strip_prefix_ignore_case(spec, "otpauth://") {
+            return Self::parse_uri(rest);
+        }
+        if let Some(rest) = strip_prefix_ignore_case(spec, "steam://") {
+            let mut params = Self::with_secret(base32_decode(rest)?);
+            params.set_steam();
+            return Ok(params);
+        }
+        Ok(Self::with_secret(base32_decode(spec)?))
+    }
+
+    fn set_steam(&mut self) {
+        self.kind = Kind::Totp;
+        self.encoding = Encoding::Steam;
+        self.algorithm = Algorithm::Sha1;
+        self.digits = STEAM_DIGITS;
+        self.period = 30;
+    }
+
+    fn parse_uri(rest: &str) -> Result<Self, String> {
+        let (kind, rest) = rest.split_once('/').ok_or_else(|| t!("otpauth URI の形式が不正です"))?;
+        let kind = match kind.to_ascii_lowercase().as_str() {
+            "totp" => Kind::Totp,
+            "hotp" => Kind::Hotp,
+            _ => return Err(t!("未対応の OTP 種別です: {}", kind)),
+        };
+        let (label, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let label = percent_decode(label)?;
+        let mut params = Self::with_secret(Vec::new());
+        params.kind = kind;
+        params.label = if label.is_empty() { None } else { Some(label) };
+        let mut has_secret = false;
+        let mut has_counter = false;
+        let mut steam = false;
+        for pair in query.split('&').filter(|p| !p.is_empty()) {
+            let (k, v) = pair.split_once('=').unwrap_or((pair, ""));
+            let v = percent_decode(v)?;
+            match k.to_ascii_lowercase().as_str() {
+                "secret" => { params.secret = base32_decode(&v)?; has_secret = true; }
+                "algorithm" => params.algorithm = Algorithm::parse(&v)?,
+                "digits" => {
+                    params.digits = v.parse().map_err(|_| t!("digits が不正です: {}", v))?;
+                }
+                "period" => {
+                    params.period = v.parse().map_err(|_| t!("period が不正です: {}", v))?;
+                    if params.period == 0 { return Err(t!("period は 1 以上で指定してください")); }
+                }
+                "counter" => {
+                    params.counter = v.parse().map_err(|_| t!("counter が不正です: {}", v))?;
+                    has_counter = true;
+                }
+                "encoder" => match v.to_ascii_lowercase().as_str() {
+                    "steam" => steam = true,
+                    "" | "decimal" => {}
+                    _ => return Err(t!("未対応のエンコーダです: {}", v)),
+                },
+                "issuer" => params.issuer = Some(v),
+                _ => {} // image 等の未知パラメータは無視
+            }
+        }
+        if !has_secret { return Err(t!("otpauth URI に secret がありません")); }
+        if kind == Kind::Hotp && !has_counter { return Err(t!("hotp の URI には counter が必要です")); }
+        if steam {
+            if kind == Kind::Hotp { return Err(t!("Steam 形式は TOTP のみ対応しています")); }
+            params.set_steam();
+        } else if !(6..=8).contains(&params.digits) {
+            // Steam 以外の桁数は RFC 4226 の範囲に限る
+            return Err(t!("digits は 6〜8 で指定してください: {}", params.digits));
+        }
+        Ok(params)
+    }
+
+    // 保存・エクスポート用の正規化した otpauth URI
+    pub fn to_uri(&self) -> String {
+        let kind = match self.kind { Kind::Totp => "totp", Kind::Hotp => "hotp" };
+        let mut uri = format!(
+            "otpauth://{}/{}?secret={}&algorithm={}&digits={}",
+            kind,
+            percent_encode(self.label.as_deref().unwrap_or("")),
+            base32_encode(&self.secret),
+            self.algorithm.as_str(),
+            self.digits,
+        );
+        match self.kind {
+            Kind::Totp => uri.push_str(&format!("&period={}", self.period)),
+            Kind::Hotp => uri.push_str(&format!("&counter={}", self.counter)),
+        }
+        if self.encoding == Encoding::Steam {
+            uri.push_str("&encoder=steam");
+        }
+        if let Some(issuer) = &self.issuer {
+            uri.push_str("&issuer=");
+            uri.push_str(&percent_encode(issuer));
+        }
+        uri
+    }
+
+    // 指定時刻（UNIX秒）のコードと、次の切替までの残り秒数
+    pub fn totp_at(&self, unix_time: u64) -> (String, u64) {
+        let counter = unix_time / self.period;
+        let remaining = self.period - unix_time % self.period;
+        (self.code_for_counter(counter), remaining)
+    }
+
+    // 移動因子からコードを計算（表記は encoding に従う）
+    pub fn code_for_counter(&self, counter: u64) -> String {
+        let bin = truncate(&self.secret, counter, self.algorithm);
+        match self.encoding {
+            Encoding::Decimal => {
+                format!("{:0width$}", bin % 10u32.pow(self.digits), width = self.digits as usize)
+            }
+            Encoding::Steam => {
+                let mut v = bin;
+                let mut code = String::with_capacity(STEAM_DIGITS as usize);
+                for _ in 0..STEAM_DIGITS {
+                    code.push(STEAM_ALPHABET[v as usize % STEAM_ALPHABET.len()] as char);
+                    v /= STEAM_ALPHABET.len() as u32;
+                }
+                code
+            }
+        }
+    }
+}
+
+// 大文字小文字を区別せずに接頭辞を外す（バイト位置で切ると多バイト文字の途中になりうるため get で確かめる）
+fn strip_prefix_ignore_case<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+    s.get(..prefix.len()).filter(|p| p.eq_ignore_ascii_case(prefix)).map(|_| &s[prefix.len()..])
+}
+
+// RFC 4226 の動的切り詰め（31ビットの整数を返す）
+fn truncate(secret: &[u8], counter: u64, algorithm: Algorithm) -> u32 {
+    let msg = counter.to_be_bytes();
+    let mac = match algorithm {
+        Algorithm::Sha1 => hmac_digest::<Hmac<Sha1>>(secret, &msg),
+        Algorithm::Sha256 => hmac_digest::<Hmac<Sha256>>(secret, &msg),
+        Algorithm::Sha512 => hmac_digest::<Hmac<Sha512>>(secret, &msg),
+    };
+    let offset = (mac[mac.len() - 1] & 0x0f) as usize;
+    u32::from_be_bytes([mac[offset] & 0x7f, mac[offset + 1], mac[offset + 2], mac[offset + 3]])
+}
+
+fn hmac_digest<M: Mac + hmac::digest::KeyInit>(key: &[u8], msg: &[u8]) -> Vec<u8> {
+    let mut mac = <M as hmac::digest::KeyInit>::new_from_slice(key).expect("HMAC は任意長の鍵を受け付ける");
+    mac.update(msg);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn base32_decode(s: &str) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    let mut buf: u64 = 0;
+    let mut bits = 0u32;
+    for c in s.chars().filter(|c| !c.is_whitespace() && *c != '-' && *c != '=') {
+        // as u8 は ASCII 以外を切り詰めて別の文字にするため、ASCII のときだけ照合する
+        let v = BASE32_ALPHABET
+            .iter()
+            .position(|&a| c.is_ascii() && a == c.to_ascii_uppercase() as u8)
+            .ok_or_else(|| t!("base32 として不正な文字です: {}", c))?;
+        buf = (buf << 5) | v as u64;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buf >> bits) as u8);
+            buf &= (1 << bits) - 1;
+        }
+    }
+    if out.is_empty() { return Err(t!("シークレットが空です")); }
+    Ok(out)
+}
+
+fn base32_encode(data: &[u8]) -> String {
+    let mut out = String::new();
+    let mut buf: u64 = 0;
+    let mut bits = 0u32;
+    for &b in data {
+        buf = (buf << 8) | b as u64;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(BASE32_ALPHABET[((buf >> bits) & 0x1f) as usize] as char);
+        }
+        buf &= (1 << bits) - 1;
+    }
+    if bits > 0 {
+        out.push(BASE32_ALPHABET[((buf << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    out
+}
+
+fn percent_decode(s: &str) -> Result<String, String> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).map_err(|_| t!("URI のエスケープが不正です"))?;
+                out.push(u8::from_str_radix(hex, 16).map_err(|_| t!("URI のエスケープが不正です"))?);
+                i += 3;
+            }
+            b'%' => return Err(t!("URI のエスケープが不正です")),
+            b'+' => { out.push(b' '); i += 1; }
+            b => { out.push(b); i += 1; }
+        }
+    }
+    String::from_utf8(out).map_err(|_| t!("URI が UTF-8 ではありません"))
+}
+
+fn percent_encode(s: &str) -> String {
+    let mut out = String::new();
+    for b in s.bytes() {
+        if b.is_ascii_alphanumeric() || b"-._~:@".contains(&b) {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{:02X}", b));
+        }
+    }
+    out
+}
@@ -0,0 +1,191 @@
+// get の項目の取り出し（--field）と出力形式（--format）
+//
+//   --field NAME       1項目の値をそのまま出す（url / username / password / title / note、それ以外は任意フィールド名）
+//   --format TEMPLATE  {username} などを値で置き換える。{field.<名前>} は任意フィールド、{{ と }} は波括弧、
+//                      \n \t \\ は改行・タブ・\
+//   --format shell     export USERNAME='...'（sh の eval 用。' は '\'' にする）
+//   --format env       USERNAME=...（docker --env-file などの1行1変数。引用しないため改行を含む値はエラー）
+//   --format dotenv    USERNAME="..."（\ " $ と改行をエスケープ）
+//
+// shell / env / dotenv の変数名は URL / USERNAME / PASSWORD / TITLE / NOTE と任意フィールドの FIELD_<名前>
+// （英数字以外は _ にして大文字）。未設定の title / note は空文字。
+// 別の任意フィールドが同じ変数名になる場合（a-b と a_b など）は、どちらかを黙って上書きせずエラーにする
+
+use tsupasswd::Entry;
+
+pub const STANDARD_FIELDS: [&str; 5] = ["url", "username", "password", "title", "note"];
+
+#[derive(Debug, Clone)]
+pub enum Format {
+    Template(Vec<Piece>),
+    Shell,
+    Env,
+    Dotenv,
+}
+
+#[derive(Debug, Clone)]
+pub enum Piece {
+    Text(String),
+    Field(String),
+}
+
+// 出力できなかった理由
+pub enum RenderError {
+    // レコードにない任意フィールド
+    MissingField(String),
+    // env 形式で表せない値（改行を含む）
+    Multiline(String),
+    // 同じ変数名になる2つの任意フィールド（変数名, フィールド名, フィールド名）
+    Collision(String, String, String),
+}
+
+impl Format {
+    pub fn parse(s: &str) -> Result<Format, String> {
+        match s {
+            "shell" => return Ok(Format::Shell),
+            "env" => return Ok(Format::Env),
+            "dotenv" => return Ok(Format::Dotenv),
+            _ => {}
+        }
+        let mut pieces = Vec::new();
+        let mut text = String::new();
+        let mut chars = s.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(t!("テンプレートに閉じていない { があります")),
+                        }
+                    }
+                    let known = STANDARD_FIELDS.contains(&name.as_str())
+                        || name.strip_prefix("field.").is_some_and(|n| !n.is_empty());
+                    if !known {
+                        return Err(t!("テンプレートに不明な項目があります: {}（url / username / password / title / note / field.<名前>）", name));
+                    }
+                    if !text.is_empty() { pieces.push(Piece::Text(std::mem::take(&mut text))); }
+                    pieces.push(Piece::Field(name));
+                }
+                '}' => return Err(t!("テンプレートに対応する { のない } があります")),
+                '\\' => match chars.peek() {
+                    Some('n') => { chars.next(); text.push('\n'); }
+                    Some('t') => { chars.next(); text.push('\t'); }
+                    Some('\\') => { chars.next(); text.push('\\'); }
+                    _ => text.push('\\'),
+                },
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() { pieces.push(Piece::Text(text)); }
+        Ok(Format::Template(pieces))
+    }
+
+    // 末尾の改行は含めない
+    pub fn render(&self, entry: &Entry) -> Result<String, RenderError> {
+        match self {
+            Format::Template(pieces) => {
+                let mut out = String::new();
+                for piece in pieces {
+                    match piece {
+                        Piece::Text(t) => out.push_str(t),
+                        Piece::Field(name) => out.push_str(field(entry, name).ok_or_else(|| RenderError::MissingField(name.clone()))?),
+                    }
+                }
+                Ok(out)
+            }
+            Format::Shell => lines(entry, |name, value| format!("export {}={}", name, shell_quote(value))),
+            Format::Env => {
+                if let Some((name, _)) = variables(entry)?.into_iter().find(|(_, v)| v.contains(['\n', '\r'])) {
+                    return Err(RenderError::Multiline(name));
+                }
+                lines(entry, |name, value| format!("{}={}", name, value))
+            }
+            Format::Dotenv => lines(entry, |name, value| format!("{}={}", name, dotenv_quote(value))),
+        }
+    }
+}
+
+// 項目名（url などの標準の項目、field.<名前> または <名前> の任意フィールド）の値。title / note の未設定は空文字
+pub fn field<'a>(entry: &'a Entry, name: &str) -> Option<&'a str> {
+    match name {
+        "url" => Some(&entry.url),
+        "username" => Some(&entry.username),
+        "password" => Some(&entry.password),
+        "title" => Some(entry.title.as_deref().unwrap_or("")),
+        "note" => Some(entry.note.as_deref().unwrap_or("")),
+        _ => {
+            let name = name.strip_prefix("field.").unwrap_or(name);
+            entry.fields.iter().find(|f| f.name == name).map(|f| f.value.as_str())
+        }
+    }
+}
+
+// shell / env / dotenv で出す変数（名前, 値）
+fn variables(entry: &Entry) -> Result<Vec<(String, &str)>, RenderError> {
+    let mut vars: Vec<(String, &str)> = STANDARD_FIELDS
+        .iter()
+        .map(|name| (name.to_uppercase(), field(entry, name).unwrap_or("")))
+        .collect();
+    for (i, f) in entry.fields.iter().enumerate() {
+        let name = variable_name(&f.name);
+        if let Some(other) = entry.fields[..i].iter().find(|o| variable_name(&o.name) == name) {
+            return Err(RenderError::Collision(name, other.name.clone(), f.name.clone()));
+        }
+        vars.push((name, &f.value));
+    }
+    Ok(vars)
+}
+
+// 任意フィールドの変数名 FIELD_<名前>
+fn variable_name(field: &str) -> String {
+    let name: String = field.chars().map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' }).collect();
+    format!("FIELD_{}", name)
+}
+
+fn lines(entry: &Entry, line: impl Fn(&str, &str) -> String) -> Result<String, RenderError> {
+    Ok(variables(entry)?.iter().map(|(name, value)| line(name, value)).collect::<Vec<_>>().join("\n"))
+}
+
+// sh の単一引用符。中の ' は '\'' で閉じて挟む
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+// 既定の出力（key="value"）の二重引用符。\ " と改行・タブをエスケープして1行に収める
+pub fn quote(s: &str) -> String {
+    escape(s, false)
+}
+
+// .env の二重引用符。変数展開されないよう $ もエスケープする
+fn dotenv_quote(s: &str) -> String {
+    escape(s, true)
+}
+
+fn escape(s: &str, dollar: bool) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '$' if dollar => out.push_str("\\$"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
@@ -0,0 +1,30 @@
+// private_file: 秘密を含むファイル（inject・export の書き出し先）を他のユーザから読めない 0600 で書く
+//
+// 一時ファイルに書いてから置き換える（失敗しても書きかけのファイルを残さない）。
+// 一時ファイルは前回の残りやシンボリックリンクを消してから新規に作り、既存のファイル越しに書かない
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+pub fn write(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let name = path.file_name().ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, t!("ファイル名がありません")))?;
+    let tmp = path.with_file_name(format!(".{}.tmp", name.to_string_lossy()));
+    match fs::remove_file(&tmp) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+    let mut opts = fs::OpenOptions::new();
+    opts.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        opts.mode(0o600);
+    }
+    let mut file = opts.open(&tmp)?;
+    let result = file.write_all(contents).and_then(|_| file.sync_all()).and_then(|_| fs::rename(&tmp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
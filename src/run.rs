@@ -0,0 +1,124 @@
+// run: 保管庫の値を環境変数にして子プロセスを実行する
+//
+// 値は子プロセスの環境変数にだけ渡し、標準出力・監査ログには出さない。
+// 子プロセスには保管庫の鍵になる AUTH_SECRET と TSUPASSWD_* を引き継がない。
+// --redact では子プロセスの標準出力・標準エラーをパイプで受け、値を <redacted> に置き換えてから流す
+// （読み込みの区切りで値が分かれても置き換えられるよう、値の先頭と一致する末尾は続きを読むまで保留する）
+
+use std::ffi::OsString;
+use std::io::{self, Read, Write};
+use std::process::{Command, ExitStatus, Stdio};
+use std::thread;
+
+pub const MASK: &[u8] = b"<redacted>";
+
+// 子プロセスの環境から外す変数（名前が TSUPASSWD_ で始まるものも外す）
+const SCRUBBED: &[&str] = &["AUTH_SECRET"];
+
+// 保管庫の値の参照 url:<URL>[#<項目>]（項目は get --field と同じ。省略時は password）
+#[derive(Debug, Clone)]
+pub struct Reference {
+    pub url: String,
+    pub field: String,
+}
+
+impl Reference {
+    pub fn parse(s: &str) -> Result<Reference, String> {
+        let invalid = || t!("参照は url:<URL>#<項目> の形式で指定してください: {}", s);
+        let rest = s.strip_prefix("url:").ok_or_else(invalid)?;
+        // URL 自体が # を含む場合は最後の # 以降を項目とみなす
+        let (url, field) = rest.rsplit_once('#').unwrap_or((rest, "password"));
+        if url.is_empty() || field.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Reference { url: url.to_string(), field: field.to_string() })
+    }
+}
+
+// 子プロセスの終了コードを返す（シグナルで終了した場合は 128 + シグナル番号）
+pub fn exec(command: &[OsString], vars: &[(String, String)], redact: bool) -> io::Result<i32> {
+    let mut cmd = Command::new(&command[0]);
+    cmd.args(&command[1..]);
+    for (name, _) in std::env::vars_os() {
+        if SCRUBBED.iter().any(|s| name == *s) || name.to_string_lossy().starts_with("TSUPASSWD_") {
+            cmd.env_remove(&name);
+        }
+    }
+    cmd.envs(vars.iter().map(|(name, value)| (name, value)));
+    // 端末の Ctrl-C は子プロセスにも届くため、こちらは終了せず子プロセスの終了を待つ
+    tokio::spawn(async { while tokio::signal::ctrl_c().await.is_ok() {} });
+    if !redact {
+        return cmd.status().map(exit_code);
+    }
+
+    // ある値が別の値を含む場合に備え、長い値から照合する
+    let mut secrets: Vec<&[u8]> = vars.iter().map(|(_, value)| value.as_bytes()).filter(|v| !v.is_empty()).collect();
+    secrets.sort_by_key(|s| std::cmp::Reverse(s.len()));
+    let mut child = cmd.stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+    let mut stderr = child.stderr.take().expect("stderr is piped");
+    let secrets = &secrets;
+    thread::scope(|s| {
+        s.spawn(move || pump(&mut stdout, io::stdout(), secrets));
+        s.spawn(move || pump(&mut stderr, io::stderr(), secrets));
+        child.wait()
+    })
+    .map(exit_code)
+}
+
+fn exit_code(status: ExitStatus) -> i32 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return 128 + signal;
+        }
+    }
+    status.code().unwrap_or(1)
+}
+
+// 出力先に書けなくなっても、子プロセスが詰まらないよう最後まで読み捨てる
+fn pump(input: &mut impl Read, mut out: impl Write, secrets: &[&[u8]]) {
+    let mut pending = Vec::new();
+    let mut buf = [0u8; 8192];
+    loop {
+        let n = match input.read(&mut buf) {
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(_) => return,
+        };
+        pending.extend_from_slice(&buf[..n]);
+        let done = redact(&mut pending, secrets, n == 0);
+        if out.write_all(&done).and_then(|_| out.flush()).is_err() {
+            let _ = io::copy(input, &mut io::sink());
+            return;
+        }
+        if n == 0 {
+            return;
+        }
+    }
+}
+
+// pending の先頭から、値を置き換えて出力してよい部分を取り出す。
+// 末尾が値の途中までと一致する間は（入力が終わるまで）残しておく
+fn redact(pending: &mut Vec<u8>, secrets: &[&[u8]], eof: bool) -> Vec<u8> {
+    let mut out = Vec::with_capacity(pending.len());
+    let mut i = 0;
+    'scan: while i < pending.len() {
+        let rest = &pending[i..];
+        if !eof && secrets.iter().any(|s| s.len() > rest.len() && s.starts_with(rest)) {
+            break;
+        }
+        for s in secrets {
+            if rest.starts_with(s) {
+                out.extend_from_slice(MASK);
+                i += s.len();
+                continue 'scan;
+            }
+        }
+        out.push(pending[i]);
+        i += 1;
+    }
+    pending.drain(..i);
+    out
+}
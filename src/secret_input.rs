@@ -0,0 +1,74 @@
+// パスワード・認証シークレットの入力元（argv に載せずに渡すための経路）
+//
+//   --prompt          端末でエコーなしに入力（新しいパスワードは確認のため2回）
+//   --password-stdin  標準入力から読む（末尾の改行1つは除く）
+//   --secret-fd N     ファイルディスクリプタ N から読む（例: 3< secret.txt）
+
+use std::io::Read;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SecretSource {
+    Argv(String),
+    Prompt,
+    Stdin,
+    Fd(u32),
+}
+
+// 入力元の指定が重複していればエラー
+pub fn choose(slot: &mut Option<SecretSource>, source: SecretSource) -> Result<(), String> {
+    if slot.is_some() {
+        return Err(t!("パスワードの指定は --prompt / --password-stdin / --secret-fd / 引数のいずれか1つにしてください"));
+    }
+    *slot = Some(source);
+    Ok(())
+}
+
+// argv で渡された場合は警告を出す。confirm は新しいパスワードを設定する場合に指定
+pub fn read(source: SecretSource, label: &str, confirm: bool) -> Result<String, String> {
+    let value = match source {
+        SecretSource::Argv(v) => {
+            eprintln!(
+                "{}",
+                t!("警告: {}をコマンドライン引数で渡すと ps やシェル履歴から見える可能性があります（--prompt / --password-stdin / --secret-fd を推奨）", label)
+            );
+            v
+        }
+        SecretSource::Prompt => {
+            let first = rpassword::prompt_password(format!("{}: ", label)).map_err(|e| t!("{}を入力できません: {}", label, e))?;
+            if confirm {
+                let second = rpassword::prompt_password(t!("{}（確認）: ", label)).map_err(|e| t!("{}を入力できません: {}", label, e))?;
+                if first != second {
+                    return Err(t!("{}が一致しません", label));
+                }
+            }
+            first
+        }
+        SecretSource::Stdin => {
+            let mut buf = String::new();
+            std::io::stdin().read_to_string(&mut buf).map_err(|e| t!("標準入力を読めません: {}", e))?;
+            trim_newline(buf)
+        }
+        SecretSource::Fd(fd) => trim_newline(read_fd(fd)?),
+    };
+    if value.is_empty() {
+        return Err(t!("{}が空です", label));
+    }
+    Ok(value)
+}
+
+fn trim_newline(mut s: String) -> String {
+    if s.ends_with('\n') { s.pop(); }
+    if s.ends_with('\r') { s.pop(); }
+    s
+}
+
+#[cfg(unix)]
+fn read_fd(fd: u32) -> Result<String, String> {
+    // unsafe な from_raw_fd を避け、/dev/fd 経由で開く
+    std::fs::read_to_string(format!("/dev/fd/{}", fd)).map_err(|e| t!("ファイルディスクリプタ {} を読めません: {}", fd, e))
+}
+
+#[cfg(not(unix))]
+fn read_fd(_fd: u32) -> Result<String, String> {
+    Err(t!("--secret-fd はこのOSでは使えません（--password-stdin を使ってください）"))
+}
@@ -0,0 +1,56 @@
+// update / delete の対象指定
+//
+//   3f2a…                      id（git と同様に一意な先頭 MIN_PREFIX 文字以上でも可）
+//   url:example.com user:alice 条件の組み合わせ（すべてを満たすもの）
+//
+// 条件: url:（部分一致）/ user:（完全一致）/ title:（部分一致）/ folder:（配下を含む）/ tag:
+
+pub const MIN_PREFIX: usize = 4;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Term {
+    Url(String),
+    User(String),
+    Title(String),
+    Folder(String),
+    Tag(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Target {
+    Id(String),
+    Terms(Vec<Term>),
+}
+
+fn parse_term(token: &str) -> Result<Option<Term>, String> {
+    let Some((key, value)) = token.split_once(':') else { return Ok(None) };
+    if value.is_empty() {
+        return Err(t!("条件の値が空です: {}", token));
+    }
+    let v = value.to_string();
+    match key {
+        "url" => Ok(Some(Term::Url(v))),
+        "user" => Ok(Some(Term::User(v))),
+        "title" => Ok(Some(Term::Title(v))),
+        "folder" => Ok(Some(Term::Folder(v))),
+        "tag" => Ok(Some(Term::Tag(v))),
+        _ => Err(t!("未知の条件です: {}（url: / user: / title: / folder: / tag: のいずれか）", token)),
+    }
+}
+
+pub fn parse(tokens: &[String]) -> Result<Target, String> {
+    let mut ids = Vec::new();
+    let mut terms = Vec::new();
+    for token in tokens {
+        match parse_term(token)? {
+            Some(term) => terms.push(term),
+            None => ids.push(token.clone()),
+        }
+    }
+    match (ids.len(), terms.is_empty()) {
+        (0, false) => Ok(Target::Terms(terms)),
+        (1, true) => Ok(Target::Id(ids.remove(0))),
+        (0, true) => Err(t!("対象の id または条件（url:… user:… など）を指定してください")),
+        _ => Err(t!("id は1つだけ指定し、条件（url:… user:… など）と組み合わせないでください")),
+    }
+}
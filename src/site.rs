@@ -0,0 +1,54 @@
+// URL の正規化とサイト単位の照合（登録可能ドメインは公開接尾辞リストで判定）
+
+use url::{Host, Url};
+
+// 照合に使う正規化済みホストと登録可能ドメイン
+#[derive(Debug, Clone, PartialEq)]
+pub struct Site {
+    pub host: String,
+    pub domain: String,
+}
+
+// スキーム省略時は https とみなす。ホストは小文字化して先頭の www. を除き、
+// 既定以外のポートは host に含める（パス・クエリは照合に使わない）
+pub fn parse(input: &str) -> Option<Site> {
+    let input = input.trim();
+    if input.is_empty() { return None; }
+    let with_scheme = if input.contains("://") { input.to_string() } else { format!("https://{}", input) };
+    let url = Url::parse(&with_scheme).ok()?;
+    let bare = match url.host()? {
+        Host::Domain(d) => d.trim_end_matches('.').to_string(),
+        Host::Ipv4(ip) => ip.to_string(),
+        Host::Ipv6(ip) => format!("[{}]", ip),
+    };
+    let bare = bare.strip_prefix("www.").map(str::to_string).unwrap_or(bare);
+    if bare.is_empty() { return None; }
+    let domain = match url.host() {
+        Some(Host::Domain(_)) => psl::domain_str(&bare).unwrap_or(&bare).to_string(),
+        _ => bare.clone(),
+    };
+    let host = match url.port() {
+        Some(port) => format!("{}:{}", bare, port),
+        None => bare,
+    };
+    Some(Site { host, domain })
+}
+
+// get の照合の厳しさ
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchMode {
+    Exact,  // 入力どおりの URL
+    Host,   // 正規化したホスト（www. とスキームの違いを無視）
+    Domain, // 登録可能ドメイン（サブドメインの違いも無視）
+}
+
+impl MatchMode {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "exact" => Ok(MatchMode::Exact),
+            "host" => Ok(MatchMode::Host),
+            "domain" => Ok(MatchMode::Domain),
+            _ => Err(t!("--match は exact / host / domain のいずれかを指定してください: {}", s)),
+        }
+    }
+}
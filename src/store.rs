@@ -0,0 +1,98 @@
+// 保管庫の保存先
+//
+// Vault は検索・並び替え・暗号化を受け持ち、保存先はレコードをそのまま読み書きするだけにする。
+// 保存先を増やすときは VaultStore を実装して Vault::with_store に渡す
+//
+//   SqliteStore  既定の保存先（Vault::open）
+//   MemoryStore  プロセス内だけの保存先（テスト用）
+//   FileStore    全体を暗号化した1つのファイル
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::site;
+use crate::vault::CustomField;
+
+mod file;
+mod memory;
+mod sqlite;
+
+pub use file::FileStore;
+pub use memory::MemoryStore;
+pub use sqlite::SqliteStore;
+
+// 保存されているままのレコード（password・totp・secret フィールドは暗号化済み）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StoredRecord {
+    pub id: String,
+    pub url: String,
+    pub username: String,
+    pub password: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub totp: Option<String>,
+    // HOTP の次回カウンタ（TOTP や未設定は None）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub otp_counter: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub folder: Option<String>,
+    // 名前順
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    // 名前順
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<CustomField>,
+    pub created_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+    // ゴミ箱へ移動した日時（None は通常のレコード）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<String>,
+}
+
+pub trait VaultStore {
+    // ゴミ箱のものを含むすべてのレコード（順序は問わない）
+    fn records(&self) -> Result<Vec<StoredRecord>>;
+
+    fn record(&self, id: &str) -> Result<Option<StoredRecord>>;
+
+    // 同じ id があれば置き換える
+    fn put(&self, rec: &StoredRecord) -> Result<()>;
+
+    // 完全に削除する（存在しなければ何もしない）
+    fn remove(&self, id: &str) -> Result<()>;
+
+    fn meta(&self, key: &str) -> Result<Option<String>>;
+
+    fn set_meta(&self, key: &str, value: &str) -> Result<()>;
+
+    // 現在のカウンタ値を返して 1 進める（同じ値を二度返さない）
+    fn next_hotp_counter(&self, id: &str) -> Result<u64>;
+
+    // url が入力どおりに一致するか、正規化したホスト（host）・登録可能ドメイン（domain）が一致するレコード
+    // （ゴミ箱のものを含む、順序は問わない）。既定では全レコードの url から都度求める
+    fn records_for_site(&self, url: &str, host: Option<&str>, domain: Option<&str>) -> Result<Vec<StoredRecord>> {
+        let mut records = self.records()?;
+        records.retain(|r| {
+            if r.url == url { return true; }
+            let Some(site) = site::parse(&r.url) else { return false };
+            host.is_some_and(|h| site.host == h) || domain.is_some_and(|d| site.domain == d)
+        });
+        Ok(records)
+    }
+
+    // 全文検索の検索式に一致する id を関連度順に返す
+    fn full_text_search(&self, _query: &str) -> Result<Vec<String>> {
+        Err(Error::InvalidInput(t!("この保存先は全文検索（--fts）に対応していません")))
+    }
+}
+
+// MemoryStore / FileStore 共通の HOTP カウンタ操作
+fn advance_counter(rec: &mut StoredRecord) -> Result<u64> {
+    let used = rec.otp_counter.ok_or_else(|| Error::NotFound(t!("id={} の HOTP カウンタ", rec.id)))?;
+    rec.otp_counter = Some(used + 1);
+    Ok(used as u64)
+}
@@ -0,0 +1,135 @@
+// 全体を1つのファイルに保存する保存先
+//
+// レコードと meta を JSON にまとめ、シークレットから導出した鍵で丸ごと暗号化する
+// （url や username も平文で残らない）。変更のたびに一時ファイルへ書いて置き換える。
+// 読み込みと変更は同じディレクトリの <名前>.lock を排他ロックして行い、変更の前に読み直すため
+// 別のプロセスの変更を上書きしない
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+
+use super::{MemoryStore, StoredRecord, VaultStore};
+use crate::crypto::Cipher;
+use crate::error::{Error, Result};
+
+const FORMAT: &str = "tsupasswd-vault";
+const VERSION: u32 = 1;
+// ファイル全体の鍵導出に使う id（レコードの id とは重ならない）
+const FILE_KEY_ID: &str = "vault-file";
+const LOCK_EXTENSION: &str = "lock";
+
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    format: String,
+    version: u32,
+    data: String,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct Contents {
+    records: Vec<StoredRecord>,
+    meta: BTreeMap<String, String>,
+}
+
+pub struct FileStore {
+    path: PathBuf,
+    cipher: Cipher,
+    inner: MemoryStore,
+}
+
+impl FileStore {
+    // ファイルが無ければ空の保管庫として開き、最初の変更で作成する。
+    // 復号できなければ Error::WrongSecret
+    pub fn open(path: &Path, secret: &str) -> Result<Self> {
+        let cipher = Cipher::new(secret);
+        let contents = if path.exists() {
+            let _lock = lock(path)?;
+            load(path, &cipher)?
+        } else {
+            Contents::default()
+        };
+        Ok(FileStore { path: path.to_path_buf(), cipher, inner: MemoryStore::from_parts(contents.records, contents.meta) })
+    }
+
+    // ロックしたままファイルの最新の内容を読み直してから変更し、書き出す
+    fn modify<T>(&self, change: impl FnOnce(&MemoryStore) -> Result<T>) -> Result<T> {
+        let _lock = lock(&self.path)?;
+        let contents = if self.path.exists() { load(&self.path, &self.cipher)? } else { Contents::default() };
+        self.inner.replace(contents.records, contents.meta);
+        let value = change(&self.inner)?;
+        self.save()?;
+        Ok(value)
+    }
+
+    fn save(&self) -> Result<()> {
+        let contents = Contents { records: self.inner.records()?, meta: self.inner.meta_map() };
+        let envelope = Envelope {
+            format: FORMAT.to_string(),
+            version: VERSION,
+            data: self.cipher.encrypt(FILE_KEY_ID, &serde_json::to_string(&contents)?)?,
+        };
+        let tmp = self.path.with_extension("tmp");
+        let mut opts = fs::OpenOptions::new();
+        opts.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            opts.mode(0o600);
+        }
+        let mut f = opts.open(&tmp)?;
+        f.write_all(serde_json::to_string_pretty(&envelope)?.as_bytes())?;
+        f.sync_all()?;
+        fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+fn load(path: &Path, cipher: &Cipher) -> Result<Contents> {
+    let envelope: Envelope = serde_json::from_str(&fs::read_to_string(path)?)?;
+    if envelope.format != FORMAT || envelope.version != VERSION {
+        return Err(Error::Format(t!("保管庫ファイルの形式が不正です: {}", path.display())));
+    }
+    let json = cipher.decrypt(FILE_KEY_ID, &envelope.data).map_err(|_| Error::WrongSecret)?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+// 保管庫ファイルの排他ロック（戻り値を手放すと解放される）
+fn lock(path: &Path) -> Result<fs::File> {
+    if let Some(dir) = path.parent() { fs::create_dir_all(dir)?; }
+    let lock = fs::OpenOptions::new().create(true).truncate(false).write(true).open(path.with_extension(LOCK_EXTENSION))?;
+    lock.lock()?;
+    Ok(lock)
+}
+
+impl VaultStore for FileStore {
+    fn records(&self) -> Result<Vec<StoredRecord>> {
+        self.inner.records()
+    }
+
+    fn record(&self, id: &str) -> Result<Option<StoredRecord>> {
+        self.inner.record(id)
+    }
+
+    fn put(&self, rec: &StoredRecord) -> Result<()> {
+        self.modify(|inner| inner.put(rec))
+    }
+
+    fn remove(&self, id: &str) -> Result<()> {
+        self.modify(|inner| inner.remove(id))
+    }
+
+    fn meta(&self, key: &str) -> Result<Option<String>> {
+        self.inner.meta(key)
+    }
+
+    fn set_meta(&self, key: &str, value: &str) -> Result<()> {
+        self.modify(|inner| inner.set_meta(key, value))
+    }
+
+    fn next_hotp_counter(&self, id: &str) -> Result<u64> {
+        self.modify(|inner| inner.next_hotp_counter(id))
+    }
+}
@@ -0,0 +1,72 @@
+// プロセス内だけの保存先。ファイルを作らないためテストや一時的な保管庫に使う
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+use super::{advance_counter, StoredRecord, VaultStore};
+use crate::error::{Error, Result};
+
+#[derive(Default)]
+pub struct MemoryStore {
+    records: RefCell<BTreeMap<String, StoredRecord>>,
+    meta: RefCell<BTreeMap<String, String>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // FileStore が読み込んだ内容から作る
+    pub(super) fn from_parts(records: Vec<StoredRecord>, meta: BTreeMap<String, String>) -> Self {
+        MemoryStore {
+            records: RefCell::new(records.into_iter().map(|r| (r.id.clone(), r)).collect()),
+            meta: RefCell::new(meta),
+        }
+    }
+
+    // FileStore がファイルを読み直したときに内容を置き換える
+    pub(super) fn replace(&self, records: Vec<StoredRecord>, meta: BTreeMap<String, String>) {
+        *self.records.borrow_mut() = records.into_iter().map(|r| (r.id.clone(), r)).collect();
+        *self.meta.borrow_mut() = meta;
+    }
+
+    pub(super) fn meta_map(&self) -> BTreeMap<String, String> {
+        self.meta.borrow().clone()
+    }
+}
+
+impl VaultStore for MemoryStore {
+    fn records(&self) -> Result<Vec<StoredRecord>> {
+        Ok(self.records.borrow().values().cloned().collect())
+    }
+
+    fn record(&self, id: &str) -> Result<Option<StoredRecord>> {
+        Ok(self.records.borrow().get(id).cloned())
+    }
+
+    fn put(&self, rec: &StoredRecord) -> Result<()> {
+        self.records.borrow_mut().insert(rec.id.clone(), rec.clone());
+        Ok(())
+    }
+
+    fn remove(&self, id: &str) -> Result<()> {
+        self.records.borrow_mut().remove(id);
+        Ok(())
+    }
+
+    fn meta(&self, key: &str) -> Result<Option<String>> {
+        Ok(self.meta.borrow().get(key).cloned())
+    }
+
+    fn set_meta(&self, key: &str, value: &str) -> Result<()> {
+        self.meta.borrow_mut().insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    fn next_hotp_counter(&self, id: &str) -> Result<u64> {
+        let mut records = self.records.borrow_mut();
+        let rec = records.get_mut(id).ok_or_else(|| Error::NotFound(format!("id={}", id)))?;
+        advance_counter(rec)
+    }
+}
@@ -0,0 +1,360 @@
+// SQLite の保存先（既定）
+//
+// 任意フィールドとタグは別テーブルに持ち、全文検索索引（FTS5 trigram）はトリガで同期する。
+// 旧バージョンで作成されたDBは開くときに列を補って移行する
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use super::{StoredRecord, VaultStore};
+use crate::error::{Error, Result};
+use crate::site;
+use crate::vault::CustomField;
+
+const COLLECTION: &str = "passwords"; // SQLiteのテーブル名としても使用
+const FIELDS_TABLE: &str = "fields"; // レコードごとの任意フィールド
+const TAGS_TABLE: &str = "tags";
+const PASSWORD_TAGS_TABLE: &str = "password_tags"; // passwords と tags の多対多
+const FTS_TABLE: &str = "passwords_fts"; // title/note/url/username の全文検索索引（FTS5 trigram）
+const META_TABLE: &str = "meta"; // 鍵確認値などの保管庫全体の設定
+const RECORD_COLUMNS: &str = "id, url, username, password, title, note, totp, otp_counter, folder, created_at, updated_at, deleted_at";
+
+pub struct SqliteStore {
+    conn: Connection,
+}
+
+impl SqliteStore {
+    // DBファイルを開き（無ければ作成）、旧バージョンのスキーマを移行する
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(dir) = path.parent() { fs::create_dir_all(dir)?; }
+        let conn = Connection::open(path)?;
+        init_db(&conn)?;
+        Ok(SqliteStore { conn })
+    }
+
+    fn fetch_fields(&self, id: Option<&str>) -> Result<HashMap<String, Vec<CustomField>>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT record_id, name, value, secret FROM {} WHERE ?1 IS NULL OR record_id = ?1 ORDER BY name",
+            FIELDS_TABLE
+        ))?;
+        let rows = stmt.query_map(params![id], |row| {
+            Ok((row.get::<_, String>(0)?, CustomField { name: row.get(1)?, value: row.get(2)?, secret: row.get(3)? }))
+        })?;
+        let mut out: HashMap<String, Vec<CustomField>> = HashMap::new();
+        for r in rows {
+            let (record_id, field) = r?;
+            out.entry(record_id).or_default().push(field);
+        }
+        Ok(out)
+    }
+
+    fn fetch_tags(&self, id: Option<&str>) -> Result<HashMap<String, Vec<String>>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT pt.password_id, t.name FROM {} t JOIN {} pt ON pt.tag_id = t.id WHERE ?1 IS NULL OR pt.password_id = ?1 ORDER BY t.name",
+            TAGS_TABLE, PASSWORD_TAGS_TABLE
+        ))?;
+        let rows = stmt.query_map(params![id], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+        let mut out: HashMap<String, Vec<String>> = HashMap::new();
+        for r in rows {
+            let (record_id, tag) = r?;
+            out.entry(record_id).or_default().push(tag);
+        }
+        Ok(out)
+    }
+
+    // id を指定しなければすべて
+    fn load(&self, id: Option<&str>) -> Result<Vec<StoredRecord>> {
+        let mut fields = self.fetch_fields(id)?;
+        let mut tags = self.fetch_tags(id)?;
+        let mut stmt = self.conn.prepare(&format!("SELECT {} FROM {} WHERE ?1 IS NULL OR id = ?1", RECORD_COLUMNS, COLLECTION))?;
+        let rows = stmt.query_map(params![id], |row| {
+            Ok(StoredRecord {
+                id: row.get(0)?,
+                url: row.get(1)?,
+                username: row.get(2)?,
+                password: row.get(3)?,
+                title: row.get(4)?,
+                note: row.get(5)?,
+                totp: row.get(6)?,
+                otp_counter: row.get(7)?,
+                folder: row.get(8)?,
+                tags: Vec::new(),
+                fields: Vec::new(),
+                created_at: row.get(9)?,
+                updated_at: row.get(10)?,
+                deleted_at: row.get(11)?,
+            })
+        })?;
+        let mut out = Vec::new();
+        for r in rows {
+            let mut rec = r?;
+            rec.fields = fields.remove(&rec.id).unwrap_or_default();
+            rec.tags = tags.remove(&rec.id).unwrap_or_default();
+            out.push(rec);
+        }
+        Ok(out)
+    }
+
+    fn remove_orphan_tags(&self) -> Result<()> {
+        // どのレコードにも付いていないタグは削除
+        self.conn.execute(
+            &format!("DELETE FROM {} WHERE id NOT IN (SELECT tag_id FROM {})", TAGS_TABLE, PASSWORD_TAGS_TABLE),
+            [],
+        )?;
+        Ok(())
+    }
+}
+
+impl VaultStore for SqliteStore {
+    fn records(&self) -> Result<Vec<StoredRecord>> {
+        self.load(None)
+    }
+
+    fn record(&self, id: &str) -> Result<Option<StoredRecord>> {
+        Ok(self.load(Some(id))?.pop())
+    }
+
+    fn put(&self, rec: &StoredRecord) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            &format!(
+                "INSERT INTO {} ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+                 ON CONFLICT(id) DO UPDATE SET url=excluded.url, username=excluded.username, password=excluded.password,
+                    title=excluded.title, note=excluded.note, totp=excluded.totp, otp_counter=excluded.otp_counter,
+                    folder=excluded.folder, created_at=excluded.created_at, updated_at=excluded.updated_at, deleted_at=excluded.deleted_at",
+                COLLECTION, RECORD_COLUMNS
+            ),
+            params![
+                rec.id, rec.url, rec.username, rec.password, rec.title, rec.note, rec.totp, rec.otp_counter,
+                rec.folder, rec.created_at, rec.updated_at, rec.deleted_at
+            ],
+        )?;
+        set_site(&tx, &rec.id, &rec.url)?;
+        tx.execute(&format!("DELETE FROM {} WHERE record_id = ?1", FIELDS_TABLE), params![rec.id])?;
+        for f in &rec.fields {
+            tx.execute(
+                &format!("INSERT INTO {} (record_id, name, value, secret) VALUES (?1, ?2, ?3, ?4)", FIELDS_TABLE),
+                params![rec.id, f.name, f.value, f.secret],
+            )?;
+        }
+        tx.execute(&format!("DELETE FROM {} WHERE password_id = ?1", PASSWORD_TAGS_TABLE), params![rec.id])?;
+        for tag in &rec.tags {
+            tx.execute(&format!("INSERT OR IGNORE INTO {} (name) VALUES (?1)", TAGS_TABLE), params![tag])?;
+            tx.execute(
+                &format!(
+                    "INSERT OR IGNORE INTO {} (password_id, tag_id) SELECT ?1, id FROM {} WHERE name = ?2",
+                    PASSWORD_TAGS_TABLE, TAGS_TABLE
+                ),
+                params![rec.id, tag],
+            )?;
+        }
+        self.remove_orphan_tags()?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn remove(&self, id: &str) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(&format!("DELETE FROM {} WHERE id = ?1", COLLECTION), params![id])?;
+        tx.execute(&format!("DELETE FROM {} WHERE record_id = ?1", FIELDS_TABLE), params![id])?;
+        tx.execute(&format!("DELETE FROM {} WHERE password_id = ?1", PASSWORD_TAGS_TABLE), params![id])?;
+        self.remove_orphan_tags()?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn meta(&self, key: &str) -> Result<Option<String>> {
+        Ok(self
+            .conn
+            .query_row(&format!("SELECT value FROM {} WHERE key = ?1", META_TABLE), params![key], |row| row.get(0))
+            .optional()?)
+    }
+
+    fn set_meta(&self, key: &str, value: &str) -> Result<()> {
+        self.conn.execute(
+            &format!("INSERT INTO {} (key, value) VALUES (?1, ?2) ON CONFLICT(key) DO UPDATE SET value=excluded.value", META_TABLE),
+            params![key, value],
+        )?;
+        Ok(())
+    }
+
+    // 同じ文でカウンタを進めるため、同時に実行しても同じ値を二度使わない
+    fn next_hotp_counter(&self, id: &str) -> Result<u64> {
+        let used: Option<i64> = self
+            .conn
+            .query_row(
+                &format!(
+                    "UPDATE {} SET otp_counter = otp_counter + 1 WHERE id = ?1 AND otp_counter IS NOT NULL RETURNING otp_counter - 1",
+                    COLLECTION
+                ),
+                params![id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        used.map(|c| c as u64).ok_or_else(|| Error::NotFound(t!("id={} の HOTP カウンタ", id)))
+    }
+
+    fn records_for_site(&self, url: &str, host: Option<&str>, domain: Option<&str>) -> Result<Vec<StoredRecord>> {
+        let ids: Vec<String> = {
+            let mut stmt =
+                self.conn.prepare(&format!("SELECT id FROM {} WHERE url = ?1 OR host = ?2 OR domain = ?3", COLLECTION))?;
+            stmt.query_map(params![url, host, domain], |row| row.get(0))?.collect::<rusqlite::Result<_>>()?
+        };
+        let mut out = Vec::with_capacity(ids.len());
+        for id in ids {
+            out.extend(self.load(Some(&id))?);
+        }
+        Ok(out)
+    }
+
+    // FTS5 の検索式（AND/OR/NOT、"フレーズ"、前方一致 foo*、列指定 title:foo）。
+    // trigram のため3文字未満の語には一致しない
+    fn full_text_search(&self, query: &str) -> Result<Vec<String>> {
+        let invalid = |e: rusqlite::Error| Error::InvalidInput(t!("検索式が不正です: {}", e));
+        let mut stmt = self.conn.prepare(&format!("SELECT id FROM {fts} WHERE {fts} MATCH ?1 ORDER BY rank", fts = FTS_TABLE))?;
+        let rows = stmt.query_map(params![query], |row| row.get::<_, String>(0)).map_err(invalid)?;
+        rows.collect::<rusqlite::Result<_>>().map_err(invalid)
+    }
+}
+
+fn init_db(conn: &Connection) -> Result<()> {
+    conn.execute(
+        &format!(
+            "CREATE TABLE IF NOT EXISTS {} (
+                id TEXT PRIMARY KEY,
+                url TEXT NOT NULL,
+                username TEXT NOT NULL,
+                password TEXT NOT NULL,
+                title TEXT,
+                note TEXT,
+                created_at TEXT NOT NULL,
+                totp TEXT,
+                otp_counter INTEGER,
+                folder TEXT,
+                updated_at TEXT,
+                host TEXT,
+                domain TEXT,
+                deleted_at TEXT
+            )",
+            COLLECTION
+        ),
+        [],
+    )?;
+    // 旧バージョンで作成されたDBには後から追加した列を補う
+    ensure_column(conn, COLLECTION, "totp", "TEXT")?;
+    ensure_column(conn, COLLECTION, "otp_counter", "INTEGER")?;
+    ensure_column(conn, COLLECTION, "folder", "TEXT")?;
+    ensure_column(conn, COLLECTION, "updated_at", "TEXT")?;
+    ensure_column(conn, COLLECTION, "host", "TEXT")?;
+    ensure_column(conn, COLLECTION, "domain", "TEXT")?;
+    ensure_column(conn, COLLECTION, "deleted_at", "TEXT")?;
+    backfill_sites(conn)?;
+    // get の host / domain 照合（records_for_site）用
+    conn.execute_batch(&format!(
+        "CREATE INDEX IF NOT EXISTS {t}_host ON {t} (host);
+         CREATE INDEX IF NOT EXISTS {t}_domain ON {t} (domain);",
+        t = COLLECTION
+    ))?;
+    conn.execute(
+        &format!(
+            "CREATE TABLE IF NOT EXISTS {} (
+                record_id TEXT NOT NULL,
+                name TEXT NOT NULL,
+                value TEXT NOT NULL,
+                secret INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (record_id, name)
+            )",
+            FIELDS_TABLE
+        ),
+        [],
+    )?;
+    conn.execute(
+        &format!("CREATE TABLE IF NOT EXISTS {} (id INTEGER PRIMARY KEY, name TEXT NOT NULL UNIQUE)", TAGS_TABLE),
+        [],
+    )?;
+    conn.execute(
+        &format!(
+            "CREATE TABLE IF NOT EXISTS {} (
+                password_id TEXT NOT NULL,
+                tag_id INTEGER NOT NULL,
+                PRIMARY KEY (password_id, tag_id)
+            )",
+            PASSWORD_TAGS_TABLE
+        ),
+        [],
+    )?;
+    conn.execute(
+        &format!("CREATE TABLE IF NOT EXISTS {} (key TEXT PRIMARY KEY, value TEXT NOT NULL)", META_TABLE),
+        [],
+    )?;
+    init_fts(conn)?;
+    Ok(())
+}
+
+// 全文検索索引はトリガで passwords と同期する。日本語の備考も扱えるよう trigram で分割
+fn init_fts(conn: &Connection) -> Result<()> {
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)",
+        params![FTS_TABLE],
+        |row| row.get(0),
+    )?;
+    conn.execute_batch(&format!(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS {fts} USING fts5(id UNINDEXED, title, note, url, username, tokenize = 'trigram');
+         CREATE TRIGGER IF NOT EXISTS {fts}_ai AFTER INSERT ON {t} BEGIN
+             INSERT INTO {fts} (id, title, note, url, username) VALUES (new.id, new.title, new.note, new.url, new.username);
+         END;
+         CREATE TRIGGER IF NOT EXISTS {fts}_ad AFTER DELETE ON {t} BEGIN
+             DELETE FROM {fts} WHERE id = old.id;
+         END;
+         CREATE TRIGGER IF NOT EXISTS {fts}_au AFTER UPDATE OF title, note, url, username ON {t} BEGIN
+             DELETE FROM {fts} WHERE id = old.id;
+             INSERT INTO {fts} (id, title, note, url, username) VALUES (new.id, new.title, new.note, new.url, new.username);
+         END;",
+        fts = FTS_TABLE,
+        t = COLLECTION
+    ))?;
+    // 索引を新しく作った場合は既存レコードを取り込む
+    if !exists {
+        conn.execute(
+            &format!("INSERT INTO {} (id, title, note, url, username) SELECT id, title, note, url, username FROM {}", FTS_TABLE, COLLECTION),
+            [],
+        )?;
+    }
+    Ok(())
+}
+
+// host/domain 列の追加前に保存されたレコードを補完する（解釈できない URL は NULL のまま）
+fn backfill_sites(conn: &Connection) -> Result<()> {
+    let pending: Vec<(String, String)> = {
+        let mut stmt = conn.prepare(&format!("SELECT id, url FROM {} WHERE host IS NULL", COLLECTION))?;
+        stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?.collect::<rusqlite::Result<_>>()?
+    };
+    for (id, url) in pending {
+        if site::parse(&url).is_some() { set_site(conn, &id, &url)?; }
+    }
+    Ok(())
+}
+
+// host/domain 列は records_for_site が索引で照合するための写し
+fn set_site(conn: &Connection, id: &str, url: &str) -> Result<()> {
+    let site = site::parse(url);
+    conn.execute(
+        &format!("UPDATE {} SET host=?1, domain=?2 WHERE id=?3", COLLECTION),
+        params![site.as_ref().map(|s| &s.host), site.as_ref().map(|s| &s.domain), id],
+    )?;
+    Ok(())
+}
+
+fn ensure_column(conn: &Connection, table: &str, column: &str, decl: &str) -> Result<()> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let exists = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(std::result::Result::ok)
+        .any(|name| name == column);
+    if !exists {
+        conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, decl), [])?;
+    }
+    Ok(())
+}
@@ -0,0 +1,333 @@
+// 全画面の対話モード（tsupasswd tui）
+//
+// 一覧の絞り込みは Vault::search、編集・削除は Vault::update / Vault::delete をそのまま使う。
+// セッションは定期的に ensure_authenticated で確認し、期限切れになったら画面をロックする
+
+use std::io::IsTerminal;
+use std::time::{Duration, Instant};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use tsupasswd::{Entry, EntryChanges, Vault};
+
+type Error = Box<dyn std::error::Error + Send + Sync>;
+
+const TICK: Duration = Duration::from_millis(250);
+const SESSION_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+const EDIT_LABELS: [&str; 5] = ["url", "username", "password", "title", "note"];
+
+enum Mode {
+    Browse,
+    Filter,
+    Edit(Box<EditForm>),
+    ConfirmDelete,
+    Locked,
+}
+
+// 編集ダイアログ。変更された項目だけを EntryChanges にする
+struct EditForm {
+    id: String,
+    original: [String; 5],
+    values: [String; 5],
+    focus: usize,
+}
+
+impl EditForm {
+    fn new(entry: &Entry) -> Self {
+        let original = [
+            entry.url.clone(),
+            entry.username.clone(),
+            entry.password.clone(),
+            entry.title.clone().unwrap_or_default(),
+            entry.note.clone().unwrap_or_default(),
+        ];
+        EditForm { id: entry.id.clone(), values: original.clone(), original, focus: 0 }
+    }
+
+    fn changes(&self) -> EntryChanges {
+        let changed = |i: usize| (self.values[i] != self.original[i]).then(|| self.values[i].clone());
+        EntryChanges {
+            url: changed(0),
+            username: changed(1),
+            password: changed(2),
+            title: changed(3),
+            note: changed(4),
+            ..Default::default()
+        }
+    }
+}
+
+struct App {
+    entries: Vec<Entry>,
+    list: ListState,
+    filter: String,
+    reveal: bool,
+    mode: Mode,
+    status: String,
+    last_session_check: Instant,
+}
+
+impl App {
+    fn selected(&self) -> Option<&Entry> {
+        self.list.selected().and_then(|i| self.entries.get(i))
+    }
+
+    fn reload(&mut self, db: &Vault) -> Result<(), Error> {
+        let keep = self.selected().map(|e| e.id.clone());
+        self.entries = db.search(&self.filter, None)?;
+        let index = keep
+            .and_then(|id| self.entries.iter().position(|e| e.id == id))
+            .or_else(|| (!self.entries.is_empty()).then_some(0))
+            .map(|i| i.min(self.entries.len().saturating_sub(1)));
+        self.list.select(index);
+        Ok(())
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        if self.entries.is_empty() { return; }
+        let current = self.list.selected().unwrap_or(0) as isize;
+        let next = (current + delta).clamp(0, self.entries.len() as isize - 1);
+        self.list.select(Some(next as usize));
+    }
+
+    // 期限切れなら復号済みのデータを捨ててロックする
+    fn check_session(&mut self) {
+        self.last_session_check = Instant::now();
+        if crate::ensure_authenticated().is_err() && !matches!(self.mode, Mode::Locked) {
+            self.entries.clear();
+            self.list.select(None);
+            self.reveal = false;
+            self.mode = Mode::Locked;
+        }
+    }
+}
+
+pub fn run(db: &Vault) -> Result<(), Error> {
+    if !std::io::stdout().is_terminal() {
+        return Err(t!("端末（TTY）で実行してください").into());
+    }
+    let mut app = App {
+        entries: Vec::new(),
+        list: ListState::default(),
+        filter: String::new(),
+        reveal: false,
+        mode: Mode::Browse,
+        status: String::new(),
+        last_session_check: Instant::now(),
+    };
+    app.reload(db)?;
+    let mut terminal = ratatui::try_init()?;
+    let result = event_loop(&mut terminal, &mut app, db);
+    ratatui::restore();
+    result
+}
+
+fn event_loop(terminal: &mut DefaultTerminal, app: &mut App, db: &Vault) -> Result<(), Error> {
+    loop {
+        terminal.draw(|f| draw(f, app))?;
+        if app.last_session_check.elapsed() >= SESSION_CHECK_INTERVAL {
+            app.check_session();
+        }
+        if !event::poll(TICK)? { continue; }
+        let Event::Key(key) = event::read()? else { continue };
+        if key.kind != KeyEventKind::Press { continue; }
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) { return Ok(()); }
+        // 操作の直前にも確認し、期限切れのセッションで DB を触らない
+        app.check_session();
+        if handle_key(app, key, db)? { return Ok(()); }
+    }
+}
+
+// true を返したら終了
+fn handle_key(app: &mut App, key: KeyEvent, db: &Vault) -> Result<bool, Error> {
+    match &mut app.mode {
+        Mode::Locked => match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(true),
+            KeyCode::Enter if crate::ensure_authenticated().is_ok() => {
+                app.mode = Mode::Browse;
+                app.status = t!("ロックを解除しました");
+                app.reload(db)?;
+            }
+            _ => {}
+        },
+        Mode::Browse => match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(true),
+            KeyCode::Down | KeyCode::Char('j') => app.move_selection(1),
+            KeyCode::Up | KeyCode::Char('k') => app.move_selection(-1),
+            KeyCode::PageDown => app.move_selection(10),
+            KeyCode::PageUp => app.move_selection(-10),
+            KeyCode::Char('/') => app.mode = Mode::Filter,
+            KeyCode::Char('r') => app.reveal = !app.reveal,
+            KeyCode::Char('c') => {
+                if let Some(row) = app.selected() {
+                    crate::audit_event("get", Some(&row.id), true, Some("copy"));
+                    let clear_after = crate::settings().clear_after;
+                    app.status = match crate::copy_to_clipboard(&row.password, clear_after) {
+                        Ok(()) if clear_after > 0 => t!("パスワードをコピーしました（{}秒後に消去）", clear_after),
+                        Ok(()) => t!("パスワードをコピーしました"),
+                        Err(e) => t!("コピーに失敗しました: {}", e),
+                    };
+                }
+            }
+            KeyCode::Char('e') | KeyCode::Enter => {
+                if let Some(row) = app.selected() { app.mode = Mode::Edit(Box::new(EditForm::new(row))); }
+            }
+            KeyCode::Char('d') | KeyCode::Delete if app.selected().is_some() => app.mode = Mode::ConfirmDelete,
+            _ => {}
+        },
+        Mode::Filter => {
+            match key.code {
+                KeyCode::Enter | KeyCode::Esc => app.mode = Mode::Browse,
+                KeyCode::Backspace => { app.filter.pop(); }
+                KeyCode::Char(c) => app.filter.push(c),
+                _ => return Ok(false),
+            }
+            app.reload(db)?;
+        }
+        Mode::ConfirmDelete => {
+            if let KeyCode::Char('y') = key.code
+                && let Some(id) = app.selected().map(|row| row.id.clone())
+            {
+                let result = db.delete(&id);
+                crate::audit_event("delete", Some(&id), result.is_ok(), None);
+                // 失敗しても TUI を終了せず、ステータス行に出して一覧に戻る
+                app.status = match result {
+                    Ok(_) => t!("ゴミ箱へ移動しました: id={}", id),
+                    Err(e) => t!("削除に失敗しました: {}", e),
+                };
+                app.reload(db)?;
+            }
+            app.mode = Mode::Browse;
+        }
+        Mode::Edit(form) => match key.code {
+            KeyCode::Esc => app.mode = Mode::Browse,
+            KeyCode::Tab | KeyCode::Down => form.focus = (form.focus + 1) % EDIT_LABELS.len(),
+            KeyCode::BackTab | KeyCode::Up => form.focus = (form.focus + EDIT_LABELS.len() - 1) % EDIT_LABELS.len(),
+            KeyCode::Backspace => { form.values[form.focus].pop(); }
+            KeyCode::Char(c) => form.values[form.focus].push(c),
+            KeyCode::Enter => {
+                let changes = form.changes();
+                let id = form.id.clone();
+                if changes.is_empty() {
+                    app.status = t!("変更はありません");
+                } else {
+                    let result = db.update(&id, &changes);
+                    crate::audit_event("update", Some(&id), result.is_ok(), Some(&format!("changed={}", changes.changed_names().join(","))));
+                    app.status = match result {
+                        Ok(_) => t!("更新しました: id={}", id),
+                        Err(e) => t!("更新に失敗しました: {}", e),
+                    };
+                }
+                app.mode = Mode::Browse;
+                app.reload(db)?;
+            }
+            _ => {}
+        },
+    }
+    Ok(false)
+}
+
+fn mask(password: &str, reveal: bool) -> String {
+    if reveal { password.to_string() } else { "•".repeat(password.chars().count().clamp(8, 16)) }
+}
+
+fn draw(f: &mut Frame, app: &mut App) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(3), Constraint::Length(1)])
+        .split(f.area());
+
+    if let Mode::Locked = app.mode {
+        let text = t!("セッションの有効期限が切れたためロックしました。\n別の端末で `tsupasswd auth <secret>` を実行してから Enter（q で終了）");
+        let block = Block::default().borders(Borders::ALL).title(t!(" ロック中 "));
+        f.render_widget(Paragraph::new(text).block(block).wrap(Wrap { trim: false }), f.area());
+        return;
+    }
+
+    let filter_title = if let Mode::Filter = app.mode { t!(" 絞り込み（入力中、Enter で確定） ") } else { t!(" 絞り込み（/） ") };
+    let filter = Paragraph::new(app.filter.as_str()).block(Block::default().borders(Borders::ALL).title(filter_title));
+    f.render_widget(filter, rows[0]);
+
+    let cols = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+        .split(rows[1]);
+    let items: Vec<ListItem> = app
+        .entries
+        .iter()
+        .map(|e| {
+            let label = e.title.as_deref().unwrap_or(&e.url);
+            ListItem::new(format!("{}  {}", label, e.username))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(t!(" エントリ ({}) ", app.entries.len())))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    f.render_stateful_widget(list, cols[0], &mut app.list);
+
+    let detail: Vec<Line> = match app.selected() {
+        Some(Entry { id, url, username, password, title, note, folder, tags, .. }) => {
+            let mut lines = vec![
+                Line::from(format!("id:       {}", id)),
+                Line::from(format!("url:      {}", url)),
+                Line::from(format!("username: {}", username)),
+                Line::from(format!("password: {}", mask(password, app.reveal))),
+            ];
+            if let Some(t) = title { lines.push(Line::from(format!("title:    {}", t))); }
+            if let Some(folder) = folder { lines.push(Line::from(format!("folder:   {}", folder))); }
+            if !tags.is_empty() { lines.push(Line::from(format!("tags:     {}", tags.join(",")))); }
+            if let Some(n) = note { lines.push(Line::from(format!("note:     {}", n))); }
+            lines
+        }
+        None => vec![Line::from(t!("エントリがありません"))],
+    };
+    let detail = Paragraph::new(detail).block(Block::default().borders(Borders::ALL).title(t!(" 詳細 "))).wrap(Wrap { trim: false });
+    f.render_widget(detail, cols[1]);
+
+    let help = if app.status.is_empty() {
+        t!("↑↓/jk 移動  / 絞り込み  r 表示切替  c コピー  e 編集  d 削除  q 終了")
+    } else {
+        app.status.clone()
+    };
+    f.render_widget(Paragraph::new(help), rows[2]);
+
+    match &app.mode {
+        Mode::ConfirmDelete => {
+            let area = centered(f.area(), 50, 5);
+            let name = app.selected().map(|row| row.title.clone().unwrap_or_else(|| row.url.clone())).unwrap_or_default();
+            let text = t!("「{}」をゴミ箱へ移動しますか？ (y/N)", name);
+            f.render_widget(Clear, area);
+            f.render_widget(Paragraph::new(text).block(Block::default().borders(Borders::ALL).title(t!(" 削除 "))), area);
+        }
+        Mode::Edit(form) => {
+            let area = centered(f.area(), 70, EDIT_LABELS.len() as u16 + 4);
+            let mut lines: Vec<Line> = EDIT_LABELS
+                .iter()
+                .enumerate()
+                .map(|(i, label)| {
+                    let value = if i == 2 { mask(&form.values[i], app.reveal) } else { form.values[i].clone() };
+                    let line = format!("{:<9} {}{}", format!("{}:", label), value, if i == form.focus { "▏" } else { "" });
+                    if i == form.focus { Line::styled(line, Style::default().add_modifier(Modifier::BOLD)) } else { Line::from(line) }
+                })
+                .collect();
+            lines.push(Line::from(""));
+            lines.push(Line::from(t!("Tab 次の項目  Enter 保存  Esc 取消")));
+            f.render_widget(Clear, area);
+            f.render_widget(Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(t!(" 編集 "))), area);
+        }
+        _ => {}
+    }
+}
+
+fn centered(area: Rect, width_percent: u16, height: u16) -> Rect {
+    let width = area.width * width_percent / 100;
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + area.height.saturating_sub(height) / 2,
+        width,
+        height: height.min(area.height),
+    }
+}
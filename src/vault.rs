@@ -0,0 +1,760 @@
+// 保管庫
+//
+// パスワード・TOTP・secret フィールドは Cipher でレコードごとに暗号化して保存する。
+// Vault::open で開いた直後はロック状態で、unlock でシークレットを渡すと読み書きできる。
+// 検索・絞り込み・並び替えはここで行い、保存先（store::VaultStore）はレコードを読み書きするだけ
+
+use std::cmp::Ordering;
+use std::fs;
+use std::path::Path;
+use chrono::Utc;
+use csv::{ReaderBuilder, WriterBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::{self, Cipher};
+use crate::error::{Error, Result};
+use crate::store::{MemoryStore, SqliteStore, StoredRecord, VaultStore};
+use crate::{fuzzy, otp, private_file, selector, site};
+
+const KEY_CHECK: &str = "key_check"; // 鍵確認値の meta キー（暗号化の id にも使う）
+
+// 任意の名前付きフィールド（秘密の質問、PIN、リカバリコード等）。secret=true の値は暗号化して保存
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomField {
+    pub name: String,
+    pub value: String,
+    #[serde(default)]
+    pub secret: bool,
+}
+
+// 保存済みのレコード（password と secret フィールドは復号済み）
+#[derive(Debug, Clone, Serialize)]
+pub struct Entry {
+    pub id: String,
+    pub url: String,
+    pub username: String,
+    pub password: String,
+    pub title: Option<String>,
+    pub note: Option<String>,
+    pub folder: Option<String>,
+    pub tags: Vec<String>,
+    pub fields: Vec<CustomField>,
+    pub created_at: String,
+    pub updated_at: Option<String>,
+}
+
+// add で保存する内容。totp は base32 / otpauth:// URI / steam:// のいずれか
+#[derive(Debug, Clone, Default)]
+pub struct NewEntry {
+    pub url: String,
+    pub username: String,
+    pub password: String,
+    pub title: Option<String>,
+    pub note: Option<String>,
+    pub totp: Option<String>,
+    pub fields: Vec<CustomField>,
+    pub folder: Option<String>,
+    pub tags: Vec<String>,
+}
+
+// update / delete の対象の概要
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Target {
+    pub id: String,
+    pub url: String,
+    pub username: String,
+}
+
+impl std::fmt::Display for Target {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "id={} url=\"{}\" username=\"{}\"", self.id, self.url, self.username)
+    }
+}
+
+// OTP を登録済みのレコード（uri は復号済みの otpauth URI）
+#[derive(Debug, Clone)]
+pub struct OtpSecret {
+    pub id: String,
+    pub username: String,
+    pub uri: String,
+}
+
+pub struct Vault {
+    store: Box<dyn VaultStore>,
+    cipher: Option<Cipher>,
+}
+
+// フォルダは `work/aws` のような `/` 区切りのパス。前後の `/` と空要素は除く
+pub fn normalize_folder(folder: &str) -> Option<String> {
+    let parts: Vec<&str> = folder.split('/').map(str::trim).filter(|p| !p.is_empty()).collect();
+    if parts.is_empty() { None } else { Some(parts.join("/")) }
+}
+
+// `--tag a,b` のようなカンマ区切りも受け付ける
+pub fn parse_tags_arg(arg: &str) -> Vec<String> {
+    arg.split(',').map(str::trim).filter(|t| !t.is_empty()).map(|t| t.to_string()).collect()
+}
+
+// 大文字小文字を区別しない部分一致（% や _ も文字として扱う）
+fn contains_ci(text: &str, needle: &str) -> bool {
+    text.to_lowercase().contains(&needle.to_lowercase())
+}
+
+// フォルダ自身と配下のフォルダに一致
+fn in_folder(rec: &StoredRecord, folder: &str) -> bool {
+    rec.folder.as_deref().is_some_and(|f| f == folder || f.strip_prefix(folder).is_some_and(|rest| rest.starts_with('/')))
+}
+
+fn has_tag(rec: &StoredRecord, tag: Option<&str>) -> bool {
+    tag.is_none_or(|t| rec.tags.iter().any(|x| x == t))
+}
+
+fn newest_first(a: &StoredRecord, b: &StoredRecord) -> Ordering {
+    b.created_at.cmp(&a.created_at)
+}
+
+// 付いていないタグだけを追加し、名前順に保つ
+fn add_tags(rec: &mut StoredRecord, tags: &[String]) {
+    for tag in tags {
+        if !rec.tags.contains(tag) { rec.tags.push(tag.clone()); }
+    }
+    rec.tags.sort();
+}
+
+// list の並び順
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortKey { #[default] Created, Updated, Url, Title }
+
+impl SortKey {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "created" => Some(SortKey::Created),
+            "updated" => Some(SortKey::Updated),
+            "url" => Some(SortKey::Url),
+            "title" => Some(SortKey::Title),
+            _ => None,
+        }
+    }
+
+    // url / title は大文字小文字を区別しない。未更新のものは作成日時を更新日時とみなす
+    fn compare(&self, a: &StoredRecord, b: &StoredRecord) -> Ordering {
+        match self {
+            SortKey::Created => a.created_at.cmp(&b.created_at),
+            SortKey::Updated => {
+                a.updated_at.as_ref().unwrap_or(&a.created_at).cmp(b.updated_at.as_ref().unwrap_or(&b.created_at))
+            }
+            SortKey::Url => a.url.to_lowercase().cmp(&b.url.to_lowercase()),
+            SortKey::Title => {
+                let key = |r: &StoredRecord| r.title.as_deref().unwrap_or("").to_lowercase();
+                key(a).cmp(&key(b))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ListOptions {
+    pub folder: Option<String>,
+    pub tag: Option<String>,
+    pub sort: SortKey,
+    pub desc: bool,
+    pub limit: Option<i64>,
+    pub offset: i64,
+    pub show_passwords: bool,
+}
+
+// list の1行。password は show_passwords 指定時のみ復号して設定
+#[derive(Debug, Clone, Serialize)]
+pub struct ListEntry {
+    pub id: String,
+    pub url: String,
+    pub username: String,
+    pub title: Option<String>,
+    pub note: Option<String>,
+    pub folder: Option<String>,
+    pub tags: Vec<String>,
+    pub created_at: String,
+    pub updated_at: Option<String>,
+    pub password: Option<String>,
+}
+
+// update で指定された変更内容（None の項目は既存値を維持）
+#[derive(Debug, Default)]
+pub struct EntryChanges {
+    pub url: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub title: Option<String>,
+    pub note: Option<String>,
+    pub totp: Option<String>, // base32 / otpauth:// URI / steam://
+    pub fields: Vec<CustomField>,
+    pub unset_fields: Vec<String>,
+    pub folder: Option<String>, // 空文字はフォルダ解除
+    pub tags: Vec<String>,
+    pub untags: Vec<String>,
+}
+
+impl EntryChanges {
+    // 監査ログ用に変更された項目名だけを返す（値は含めない）
+    pub fn changed_names(&self) -> Vec<&'static str> {
+        let mut names = Vec::new();
+        if self.url.is_some() { names.push("url"); }
+        if self.username.is_some() { names.push("username"); }
+        if self.password.is_some() { names.push("password"); }
+        if self.title.is_some() { names.push("title"); }
+        if self.note.is_some() { names.push("note"); }
+        if self.totp.is_some() { names.push("totp"); }
+        if !self.fields.is_empty() || !self.unset_fields.is_empty() { names.push("fields"); }
+        if self.folder.is_some() { names.push("folder"); }
+        if !self.tags.is_empty() || !self.untags.is_empty() { names.push("tags"); }
+        names
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.url.is_none() && self.username.is_none() && self.password.is_none()
+            && self.title.is_none() && self.note.is_none() && self.totp.is_none()
+            && self.fields.is_empty() && self.unset_fields.is_empty()
+            && self.folder.is_none() && self.tags.is_empty() && self.untags.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TrashEntry {
+    pub id: String,
+    pub url: String,
+    pub username: String,
+    pub title: Option<String>,
+    pub deleted_at: String,
+}
+
+// エクスポート/インポート用の平文レコード（password/totp/secretフィールドは復号済み）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportRecord {
+    #[serde(default)]
+    pub id: String,
+    pub url: String,
+    pub username: String,
+    pub password: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub created_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub totp: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<CustomField>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub folder: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl Vault {
+    // SQLite の保管庫を開く（存在しなければ作成）。開いた直後はロック状態
+    pub fn open(path: &Path) -> Result<Self> {
+        Ok(Self::with_store(Box::new(SqliteStore::open(path)?)))
+    }
+
+    // ファイルを作らない保管庫（テスト用）
+    pub fn in_memory() -> Self {
+        Self::with_store(Box::new(MemoryStore::new()))
+    }
+
+    pub fn with_store(store: Box<dyn VaultStore>) -> Self {
+        Vault { store, cipher: None }
+    }
+
+    // シークレットを鍵確認値と照合してロックを解除する。
+    // 鍵確認値の無い保管庫では最新のレコードが復号できることを確かめてから登録する
+    pub fn unlock(&mut self, secret: &str) -> Result<()> {
+        let cipher = Cipher::new(secret);
+        match self.store.meta(KEY_CHECK)? {
+            Some(value) => {
+                if cipher.decrypt(KEY_CHECK, &value).is_err() { return Err(Error::WrongSecret); }
+            }
+            None => {
+                let records = self.store.records()?;
+                let newest = records.iter().max_by(|a, b| a.created_at.cmp(&b.created_at));
+                if newest.is_some_and(|r| crypto::looks_encrypted(&r.password) && cipher.decrypt(&r.id, &r.password).is_err()) {
+                    return Err(Error::WrongSecret);
+                }
+                self.store.set_meta(KEY_CHECK, &cipher.encrypt(KEY_CHECK, KEY_CHECK)?)?;
+            }
+        }
+        self.cipher = Some(cipher);
+        Ok(())
+    }
+
+    pub fn lock(&mut self) {
+        self.cipher = None;
+    }
+
+    pub fn is_unlocked(&self) -> bool {
+        self.cipher.is_some()
+    }
+
+    fn cipher(&self) -> Result<&Cipher> {
+        self.cipher.as_ref().ok_or(Error::Locked)
+    }
+
+    // 暗号化前のバージョンで平文のまま保存された値はそのまま返す
+    fn decrypt_or_raw(&self, id: &str, value: String) -> String {
+        match &self.cipher {
+            Some(c) => c.decrypt(id, &value).unwrap_or(value),
+            None => value,
+        }
+    }
+
+    // ゴミ箱のものを除くレコード
+    fn live_records(&self) -> Result<Vec<StoredRecord>> {
+        let mut records = self.store.records()?;
+        records.retain(|r| r.deleted_at.is_none());
+        Ok(records)
+    }
+
+    fn live_record(&self, id: &str) -> Result<StoredRecord> {
+        self.store
+            .record(id)?
+            .filter(|r| r.deleted_at.is_none())
+            .ok_or_else(|| Error::NotFound(format!("id={}", id)))
+    }
+
+    // パスワードと secret フィールドを復号する
+    fn entry(&self, rec: StoredRecord) -> Entry {
+        let password = self.decrypt_or_raw(&rec.id, rec.password);
+        let fields = rec
+            .fields
+            .into_iter()
+            .map(|f| CustomField { value: if f.secret { self.decrypt_or_raw(&rec.id, f.value) } else { f.value }, ..f })
+            .collect();
+        Entry {
+            id: rec.id,
+            url: rec.url,
+            username: rec.username,
+            password,
+            title: rec.title,
+            note: rec.note,
+            folder: rec.folder,
+            tags: rec.tags,
+            fields,
+            created_at: rec.created_at,
+            updated_at: rec.updated_at,
+        }
+    }
+
+    // 同名のフィールドは上書き
+    fn set_fields(&self, rec: &mut StoredRecord, fields: &[CustomField]) -> Result<()> {
+        for f in fields {
+            let value = if f.secret { self.cipher()?.encrypt(&rec.id, &f.value)? } else { f.value.clone() };
+            rec.fields.retain(|x| x.name != f.name);
+            rec.fields.push(CustomField { name: f.name.clone(), value, secret: f.secret });
+        }
+        rec.fields.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(())
+    }
+
+    // totp を正規化した URI にして暗号化し、HOTP ならカウンタを初期化する
+    fn set_totp(&self, rec: &mut StoredRecord, spec: &str) -> Result<()> {
+        let params = otp::OtpParams::parse(spec)?;
+        rec.totp = Some(self.cipher()?.encrypt(&rec.id, &params.to_uri())?);
+        rec.otp_counter = (params.kind == otp::Kind::Hotp).then_some(params.counter as i64);
+        Ok(())
+    }
+
+    // 同じ URL のレコードがあれば上書きし、なければ新規に保存する。
+    // 上書き時は username/password を置き換え、title/note/totp は指定があるものだけ置き換える
+    pub fn add(&self, new: &NewEntry) -> Result<Entry> {
+        let cipher = self.cipher()?;
+        let now = Utc::now().to_rfc3339();
+        let existing = self.live_records()?.into_iter().filter(|r| r.url == new.url).min_by(newest_first);
+        let mut rec = match existing {
+            Some(mut rec) => {
+                rec.updated_at = Some(now);
+                rec
+            }
+            None => StoredRecord { id: uuid::Uuid::new_v4().to_string(), url: new.url.clone(), created_at: now, ..Default::default() },
+        };
+        rec.username = new.username.clone();
+        rec.password = cipher.encrypt(&rec.id, &new.password)?;
+        if new.title.is_some() { rec.title = new.title.clone(); }
+        if new.note.is_some() { rec.note = new.note.clone(); }
+        if let Some(spec) = new.totp.as_deref().filter(|s| !s.is_empty()) { self.set_totp(&mut rec, spec)?; }
+        self.set_fields(&mut rec, &new.fields)?;
+        if let Some(folder) = new.folder.as_deref().and_then(normalize_folder) { rec.folder = Some(folder); }
+        add_tags(&mut rec, &new.tags);
+        self.store.put(&rec)?;
+        Ok(self.entry(rec))
+    }
+
+    pub fn get(&self, id: &str) -> Result<Entry> {
+        self.cipher()?;
+        Ok(self.entry(self.live_record(id)?))
+    }
+
+    // 入力どおりの URL に一致するものを先頭に、新しい順で返す。
+    // host/domain 照合は入力を URL として解釈できない場合は完全一致のみ
+    pub fn find_by_url(&self, url: &str, mode: site::MatchMode) -> Result<Vec<Entry>> {
+        self.cipher()?;
+        let wanted = site::parse(url);
+        let (host, domain) = match (mode, &wanted) {
+            (site::MatchMode::Host, Some(s)) => (Some(s.host.as_str()), None),
+            (site::MatchMode::Domain, Some(s)) => (None, Some(s.domain.as_str())),
+            _ => (None, None),
+        };
+        let mut records = self.store.records_for_site(url, host, domain)?;
+        records.retain(|r| r.deleted_at.is_none());
+        records.sort_by(|a, b| (b.url == url).cmp(&(a.url == url)).then_with(|| newest_first(a, b)));
+        Ok(records.into_iter().map(|r| self.entry(r)).collect())
+    }
+
+    // find_by_url の先頭（入力どおりの URL、なければ最新）の1件。参照（run / inject）の解決用
+    pub fn fetch_by_url(&self, url: &str, mode: site::MatchMode) -> Result<Entry> {
+        self.find_by_url(url, mode)?.into_iter().next().ok_or_else(|| Error::NotFound(format!("url={}", url)))
+    }
+
+    // URL が完全に一致し OTP を登録済みのもの（新しい順）
+    pub fn otp_secrets(&self, url: &str) -> Result<Vec<OtpSecret>> {
+        let cipher = self.cipher()?;
+        let mut records: Vec<_> = self.live_records()?.into_iter().filter(|r| r.url == url && r.totp.is_some()).collect();
+        records.sort_by(newest_first);
+        let mut out = Vec::new();
+        for rec in records {
+            // シードは復号できなければコードを計算できないためエラー扱い
+            let uri = cipher.decrypt(&rec.id, rec.totp.as_deref().unwrap_or_default())?;
+            out.push(OtpSecret { id: rec.id, username: rec.username, uri });
+        }
+        Ok(out)
+    }
+
+    // 現在の HOTP カウンタ値を返し、保存先で 1 進める
+    pub fn next_hotp_counter(&self, id: &str) -> Result<u64> {
+        self.store.next_hotp_counter(id)
+    }
+
+    // url/username/title/note の部分一致（大文字小文字を区別しない）。tag 指定時はそのタグの付いたものだけ
+    pub fn search(&self, keyword: &str, tag: Option<&str>) -> Result<Vec<Entry>> {
+        self.cipher()?;
+        let mut records: Vec<_> = self
+            .live_records()?
+            .into_iter()
+            .filter(|r| has_tag(r, tag))
+            .filter(|r| {
+                contains_ci(&r.url, keyword)
+                    || contains_ci(&r.username, keyword)
+                    || contains_ci(r.title.as_deref().unwrap_or(""), keyword)
+                    || contains_ci(r.note.as_deref().unwrap_or(""), keyword)
+            })
+            .collect();
+        records.sort_by(newest_first);
+        Ok(records.into_iter().map(|r| self.entry(r)).collect())
+    }
+
+    // 保存先の全文検索で関連度順に返す（SQLite のみ対応）
+    pub fn search_fts(&self, query: &str, tag: Option<&str>) -> Result<Vec<Entry>> {
+        self.cipher()?;
+        let mut out = Vec::new();
+        for id in self.store.full_text_search(query)? {
+            if let Some(rec) = self.store.record(&id)?.filter(|r| r.deleted_at.is_none() && has_tag(r, tag)) {
+                out.push(self.entry(rec));
+            }
+        }
+        Ok(out)
+    }
+
+    // あいまい検索。スコアの高い順に最大 limit 件を返す
+    pub fn search_fuzzy(&self, query: &str, tag: Option<&str>, limit: usize) -> Result<Vec<(f64, Entry)>> {
+        self.cipher()?;
+        let now = Utc::now();
+        let mut scored = Vec::new();
+        for rec in self.live_records()?.into_iter().filter(|r| has_tag(r, tag)) {
+            let fields = [
+                fuzzy::Field { text: rec.title.as_deref().unwrap_or(""), weight: fuzzy::WEIGHT_TITLE },
+                fuzzy::Field { text: fuzzy::host_of(&rec.url), weight: fuzzy::WEIGHT_DOMAIN },
+                fuzzy::Field { text: &rec.username, weight: fuzzy::WEIGHT_USERNAME },
+                fuzzy::Field { text: &rec.url, weight: fuzzy::WEIGHT_URL },
+                fuzzy::Field { text: rec.note.as_deref().unwrap_or(""), weight: fuzzy::WEIGHT_NOTE },
+            ];
+            let touched_at = rec.updated_at.as_deref().unwrap_or(&rec.created_at);
+            let age_days = chrono::DateTime::parse_from_rfc3339(touched_at)
+                .map(|t| (now - t.with_timezone(&Utc)).num_seconds() as f64 / 86400.0)
+                .unwrap_or(365.0);
+            if let Some(score) = fuzzy::score_record(query, &fields, age_days) {
+                scored.push((score, rec));
+            }
+        }
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+        scored.truncate(limit);
+        Ok(scored.into_iter().map(|(score, rec)| (score, self.entry(rec))).collect())
+    }
+
+    // folder 指定時はそのフォルダと配下のフォルダを対象にする。
+    // 並び順が同じものは作成日時（desc なら新しい順）、id の順
+    pub fn list(&self, opts: &ListOptions) -> Result<Vec<ListEntry>> {
+        if opts.show_passwords { self.cipher()?; }
+        let mut records: Vec<_> = self
+            .live_records()?
+            .into_iter()
+            .filter(|r| opts.folder.as_deref().is_none_or(|f| in_folder(r, f)))
+            .filter(|r| has_tag(r, opts.tag.as_deref()))
+            .collect();
+        records.sort_by(|a, b| {
+            let ord = opts.sort.compare(a, b).then_with(|| a.created_at.cmp(&b.created_at));
+            if opts.desc { ord.reverse() } else { ord }.then_with(|| a.id.cmp(&b.id))
+        });
+        let limit = opts.limit.map(|n| n.max(0) as usize).unwrap_or(usize::MAX);
+        Ok(records
+            .into_iter()
+            .skip(opts.offset.max(0) as usize)
+            .take(limit)
+            .map(|rec| ListEntry {
+                password: opts.show_passwords.then(|| self.decrypt_or_raw(&rec.id, rec.password.clone())),
+                id: rec.id,
+                url: rec.url,
+                username: rec.username,
+                title: rec.title,
+                note: rec.note,
+                folder: rec.folder,
+                tags: rec.tags,
+                created_at: rec.created_at,
+                updated_at: rec.updated_at,
+            })
+            .collect())
+    }
+
+    pub fn update(&self, id: &str, changes: &EntryChanges) -> Result<Entry> {
+        let cipher = self.cipher()?;
+        let mut rec = self.live_record(id)?;
+        if let Some(v) = &changes.url { rec.url = v.clone(); }
+        if let Some(v) = &changes.username { rec.username = v.clone(); }
+        if let Some(v) = &changes.password { rec.password = cipher.encrypt(&rec.id, v)?; }
+        if let Some(v) = &changes.title { rec.title = Some(v.clone()); }
+        if let Some(v) = &changes.note { rec.note = Some(v.clone()); }
+        if let Some(v) = &changes.totp { self.set_totp(&mut rec, v)?; }
+        rec.fields.retain(|f| !changes.unset_fields.contains(&f.name));
+        self.set_fields(&mut rec, &changes.fields)?;
+        if let Some(v) = &changes.folder { rec.folder = normalize_folder(v); }
+        add_tags(&mut rec, &changes.tags);
+        rec.tags.retain(|t| !changes.untags.contains(t));
+        rec.updated_at = Some(Utc::now().to_rfc3339());
+        self.store.put(&rec)?;
+        Ok(self.entry(rec))
+    }
+
+    // 削除はゴミ箱への移動（deleted_at を設定）。完全な削除は purge
+    pub fn delete(&self, id: &str) -> Result<()> {
+        let mut rec = self.live_record(id)?;
+        rec.deleted_at = Some(Utc::now().to_rfc3339());
+        self.store.put(&rec)
+    }
+
+    // id（完全一致または一意な先頭部分）か条件（selector の書式）で対象を決める。
+    // 条件に複数件が一致する場合は all_matching 指定時のみ全件を返し、それ以外は Ambiguous
+    pub fn resolve(&self, tokens: &[String], all_matching: bool) -> Result<Vec<Target>> {
+        let mut records = self.live_records()?;
+        records.sort_by(newest_first);
+        let target = |r: StoredRecord| Target { id: r.id, url: r.url, username: r.username };
+        match selector::parse(tokens)? {
+            selector::Target::Id(id) => {
+                if let Some(i) = records.iter().position(|r| r.id == id) {
+                    return Ok(vec![target(records.swap_remove(i))]);
+                }
+                if id.chars().count() < selector::MIN_PREFIX {
+                    return Err(Error::InvalidInput(t!(
+                        "id={} が見つかりません（先頭部分で指定する場合は{}文字以上）",
+                        id,
+                        selector::MIN_PREFIX
+                    )));
+                }
+                let prefix = id.to_lowercase();
+                let rows: Vec<_> = records.into_iter().filter(|r| r.id.to_lowercase().starts_with(&prefix)).map(target).collect();
+                match rows.len() {
+                    0 => Err(Error::NotFound(format!("id={}", id))),
+                    1 => Ok(rows),
+                    _ => Err(Error::Ambiguous { query: t!("id の先頭 {}", id), candidates: rows }),
+                }
+            }
+            selector::Target::Terms(terms) => {
+                let matches = |r: &StoredRecord| {
+                    terms.iter().all(|term| match term {
+                        selector::Term::Url(v) => contains_ci(&r.url, v),
+                        selector::Term::User(v) => &r.username == v,
+                        selector::Term::Title(v) => contains_ci(r.title.as_deref().unwrap_or(""), v),
+                        selector::Term::Folder(v) => in_folder(r, &normalize_folder(v).unwrap_or_else(|| v.clone())),
+                        selector::Term::Tag(v) => has_tag(r, Some(v)),
+                    })
+                };
+                let rows: Vec<_> = records.into_iter().filter(|r| matches(r)).map(target).collect();
+                match rows.len() {
+                    0 => Err(Error::NotFound(t!("条件（{}）に一致するレコード", tokens.join(" ")))),
+                    n if n > 1 && !all_matching => Err(Error::Ambiguous { query: t!("条件（{}）", tokens.join(" ")), candidates: rows }),
+                    _ => Ok(rows),
+                }
+            }
+        }
+    }
+
+    // ゴミ箱の一覧（削除日時の新しい順）
+    pub fn trash(&self) -> Result<Vec<TrashEntry>> {
+        let mut out: Vec<_> = self
+            .store
+            .records()?
+            .into_iter()
+            .filter_map(|r| {
+                let deleted_at = r.deleted_at?;
+                Some(TrashEntry { id: r.id, url: r.url, username: r.username, title: r.title, deleted_at })
+            })
+            .collect();
+        out.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at));
+        Ok(out)
+    }
+
+    fn trashed_record(&self, id: &str) -> Result<StoredRecord> {
+        self.store
+            .record(id)?
+            .filter(|r| r.deleted_at.is_some())
+            .ok_or_else(|| Error::NotFound(t!("ゴミ箱の id={}", id)))
+    }
+
+    pub fn restore(&self, id: &str) -> Result<()> {
+        let mut rec = self.trashed_record(id)?;
+        rec.deleted_at = None;
+        rec.updated_at = Some(Utc::now().to_rfc3339());
+        self.store.put(&rec)
+    }
+
+    // ゴミ箱のレコードを任意フィールド・タグごと完全に削除する
+    pub fn purge(&self, id: &str) -> Result<()> {
+        self.trashed_record(id)?;
+        self.store.remove(id)
+    }
+
+    pub fn export_records(&self) -> Result<Vec<ExportRecord>> {
+        self.cipher()?;
+        let mut records = self.live_records()?;
+        records.sort_by(newest_first);
+        let mut out = Vec::new();
+        for rec in records {
+            // HOTP は現在のカウンタを URI に反映して出力
+            let totp = rec.totp.clone().map(|t| self.decrypt_or_raw(&rec.id, t)).map(|uri| {
+                match (otp::OtpParams::parse(&uri), rec.otp_counter) {
+                    (Ok(mut p), Some(c)) if p.kind == otp::Kind::Hotp => { p.counter = c as u64; p.to_uri() }
+                    _ => uri,
+                }
+            });
+            let entry = self.entry(rec);
+            out.push(ExportRecord {
+                id: entry.id,
+                url: entry.url,
+                username: entry.username,
+                password: entry.password,
+                title: entry.title,
+                note: entry.note,
+                created_at: entry.created_at,
+                totp,
+                fields: entry.fields,
+                folder: entry.folder,
+                tags: entry.tags,
+            });
+        }
+        Ok(out)
+    }
+
+    // 書き出し先は private_file で 0600 にする（平文のパスワードを含むため）
+    pub fn export_csv(&self, path: &Path) -> Result<()> {
+        let mut wtr = WriterBuilder::new().from_writer(Vec::new());
+        // ヘッダー: id,url,username,password,title,note,created_at,totp,fields,folder,tags
+        // （password/totpは平文、fieldsはJSON配列、tagsはカンマ区切りで出力）
+        wtr.write_record(["id", "url", "username", "password", "title", "note", "created_at", "totp", "fields", "folder", "tags"])?;
+        for rec in self.export_records()? {
+            let fields = if rec.fields.is_empty() { String::new() } else { serde_json::to_string(&rec.fields)? };
+            wtr.write_record([
+                rec.id,
+                rec.url,
+                rec.username,
+                rec.password,
+                rec.title.unwrap_or_default(),
+                rec.note.unwrap_or_default(),
+                rec.created_at,
+                rec.totp.unwrap_or_default(),
+                fields,
+                rec.folder.unwrap_or_default(),
+                rec.tags.join(","),
+            ])?;
+        }
+        let data = wtr.into_inner().map_err(|e| e.into_error())?;
+        private_file::write(path, &data)?;
+        Ok(())
+    }
+
+    pub fn export_json(&self, path: &Path) -> Result<()> {
+        let data = serde_json::to_string_pretty(&self.export_records()?)?;
+        private_file::write(path, data.as_bytes())?;
+        Ok(())
+    }
+
+    // created_at は無視して現在時刻を使用
+    pub fn import_record(&self, rec: ExportRecord) -> Result<Entry> {
+        self.add(&NewEntry {
+            url: rec.url,
+            username: rec.username,
+            password: rec.password,
+            title: rec.title,
+            note: rec.note,
+            totp: rec.totp,
+            fields: rec.fields,
+            folder: rec.folder,
+            tags: rec.tags,
+        })
+    }
+
+    pub fn import_csv(&self, path: &Path) -> Result<()> {
+        let mut rdr = ReaderBuilder::new().has_headers(true).from_path(path)?;
+        let headers = rdr.headers()?.clone();
+        for result in rdr.records() {
+            let rec = result?; // CSVレコード
+            // ヘッダー名で取得（存在しない場合は位置依存でフォールバック）
+            let get = |name: &str| headers.iter().position(|h| h == name).and_then(|i| rec.get(i).map(|s| s.to_string()));
+            let required = |name: &str, pos: usize| {
+                get(name).or_else(|| rec.get(pos).map(|s| s.to_string())).ok_or_else(|| Error::Format(t!("{} がありません", name)))
+            };
+            let url = required("url", 0)?;
+            let username = required("username", 1)?;
+            let password = required("password", 2)?;
+            let title = get("title").or_else(|| rec.get(3).map(|s| s.to_string()));
+            let note = get("note").or_else(|| rec.get(4).map(|s| s.to_string()));
+            // totp / fields / folder / tags は列がある場合のみ取り込み
+            let totp = get("totp").filter(|s| !s.is_empty());
+            let fields: Vec<CustomField> = match get("fields").filter(|s| !s.is_empty()) {
+                Some(json) => serde_json::from_str(&json)?,
+                None => Vec::new(),
+            };
+            let folder = get("folder").filter(|s| !s.is_empty());
+            let tags = get("tags").map(|s| parse_tags_arg(&s)).unwrap_or_default();
+            self.import_record(ExportRecord {
+                id: String::new(),
+                url,
+                username,
+                password,
+                title,
+                note,
+                created_at: String::new(),
+                totp,
+                fields,
+                folder,
+                tags,
+            })?;
+        }
+        Ok(())
+    }
+
+    pub fn import_json(&self, path: &Path) -> Result<()> {
+        let data = fs::read_to_string(path)?;
+        let records: Vec<ExportRecord> = serde_json::from_str(&data)?;
+        for rec in records {
+            self.import_record(rec)?;
+        }
+        Ok(())
+    }
+}
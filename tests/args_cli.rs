@@ -0,0 +1,76 @@
+mod common;
+
+use predicates::prelude::*;
+use std::path::Path;
+use common::{SECRET, authenticate, run, temp_home};
+
+// 補完エンジンとして呼び出す（fish 形式は候補を1行ずつ出力する）
+fn complete(home: &Path, words: &[&str]) -> String {
+    let mut cmd = run(home, &["--", "tsupasswd"]);
+    cmd.args(words).env("COMPLETE", "fish").env("_CLAP_COMPLETE_INDEX", words.len().to_string());
+    String::from_utf8(cmd.assert().success().get_output().stdout.clone()).expect("utf8")
+}
+
+#[test]
+fn unknown_arguments_and_bad_values_are_rejected() {
+    let home_dir = temp_home("tsupasswd_args_test_");
+    let home = home_dir.path().to_path_buf();
+    authenticate(&home);
+
+    run(&home, &["foo"]).assert().failure().stderr(predicate::str::contains("1以上の整数"));
+    run(&home, &["0"]).assert().failure();
+    run(&home, &["auth", SECRET, "--ttl", "abc"]).assert().failure().stderr(predicate::str::contains("--ttl"));
+    // 設定 session.ttl と同じ 1〜525600 分の範囲（大きな値でも桁あふれしない）
+    for bad in ["0", "525601", "9223372036854775807"] {
+        run(&home, &["auth", SECRET, "--ttl", bad]).assert().code(2).stderr(predicate::str::contains("--ttl"));
+    }
+    run(&home, &["auth", SECRET, "--ttl", "525600"]).assert().success();
+    run(&home, &["list", "--bogus"]).assert().failure().stderr(predicate::str::contains("--bogus"));
+    run(&home, &["search", "a", "b"]).assert().failure();
+    run(&home, &["update", "3f2a", "--length", "x"]).assert().failure().stderr(predicate::str::contains("--length"));
+    run(&home, &["12", "add", "a.example.com", "alice"]).assert().failure();
+    run(&home, &["add", "a.example.com", "alice", "pw", "--secret", "--field", "pin=1"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--field の後"));
+
+    // 全体オプションはコマンドの前後どちらにも書ける
+    run(&home, &["--vault", "work", "status"]).assert().failure().stderr(predicate::str::contains("未認証"));
+    run(&home, &["status", "--vault", "work"]).assert().failure().stderr(predicate::str::contains("未認証"));
+    assert_eq!(String::from_utf8(run(&home, &["8"]).assert().success().get_output().stdout.clone()).unwrap().trim().len(), 8);
+}
+
+#[test]
+fn help_is_generated_per_subcommand() {
+    let home_dir = temp_home("tsupasswd_args_test_");
+    let home = home_dir.path().to_path_buf();
+    run(&home, &["--help"]).assert().success().stdout(predicate::str::contains("completions").and(predicate::str::contains("使い方")));
+    run(&home, &["get", "--help"]).assert().success().stdout(predicate::str::contains("--match").and(predicate::str::contains("--clear-after")));
+    run(&home, &["help", "trash"]).assert().success().stdout(predicate::str::contains("restore"));
+    run(&home, &["update", "-h"]).assert().success().stdout(predicate::str::contains("--all-matching"));
+}
+
+#[test]
+fn completion_scripts_and_dynamic_url_candidates() {
+    let home_dir = temp_home("tsupasswd_args_test_");
+    let home = home_dir.path().to_path_buf();
+    for shell in ["bash", "zsh", "fish"] {
+        run(&home, &["completions", shell]).assert().success().stdout(predicate::str::contains("tsupasswd").and(predicate::str::contains("COMPLETE")));
+    }
+    run(&home, &["completions", "tcsh"]).assert().failure();
+
+    assert!(complete(&home, &["li"]).lines().any(|l| l.starts_with("list")));
+    assert!(complete(&home, &["config", "get", "gen"]).contains("generate.length"));
+
+    authenticate(&home);
+    run(&home, &["add", "https://github.com/login", "alice", "pw"]).assert().success();
+    run(&home, &["add", "gitlab.example.com", "bob", "pw"]).assert().success();
+    let urls = complete(&home, &["get", "git"]);
+    assert!(urls.lines().any(|l| l == "gitlab.example.com"), "{}", urls);
+    assert!(!urls.contains("github.com"), "{}", urls);
+    assert!(complete(&home, &["otp", "https://"]).contains("https://github.com/login"));
+    // 別の保管庫やセッションがない場合は URL を出さない
+    assert!(!complete(&home, &["--vault", "work", "get", "git"]).contains("gitlab"));
+    run(&home, &["logout"]).assert().success();
+    assert!(!complete(&home, &["get", "git"]).contains("gitlab"));
+}
@@ -0,0 +1,112 @@
+mod common;
+
+use predicates::prelude::*;
+use std::fs;
+use std::path::Path;
+use common::{SECRET, authenticate, command, run, temp_home};
+
+fn log_entries(home: &Path, args: &[&str]) -> Vec<serde_json::Value> {
+    let mut full = vec!["log", "--json"];
+    full.extend_from_slice(args);
+    let out = run(home, &full).assert().success().get_output().stdout.clone();
+    serde_json::from_slice::<serde_json::Value>(&out).expect("invalid json").as_array().expect("array expected").clone()
+}
+
+#[test]
+fn operations_are_logged_without_secrets() {
+    let home_dir = temp_home("tsupasswd_audit_test_");
+    let home = home_dir.path().to_path_buf();
+    authenticate(&home);
+
+    run(&home, &["add", "https://example.com", "alice", "hunter2-secret"]).assert().success();
+    let out = run(&home, &["search", "example.com", "--json"]).assert().success().get_output().stdout.clone();
+    let v: serde_json::Value = serde_json::from_slice(&out).expect("invalid json");
+    let id = v[0]["id"].as_str().expect("id missing").to_string();
+
+    run(&home, &["get", "https://example.com"]).assert().success();
+    run(&home, &["get", "https://missing.example.org"]).assert().failure();
+    run(&home, &["update", &id, "--password", "rotated-secret"]).assert().success();
+    run(&home, &["delete", &id, "--yes"]).assert().success();
+
+    let ops: Vec<(String, Option<String>, bool)> = log_entries(&home, &[])
+        .iter()
+        .map(|e| (e["op"].as_str().unwrap().to_string(), e["id"].as_str().map(str::to_string), e["ok"].as_bool().unwrap()))
+        .collect();
+    assert_eq!(
+        ops,
+        vec![
+            ("auth".to_string(), None, true),
+            ("add".to_string(), Some(id.clone()), true),
+            ("get".to_string(), Some(id.clone()), true),
+            ("get".to_string(), None, false),
+            ("update".to_string(), Some(id.clone()), true),
+            ("delete".to_string(), Some(id.clone()), true),
+        ]
+    );
+
+    let gets = log_entries(&home, &["--op", "get"]);
+    assert_eq!(gets.len(), 2);
+
+    let raw = fs::read_to_string(home.join(".local").join("state").join("tsupasswd").join("audit.log")).expect("audit log missing");
+    for secret in [SECRET, "hunter2-secret", "rotated-secret", "example.com"] {
+        assert!(!raw.contains(secret), "{} leaked into audit log: {}", secret, raw);
+    }
+}
+
+#[test]
+fn verify_detects_edited_removed_and_truncated_entries() {
+    let home_dir = temp_home("tsupasswd_audit_test_");
+    let home = home_dir.path().to_path_buf();
+    authenticate(&home);
+    for user in ["a", "b", "c"] {
+        run(&home, &["add", "https://example.com", user, "pw"]).assert().success();
+    }
+    run(&home, &["log", "--verify"]).assert().success().stdout(predicate::str::contains("4 件"));
+
+    let path = home.join(".local").join("state").join("tsupasswd").join("audit.log");
+    let original = fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = original.lines().collect();
+
+    // 途中の行の書き換え
+    let edited = original.replacen("\"ok\":true", "\"ok\":false", 1);
+    fs::write(&path, edited).unwrap();
+    run(&home, &["log", "--verify"]).assert().failure().stderr(predicate::str::contains("1 行目"));
+
+    // 途中の行の削除
+    let removed: Vec<&str> = lines.iter().enumerate().filter(|(i, _)| *i != 1).map(|(_, l)| *l).collect();
+    fs::write(&path, removed.join("\n") + "\n").unwrap();
+    run(&home, &["log", "--verify"]).assert().failure().stderr(predicate::str::contains("2 行目"));
+
+    // 末尾の行の削除
+    fs::write(&path, lines[..lines.len() - 1].join("\n") + "\n").unwrap();
+    run(&home, &["log", "--verify"]).assert().failure().stderr(predicate::str::contains("末尾"));
+
+    fs::write(&path, &original).unwrap();
+    run(&home, &["log", "--verify"]).assert().success();
+}
+
+#[test]
+fn concurrent_processes_keep_a_single_chain() {
+    let home_dir = temp_home("tsupasswd_audit_test_");
+    let home = home_dir.path().to_path_buf();
+    authenticate(&home);
+
+    let children: Vec<_> = (0..8)
+        .map(|_| command(&home, &["get", "https://missing.example.org"]).stdout(std::process::Stdio::null()).stderr(std::process::Stdio::null()).spawn().expect("failed to spawn"))
+        .collect();
+    for mut child in children {
+        child.wait().expect("failed to wait");
+    }
+    run(&home, &["log", "--verify"]).assert().success().stdout(predicate::str::contains("9"));
+}
+
+#[test]
+fn since_rejects_out_of_range_periods() {
+    let home_dir = temp_home("tsupasswd_audit_test_");
+    let home = home_dir.path().to_path_buf();
+    authenticate(&home);
+    for bad in ["100000000000000d", "9999999999d", "7日", "日"] {
+        run(&home, &["log", "--since", bad]).assert().code(2);
+    }
+    assert_eq!(log_entries(&home, &["--since", "1h"]).len(), 1);
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use std::path::Path;
+use common::{SECRET, run, set_auth_failures, temp_home};
+
+fn auth(home: &Path, secret: &str) -> Command {
+    let mut cmd = run(home, &["auth", "--password-stdin"]);
+    cmd.write_stdin(secret.to_string());
+    cmd
+}
+
+#[test]
+fn auth_verifies_against_stored_verifier() {
+    let home_dir = temp_home("tsupasswd_auth_test_");
+    let home = home_dir.path().to_path_buf();
+
+    // 初回の auth で AUTH_SECRET から verifier を作る（平文は保存しない）
+    auth(&home, SECRET).assert().success();
+    let verifier = fs::read_to_string(home.join(".local").join("state").join("tsupasswd").join("verifier")).expect("verifier missing");
+    assert!(!verifier.contains(SECRET));
+
+    // 以降は環境変数ではなく verifier と照合する
+    run(&home, &["auth", "--password-stdin"])
+        .env("AUTH_SECRET", "changed-secret")
+        .write_stdin("changed-secret")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn repeated_failures_lock_out_with_backoff_and_are_audited() {
+    let home_dir = temp_home("tsupasswd_auth_test_");
+    let home = home_dir.path().to_path_buf();
+
+    for _ in 0..3 {
+        auth(&home, "wrong").assert().failure().stderr(predicate::str::contains("認証に失敗しました"));
+    }
+    let attempts = fs::read_to_string(home.join(".local").join("state").join("tsupasswd").join("auth_attempts")).expect("attempts missing");
+    assert!(attempts.contains("\"failures\":3"), "{}", attempts);
+    // 待ち時間中は正しいシークレットでも拒否
+    set_auth_failures(&home, 10, 0);
+    auth(&home, SECRET).assert().code(5).stderr(predicate::str::contains("ロック"));
+
+    let log = fs::read_to_string(home.join(".local").join("state").join("tsupasswd").join("audit.log")).expect("audit log missing");
+    let failures = log
+        .lines()
+        .filter(|l| l.contains("\"op\":\"auth\"") && l.contains("\"ok\":false") && !l.contains("locked"))
+        .count();
+    assert_eq!(failures, 3, "unexpected log: {}", log);
+    assert!(!log.contains("wrong"), "secret leaked into audit log: {}", log);
+
+    // 待ち時間の経過後は成功し、失敗回数はリセットされる
+    set_auth_failures(&home, 3, 2);
+    auth(&home, SECRET).assert().success();
+    auth(&home, "wrong").assert().failure().stderr(predicate::str::contains("認証に失敗しました"));
+    auth(&home, SECRET).assert().success();
+}
+
+#[test]
+fn concurrent_failures_are_all_counted() {
+    let home_dir = temp_home("tsupasswd_auth_test_");
+    let home = home_dir.path().to_path_buf();
+    auth(&home, SECRET).assert().success();
+
+    // 同時に失敗しても失敗回数を上書きし合わない（3回目までは待ち時間がないため、すべて検証まで進む）
+    std::thread::scope(|s| {
+        for _ in 0..3 {
+            s.spawn(|| auth(&home, "wrong").assert().code(4));
+        }
+    });
+    let attempts = fs::read_to_string(home.join(".local").join("state").join("tsupasswd").join("auth_attempts")).expect("attempts missing");
+    let attempts: serde_json::Value = serde_json::from_str(&attempts).expect("invalid json");
+    assert_eq!(attempts["failures"], 3, "{}", attempts);
+}
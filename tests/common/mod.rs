@@ -0,0 +1,61 @@
+// 統合テストで共有する補助関数（各テストファイルで mod common; として読み込む）
+// テストファイルごとに使う関数が異なるため、使わない関数の警告は出さない
+#![allow(dead_code)]
+
+use assert_cmd::prelude::*;
+use assert_cmd::Command;
+use std::path::Path;
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tempfile::TempDir;
+
+// テストで使う AUTH_SECRET（初回の auth でこの値の verifier を作る）
+pub const SECRET: &str = "test-secret-123";
+
+pub fn bin_cmd() -> process::Command {
+    let mut cmd = process::Command::cargo_bin("tsupasswd").expect("binary 'tsupasswd' not found");
+    // 実行環境の保管庫・設定ファイル・表示言語の指定を引き継がない
+    cmd.env_remove("TSUPASSWD_VAULT").env_remove("XDG_DATA_HOME").env_remove("XDG_STATE_HOME");
+    cmd.env_remove("TSUPASSWD_CONFIG").env_remove("XDG_CONFIG_HOME");
+    cmd.env_remove("LC_ALL").env_remove("LC_MESSAGES").env_remove("LANG");
+    // 認証の鍵導出（PBKDF2）の反復回数を減らす（デバッグビルドのみ有効）
+    cmd.env("TSUPASSWD_PBKDF2_ROUNDS", "1000");
+    cmd
+}
+
+// HOME にする一時ディレクトリ（prefix はテストファイルごとの名前）
+pub fn temp_home(prefix: &str) -> TempDir {
+    tempfile::Builder::new().prefix(prefix).tempdir().expect("failed to create tempdir")
+}
+
+// home を HOME にしたコマンド（spawn するなど std::process::Command が必要な場合）
+pub fn command(home: &Path, args: &[&str]) -> process::Command {
+    let mut cmd = bin_cmd();
+    cmd.env("AUTH_SECRET", SECRET).env("HOME", home).args(args);
+    cmd
+}
+
+// home を HOME にしたコマンド
+pub fn run(home: &Path, args: &[&str]) -> Command {
+    Command::from_std(command(home, args))
+}
+
+// 成功を確かめて標準出力を返す
+pub fn stdout_of(cmd: &mut Command) -> String {
+    String::from_utf8(cmd.assert().success().get_output().stdout.clone()).expect("utf8")
+}
+
+// セッションを開始する（有効期限 5 分）
+pub fn authenticate(home: &Path) {
+    run(home, &["auth", SECRET, "--ttl", "5"]).assert().success();
+}
+
+// 認証の失敗回数と最後の失敗時刻（seconds_ago 秒前）を書き換える。
+// 記録は秒単位のため、実際に失敗させると短い待ち時間は秒の境目で切れてしまう
+pub fn set_auth_failures(home: &Path, failures: u32, seconds_ago: i64) {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).expect("clock before 1970").as_secs() as i64;
+    let state = home.join(".local").join("state").join("tsupasswd");
+    std::fs::create_dir_all(&state).expect("failed to create state dir");
+    let attempts = format!("{{\"failures\":{},\"last_failure\":{}}}", failures, now - seconds_ago);
+    std::fs::write(state.join("auth_attempts"), attempts).expect("failed to write auth_attempts");
+}
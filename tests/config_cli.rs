@@ -0,0 +1,68 @@
+mod common;
+
+use predicates::prelude::*;
+use std::fs;
+use common::{SECRET, run, stdout_of, temp_home};
+
+#[test]
+fn config_set_get_list_round_trip_and_validate() {
+    let home_dir = temp_home("tsupasswd_config_test_");
+    let home = home_dir.path().to_path_buf();
+
+    assert_eq!(stdout_of(&mut run(&home, &["config", "get", "generate.length"])).trim(), "16");
+    run(&home, &["config", "set", "generate.length", "24"]).assert().success();
+    run(&home, &["config", "set", "generate.policy", "digits"]).assert().success();
+    assert_eq!(stdout_of(&mut run(&home, &["config", "get", "generate.length"])).trim(), "24");
+
+    run(&home, &["config", "set", "generate.length", "abc"]).assert().failure().stderr(predicate::str::contains("generate.length"));
+    run(&home, &["config", "set", "generate.policy", "emoji"]).assert().failure().stderr(predicate::str::contains("alnum"));
+    run(&home, &["config", "set", "no.such", "1"]).assert().failure().stderr(predicate::str::contains("不明な設定キー"));
+
+    let raw = fs::read_to_string(home.join(".config").join("tsupasswd").join("config.toml")).expect("config missing");
+    assert!(raw.contains("[generate]"));
+    assert!(raw.contains("length = 24"));
+
+    let list: serde_json::Value = serde_json::from_str(&stdout_of(&mut run(&home, &["config", "list", "--json"]))).expect("invalid json");
+    assert_eq!(list["generate.length"]["value"], "24");
+    assert_eq!(list["generate.length"]["default"], false);
+    assert_eq!(list["session.ttl"]["default"], true);
+
+    // 生成の既定値に反映される
+    let generated = stdout_of(&mut run(&home, &[]));
+    assert_eq!(generated.trim().len(), 24);
+    assert!(generated.trim().chars().all(|c| c.is_ascii_digit()));
+}
+
+#[test]
+fn config_defaults_apply_to_session_output_and_vault() {
+    let home_dir = temp_home("tsupasswd_config_test_");
+    let home = home_dir.path().to_path_buf();
+    run(&home, &["config", "set", "session.ttl", "90"]).assert().success();
+    run(&home, &["config", "set", "output.format", "json"]).assert().success();
+    run(&home, &["config", "set", "vault.path", "work"]).assert().success();
+
+    run(&home, &["auth", SECRET]).assert().success().stdout(predicate::str::contains("90 分"));
+    let status: serde_json::Value = serde_json::from_str(&stdout_of(&mut run(&home, &["status"]))).expect("invalid json");
+    assert!(status["remaining_seconds"].as_i64().unwrap() > 30 * 60);
+    assert_eq!(status["vault"], "work");
+
+    run(&home, &["add", "https://example.com", "alice", "pw"]).assert().success();
+    let found: serde_json::Value = serde_json::from_str(&stdout_of(&mut run(&home, &["search", "example"]))).expect("invalid json");
+    assert_eq!(found.as_array().unwrap().len(), 1);
+    assert!(home.join(".local").join("share").join("tsupasswd").join("vaults").join("work.db").exists());
+}
+
+#[test]
+fn broken_config_warns_and_falls_back_to_defaults() {
+    let home_dir = temp_home("tsupasswd_config_test_");
+    let home = home_dir.path().to_path_buf();
+    let path = home.join("team.toml");
+    fs::write(&path, "[generate]\nlength = \"long\"\n").unwrap();
+
+    let out = run(&home, &[]).env("TSUPASSWD_CONFIG", &path).assert().success().stderr(predicate::str::contains("generate.length")).get_output().stdout.clone();
+    assert_eq!(String::from_utf8(out).unwrap().trim().len(), 16);
+
+    fs::write(&path, "[generate\n").unwrap();
+    run(&home, &["5"]).env("TSUPASSWD_CONFIG", &path).assert().success().stderr(predicate::str::contains("設定ファイルを解釈できません"));
+    run(&home, &["config", "set", "generate.length", "20"]).env("TSUPASSWD_CONFIG", &path).assert().failure();
+}
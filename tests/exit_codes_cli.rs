@@ -0,0 +1,80 @@
+mod common;
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use common::{SECRET, run, set_auth_failures, temp_home};
+
+// 標準エラーの1行を JSON として読み、error と message を返す
+fn json_error(cmd: &mut Command, code: i32) -> (String, String) {
+    let out = cmd.assert().code(code).get_output().stderr.clone();
+    let text = String::from_utf8(out).expect("utf8");
+    let v: serde_json::Value = serde_json::from_str(text.trim()).unwrap_or_else(|e| panic!("not json: {} ({})", text, e));
+    (v["error"].as_str().unwrap_or_default().to_string(), v["message"].as_str().unwrap_or_default().to_string())
+}
+
+#[test]
+fn failures_exit_with_distinct_codes() {
+    let home_dir = temp_home("tsupasswd_exit_test_");
+    let home = home_dir.path().to_path_buf();
+
+    run(&home, &["get", "example.com"]).assert().code(3).stderr(predicate::str::contains("未認証"));
+    run(&home, &["status"]).assert().code(3);
+    run(&home, &["auth", "wrong"]).assert().code(4).stderr(predicate::str::contains("認証に失敗しました"));
+    run(&home, &["auth", "wrong"]).assert().code(4);
+    run(&home, &["auth", "wrong"]).assert().code(4);
+    set_auth_failures(&home, 10, 0);
+    run(&home, &["auth", SECRET]).assert().code(5).stderr(predicate::str::contains("ロック"));
+    set_auth_failures(&home, 3, 2);
+    run(&home, &["auth", SECRET]).assert().success();
+
+    run(&home, &["list", "--bogus"]).assert().code(2);
+    run(&home, &["list", "--columns", "url,bogus"]).assert().code(2).stderr(predicate::str::contains("不明な列"));
+    run(&home, &["add", "a.example.com", "alice", "pw", "--totp", "!!!"]).assert().code(11);
+
+    run(&home, &["add", "a.example.com", "alice", "pw1", "--title", "work"]).assert().success();
+    run(&home, &["add", "b.example.com", "alice", "pw2", "--title", "work"]).assert().success();
+    run(&home, &["get", "missing.example.com"]).assert().code(6);
+    run(&home, &["search", "nothing-matches"]).assert().code(6);
+    run(&home, &["otp", "a.example.com"]).assert().code(6);
+    run(&home, &["delete", "user:alice", "--yes"]).assert().code(7).stderr(predicate::str::contains("--all-matching"));
+    // 端末でなく --yes もない場合は確認できないため中止
+    run(&home, &["delete", "url:a.example.com"]).assert().code(8);
+    run(&home, &["get", "a.example.com"]).assert().success();
+}
+
+#[test]
+fn json_mode_reports_errors_as_objects() {
+    let home_dir = temp_home("tsupasswd_exit_test_");
+    let home = home_dir.path().to_path_buf();
+
+    let (error, message) = json_error(&mut run(&home, &["--json", "get", "example.com"]), 3);
+    assert_eq!(error, "unauthenticated");
+    assert!(message.contains("未認証"), "{}", message);
+
+    run(&home, &["auth", SECRET]).assert().success();
+    run(&home, &["add", "a.example.com", "alice", "pw"]).assert().success();
+    let (error, message) = json_error(&mut run(&home, &["get", "missing.example.com", "--json"]), 6);
+    assert_eq!(error, "not_found");
+    assert!(message.contains("missing.example.com"), "{}", message);
+    assert_eq!(json_error(&mut run(&home, &["search", "zzz", "--json"]), 6).0, "not_found");
+    assert_eq!(json_error(&mut run(&home, &["get", "a.example.com", "--copy", "--json"]), 2).0, "usage");
+    // 入力値の誤りは引数の誤りと別の終了コード
+    assert_eq!(json_error(&mut run(&home, &["--json", "add", "b.example.com", "bob", "pw", "--totp", "!!!"]), 11).0, "invalid_input");
+
+    // 引数の誤りも JSON で報告する（ヘルプはそのまま）
+    let (error, message) = json_error(&mut run(&home, &["--json", "list", "--bogus"]), 2);
+    assert_eq!(error, "usage");
+    assert!(message.contains("--bogus"), "{}", message);
+    run(&home, &["--json", "list", "--help"]).assert().success().stdout(predicate::str::contains("--columns"));
+
+    // 設定で JSON 出力を既定にした場合もエラーは JSON
+    run(&home, &["config", "set", "output.format", "json"]).assert().success();
+    assert_eq!(json_error(&mut run(&home, &["otp", "missing.example.com"]), 6).0, "not_found");
+
+    // --json なしの既定ではメッセージだけを出す
+    run(&home, &["config", "set", "output.format", "text"]).assert().success();
+    run(&home, &["get", "missing.example.com"])
+        .assert()
+        .code(6)
+        .stderr(predicate::str::starts_with("見つかりませんでした"));
+}
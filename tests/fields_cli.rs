@@ -0,0 +1,91 @@
+mod common;
+
+use std::fs;
+use common::{authenticate, run, stdout_of, temp_home};
+
+#[test]
+fn custom_fields_add_update_get_and_export() {
+    let home_dir = temp_home("tsupasswd_fields_test_");
+    let home = home_dir.path().to_path_buf();
+    authenticate(&home);
+
+    run(&home, &[
+        "add", "bank.example.com", "alice", "pw",
+        "--field", "account=123-456",
+        "--field", "pin=9876", "--secret",
+    ])
+    .assert()
+    .success();
+
+    // get --json に任意フィールドが含まれる（secret は復号済み）
+    let v: serde_json::Value = serde_json::from_str(&stdout_of(&mut run(&home, &["get", "bank.example.com", "--json"])))
+        .expect("invalid json");
+    let fields = v[0]["fields"].as_array().expect("fields missing");
+    assert_eq!(fields.len(), 2);
+    assert_eq!(fields[0]["name"], "account");
+    assert_eq!(fields[0]["value"], "123-456");
+    assert_eq!(fields[0]["secret"], false);
+    assert_eq!(fields[1]["name"], "pin");
+    assert_eq!(fields[1]["value"], "9876");
+    assert_eq!(fields[1]["secret"], true);
+
+    // secret フィールドは DB 上では平文で保存されない
+    let db = fs::read(home.join(".local").join("share").join("tsupasswd").join("passwords.db")).expect("db missing");
+    assert!(!db.windows(4).any(|w| w == b"9876"), "secret field stored in plaintext");
+
+    // update で上書き・削除
+    let search = stdout_of(&mut run(&home, &["search", "bank.example.com"]));
+    let id = search
+        .split_whitespace()
+        .find_map(|tok| tok.strip_prefix("id="))
+        .expect("id not found in search output")
+        .to_string();
+    run(&home, &["update", &id, "--field", "recovery=aaaa bbbb", "--secret", "--unset-field", "account"])
+        .assert()
+        .success();
+    let get = stdout_of(&mut run(&home, &["get", "bank.example.com"]));
+    assert!(get.contains("field.pin=\"9876\""));
+    assert!(get.contains("field.recovery=\"aaaa bbbb\""));
+    assert!(!get.contains("field.account"));
+
+    // CSV / JSON エクスポートとインポートで往復できる
+    let csv_path = home.join("export.csv");
+    let json_path = home.join("export.json");
+    run(&home, &["export", csv_path.to_string_lossy().as_ref()]).assert().success();
+    run(&home, &["export", json_path.to_string_lossy().as_ref(), "--json"]).assert().success();
+    let csv = fs::read_to_string(&csv_path).expect("csv not written");
+    assert!(csv.lines().next().unwrap_or("").split(',').any(|h| h == "fields"));
+    assert!(csv.contains("recovery"));
+    let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(&json_path).expect("json not written"))
+        .expect("invalid json");
+    assert_eq!(json[0]["fields"].as_array().map(|a| a.len()), Some(2));
+    // 平文を含むため他のユーザから読めない（既存のファイルへの上書きも同じ）
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::write(&csv_path, "old").expect("failed to write");
+        fs::set_permissions(&csv_path, fs::Permissions::from_mode(0o644)).expect("failed to chmod");
+        run(&home, &["export", csv_path.to_string_lossy().as_ref()]).assert().success();
+        for path in [&csv_path, &json_path] {
+            assert_eq!(fs::metadata(path).unwrap().permissions().mode() & 0o777, 0o600, "{}", path.display());
+        }
+        assert!(fs::read_to_string(&csv_path).unwrap().contains("recovery"));
+    }
+
+    let other_dir = temp_home("tsupasswd_fields_test_");
+    let other = other_dir.path().to_path_buf();
+    authenticate(&other);
+    run(&other, &["import", json_path.to_string_lossy().as_ref(), "--json"]).assert().success();
+    let get = stdout_of(&mut run(&other, &["get", "bank.example.com"]));
+    assert!(get.contains("field.pin=\"9876\""));
+    assert!(get.contains("field.recovery=\"aaaa bbbb\""));
+}
+
+#[test]
+fn secret_flag_requires_preceding_field() {
+    let home_dir = temp_home("tsupasswd_fields_test_");
+    let home = home_dir.path().to_path_buf();
+    authenticate(&home);
+    run(&home, &["add", "x.example.com", "bob", "pw", "--secret"]).assert().failure();
+    run(&home, &["add", "x.example.com", "bob", "pw", "--field", "=novalue"]).assert().failure();
+}
@@ -0,0 +1,156 @@
+mod common;
+
+use assert_cmd::Command;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use common::{authenticate, run, temp_home};
+
+fn usernames_of(home: &Path, args: &[&str]) -> Vec<String> {
+    let out = run(home, args).assert().success().get_output().stdout.clone();
+    let v: serde_json::Value = serde_json::from_slice(&out).expect("invalid json");
+    v.as_array()
+        .expect("array expected")
+        .iter()
+        .map(|e| e["username"].as_str().unwrap_or_default().to_string())
+        .collect()
+}
+
+#[test]
+fn get_matches_by_host_and_registrable_domain() {
+    let home_dir = temp_home("tsupasswd_get_test_");
+    let home = home_dir.path().to_path_buf();
+    authenticate(&home);
+
+    run(&home, &["add", "https://github.com/login", "alice", "pw1"]).assert().success();
+    run(&home, &["add", "https://accounts.example.co.uk/signin", "bob", "pw2"]).assert().success();
+    run(&home, &["add", "mail.example.co.uk", "carol", "pw3"]).assert().success();
+    run(&home, &["add", "https://other.co.uk", "dave", "pw4"]).assert().success();
+
+    // 既定（host）はスキーム・www.・大文字小文字・パスの違いを無視
+    for q in ["github.com", "https://www.github.com/", "HTTPS://GitHub.com/settings"] {
+        assert_eq!(usernames_of(&home, &["get", q, "--json"]), ["alice"], "query: {}", q);
+    }
+    // exact は入力どおりの URL のみ
+    run(&home, &["get", "github.com", "--match", "exact"]).assert().failure();
+    assert_eq!(usernames_of(&home, &["get", "https://github.com/login", "--match", "exact", "--json"]), ["alice"]);
+
+    // domain は公開接尾辞（co.uk）を考慮した登録可能ドメインで照合
+    assert_eq!(usernames_of(&home, &["get", "mail.example.co.uk", "--json"]), ["carol"]);
+    let mut users = usernames_of(&home, &["get", "https://login.example.co.uk", "--match", "domain", "--json"]);
+    users.sort();
+    assert_eq!(users, ["bob", "carol"]);
+
+    run(&home, &["get", "github.com", "--match", "fuzzy"]).assert().failure();
+}
+
+#[test]
+fn get_copy_puts_password_on_clipboard_and_clears_it() {
+    let home_dir = temp_home("tsupasswd_get_test_");
+    let home = home_dir.path().to_path_buf();
+    let clip = home.join("clipboard");
+    let backend = format!("mock:{}", clip.display());
+    authenticate(&home);
+    run(&home, &["add", "https://github.com/login", "alice", "S3cret-pw"]).assert().success();
+
+    // パスワードは標準出力に出さずクリップボードへ
+    let out = run(&home, &["get", "github.com", "--copy", "--clear-after", "1"])
+        .env("TSUPASSWD_CLIPBOARD", &backend)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    assert!(!String::from_utf8_lossy(&out).contains("S3cret-pw"));
+    assert_eq!(std::fs::read_to_string(&clip).expect("clipboard not written"), "S3cret-pw");
+    wait_for(|| std::fs::read_to_string(&clip).unwrap_or_default().is_empty());
+
+    // 期限前に別の値へ置き換わっていれば消去しない
+    run(&home, &["get", "github.com", "--copy", "--clear-after", "1"])
+        .env("TSUPASSWD_CLIPBOARD", &backend)
+        .assert()
+        .success();
+    std::fs::write(&clip, "copied by user").expect("failed to overwrite clipboard");
+    std::thread::sleep(std::time::Duration::from_secs(3));
+    assert_eq!(std::fs::read_to_string(&clip).unwrap_or_default(), "copied by user");
+
+    // 消去用の子プロセスは値のハッシュを引数ではなく標準入力で受け取る
+    let fingerprint: String = Sha256::digest(b"copied by user").iter().map(|b| format!("{:02x}", b)).collect();
+    run(&home, &["__clear-clipboard", "0", &fingerprint]).env("TSUPASSWD_CLIPBOARD", &backend).assert().failure();
+    run(&home, &["__clear-clipboard", "0"]).env("TSUPASSWD_CLIPBOARD", &backend).write_stdin("0000\n").assert().success();
+    assert_eq!(std::fs::read_to_string(&clip).unwrap_or_default(), "copied by user");
+    run(&home, &["__clear-clipboard", "0"]).env("TSUPASSWD_CLIPBOARD", &backend).write_stdin(format!("{}\n", fingerprint)).assert().success();
+    assert_eq!(std::fs::read_to_string(&clip).unwrap_or_default(), "");
+
+    run(&home, &["get", "github.com", "--copy", "--json"]).env("TSUPASSWD_CLIPBOARD", &backend).assert().failure();
+
+    // 設定で JSON 出力を既定にしていても --copy はそのまま使える
+    std::fs::write(&clip, "").expect("failed to reset clipboard");
+    run(&home, &["config", "set", "output.format", "json"]).assert().success();
+    run(&home, &["get", "github.com", "--copy", "--clear-after", "0"]).env("TSUPASSWD_CLIPBOARD", &backend).assert().success();
+    assert_eq!(std::fs::read_to_string(&clip).unwrap_or_default(), "S3cret-pw");
+}
+
+fn wait_for(cond: impl Fn() -> bool) {
+    for _ in 0..50 {
+        if cond() { return; }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    panic!("condition not met within timeout");
+}
+
+#[test]
+fn get_field_and_format_output_for_scripts() {
+    let home_dir = temp_home("tsupasswd_get_test_");
+    let home = home_dir.path().to_path_buf();
+    let stdout = |args: &[&str]| {
+        let out = run(&home, args).assert().success().get_output().stdout.clone();
+        String::from_utf8(out).expect("utf8")
+    };
+    let password = "p\"a$s'w\\d";
+    authenticate(&home);
+    run(&home, &["add", "db.example.com", "alice", password, "--title", "T \"x\"", "--field", "api key=k1"]).assert().success();
+
+    // 既定の出力は引用符とバックスラッシュをエスケープ
+    assert_eq!(stdout(&["get", "db.example.com"]), "username=\"alice\" password=\"p\\\"a$s'w\\\\d\" title=\"T \\\"x\\\"\" field.api key=\"k1\"\n");
+
+    // --field はそのまま。-n で改行なし
+    assert_eq!(stdout(&["get", "db.example.com", "--field", "password"]), format!("{}\n", password));
+    assert_eq!(stdout(&["get", "db.example.com", "--field", "password", "-n"]), password);
+    assert_eq!(stdout(&["get", "db.example.com", "--field", "api key", "-n"]), "k1");
+    assert_eq!(stdout(&["get", "db.example.com", "--field", "note", "-n"]), "");
+    run(&home, &["get", "db.example.com", "--field", "missing"]).assert().code(6);
+
+    assert_eq!(stdout(&["get", "db.example.com", "--format", "{username}:{password}\\t{field.api key} {{x}}"]), format!("alice:{}\tk1 {{x}}\n", password));
+    run(&home, &["get", "db.example.com", "--format", "{usename}"]).assert().code(2);
+    run(&home, &["get", "db.example.com", "--format", "{username"]).assert().code(2);
+    run(&home, &["get", "db.example.com", "--format", "{field.missing}"]).assert().code(6);
+
+    // shell は sh に読ませて元の値に戻る
+    let script = format!("{}\nprintf '%s|%s|%s' \"$USERNAME\" \"$PASSWORD\" \"$FIELD_API_KEY\"", stdout(&["get", "db.example.com", "--format", "shell"]));
+    let out = Command::new("sh").arg("-c").arg(&script).output().expect("failed to run sh");
+    assert_eq!(String::from_utf8_lossy(&out.stdout), format!("alice|{}|k1", password));
+
+    let dotenv = stdout(&["get", "db.example.com", "--format", "dotenv"]);
+    assert!(dotenv.contains("PASSWORD=\"p\\\"a\\$s'w\\\\d\"\n"), "{}", dotenv);
+    assert!(dotenv.contains("NOTE=\"\"\n") && dotenv.contains("FIELD_API_KEY=\"k1\""), "{}", dotenv);
+    let env = stdout(&["get", "db.example.com", "--format", "env"]);
+    assert!(env.contains(&format!("PASSWORD={}\n", password)) && env.contains("URL=db.example.com\n"), "{}", env);
+    // 改行を含む値は env 形式では表せない
+    run(&home, &["update", "url:db.example.com", "--note", "line1\nline2"]).assert().success();
+    run(&home, &["get", "db.example.com", "--format", "env"]).assert().code(11);
+    assert!(stdout(&["get", "db.example.com", "--format", "dotenv"]).contains("NOTE=\"line1\\nline2\"\n"));
+
+    // 同じ変数名になる任意フィールドは片方を黙って上書きしない
+    run(&home, &["add", "cache.example.com", "bob", "pw", "--field", "a-b=1", "--field", "a_b=2"]).assert().success();
+    for format in ["shell", "env", "dotenv"] {
+        let out = run(&home, &["get", "cache.example.com", "--format", format]).assert().code(11).stdout("").get_output().stderr.clone();
+        let err = String::from_utf8(out).expect("utf8");
+        assert!(err.contains("a-b") && err.contains("a_b") && err.contains("FIELD_A_B"), "{}", err);
+    }
+    assert_eq!(stdout(&["get", "cache.example.com", "--field", "a_b", "-n"]), "2");
+
+    run(&home, &["get", "db.example.com", "--field", "password", "--format", "env"]).assert().code(2);
+    run(&home, &["get", "db.example.com", "--field", "password", "--copy"]).assert().code(2);
+    run(&home, &["get", "db.example.com", "--field", "password", "--json"]).assert().code(2);
+    run(&home, &["get", "db.example.com", "-n"]).assert().code(2);
+}
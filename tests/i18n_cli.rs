@@ -0,0 +1,99 @@
+mod common;
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::path::Path;
+use common::{SECRET, stdout_of, temp_home};
+
+fn run(home: &Path, lang: &str, args: &[&str]) -> Command {
+    let mut cmd = common::run(home, args);
+    cmd.env("LANG", lang);
+    cmd
+}
+
+fn has_japanese(text: &str) -> bool {
+    text.chars().any(|c| matches!(c, '\u{3040}'..='\u{30ff}' | '\u{4e00}'..='\u{9fff}' | '\u{ff01}'..='\u{ff5e}'))
+}
+
+#[test]
+fn english_help_has_no_untranslated_text() {
+    let home_dir = temp_home("tsupasswd_i18n_test_");
+    let home = home_dir.path().to_path_buf();
+    let commands: &[&[&str]] = &[
+        &[], &["add"], &["get"], &["otp"], &["run"], &["inject"], &["search"], &["list"], &["update"], &["delete"], &["trash"], &["trash", "list"],
+        &["trash", "restore"], &["trash", "purge"], &["tui"], &["export"], &["import"], &["auth"], &["logout"], &["status"],
+        &["log"], &["config"], &["config", "get"], &["config", "set"], &["config", "list"], &["completions"],
+    ];
+    for args in commands {
+        let mut argv = args.to_vec();
+        argv.push("--help");
+        let help = stdout_of(&mut run(&home, "en_US.UTF-8", &argv));
+        assert!(!has_japanese(&help), "untranslated help for {:?}:\n{}", args, help);
+    }
+    let help = stdout_of(&mut run(&home, "en_US.UTF-8", &["--help"]));
+    assert!(help.contains("Usage:") && help.contains("Commands:") && help.contains("<NAME|PATH>"), "{}", help);
+    // 日本語・C ロケール・未設定では従来どおり日本語
+    for lang in ["ja_JP.UTF-8", "C.UTF-8", ""] {
+        assert!(stdout_of(&mut run(&home, lang, &["get", "--help"])).contains("使い方:"));
+    }
+}
+
+#[test]
+fn messages_follow_locale_and_config() {
+    let home_dir = temp_home("tsupasswd_i18n_test_");
+    let home = home_dir.path().to_path_buf();
+
+    run(&home, "en_US.UTF-8", &["get", "example.com"]).assert().code(3).stderr(predicate::str::starts_with("Not authenticated."));
+    run(&home, "ja_JP.UTF-8", &["get", "example.com"]).assert().code(3).stderr(predicate::str::starts_with("未認証です"));
+    run(&home, "en_US.UTF-8", &["foo"]).assert().code(2).stderr(predicate::str::contains("expected an integer of 1 or more"));
+    // LC_ALL > LC_MESSAGES > LANG
+    run(&home, "en_US.UTF-8", &["get", "example.com"])
+        .env("LC_MESSAGES", "ja_JP.UTF-8")
+        .assert()
+        .stderr(predicate::str::starts_with("未認証です"));
+    run(&home, "ja_JP.UTF-8", &["get", "example.com"])
+        .env("LC_MESSAGES", "ja_JP.UTF-8")
+        .env("LC_ALL", "de_DE.UTF-8")
+        .assert()
+        .stderr(predicate::str::starts_with("Not authenticated."));
+
+    run(&home, "en_US.UTF-8", &["auth", SECRET]).assert().success().stdout(predicate::str::starts_with("Authenticated:"));
+    run(&home, "en_US.UTF-8", &["add", "a.example.com", "alice", "pw"]).assert().success().stderr(predicate::str::contains("warning:"));
+    run(&home, "en_US.UTF-8", &["get", "missing.example.com", "--json"])
+        .assert()
+        .code(6)
+        .stderr(predicate::str::contains("\"error\":\"not_found\"").and(predicate::str::contains("Not found: url=missing.example.com")));
+
+    // 設定の ui.language は環境変数より優先し、設定ファイルの警告も選んだ言語で出す
+    run(&home, "en_US.UTF-8", &["config", "set", "ui.language", "ja"]).assert().success();
+    run(&home, "en_US.UTF-8", &["get", "missing.example.com"]).assert().code(6).stderr(predicate::str::starts_with("見つかりませんでした"));
+    run(&home, "ja_JP.UTF-8", &["config", "set", "ui.language", "en"]).assert().success();
+    run(&home, "ja_JP.UTF-8", &["config", "set", "session.ttl", "0"]).assert().code(11).stderr(predicate::str::contains("must be an integer"));
+    run(&home, "ja_JP.UTF-8", &["trash", "list"]).assert().success().stdout(predicate::str::contains("The trash is empty"));
+    run(&home, "ja_JP.UTF-8", &["config", "set", "ui.language", "fr"]).assert().code(11);
+    run(&home, "ja_JP.UTF-8", &["config", "set", "ui.language", "auto"]).assert().success();
+    run(&home, "ja_JP.UTF-8", &["trash", "list"]).assert().success().stdout(predicate::str::contains("ゴミ箱は空です"));
+}
+
+#[test]
+fn library_errors_follow_the_language() {
+    let home_dir = temp_home("tsupasswd_i18n_test_");
+    let home = home_dir.path().to_path_buf();
+    run(&home, "en_US.UTF-8", &["auth", SECRET]).assert().success();
+
+    // Error の本文・条件や OTP の解析エラーもカタログで訳す
+    let cases: &[(&[&str], &str)] = &[
+        (&["delete", "12"], "id=12 not found (an id prefix needs at least 4 characters)"),
+        (&["delete", "url:"], "empty condition value: url:"),
+        (&["delete", "zzzzzzzz"], "id=zzzzzzzz not found"),
+        (&["add", "b.example.com", "bob", "pw", "--totp", "1!!"], "invalid base32 character: 1"),
+        (&["add", "b.example.com", "bob", "pw", "--totp", "otpauth://hotp/x?secret=JBSWY3DP"], "hotp URIs need a counter"),
+    ];
+    for (args, message) in cases {
+        let output = run(&home, "en_US.UTF-8", args).assert().failure().get_output().clone();
+        let stderr = String::from_utf8(output.stderr).expect("utf8");
+        assert!(stderr.contains(message), "{:?}: {}", args, stderr);
+        assert!(!has_japanese(&stderr), "{:?}: {}", args, stderr);
+    }
+    run(&home, "ja_JP.UTF-8", &["delete", "zzzzzzzz"]).assert().code(6).stderr(predicate::str::contains("id=zzzzzzzz が見つかりません"));
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use predicates::prelude::*;
+use common::{authenticate, run, temp_home};
+
+#[test]
+fn inject_replaces_references_and_writes_private_file() {
+    let home_dir = temp_home("tsupasswd_inject_test_");
+    let home = home_dir.path().to_path_buf();
+    authenticate(&home);
+    run(&home, &["add", "https://db.internal/admin", "dbuser", "p\"w$1", "--field", "api token=tok123"]).assert().success();
+
+    let template = home.join("config.tmpl");
+    std::fs::write(
+        &template,
+        "user: {{ tsupasswd \"db.internal\" \"username\" }}\n\
+         pass: '{{tsupasswd \"https://db.internal/\"}}'\n\
+         token: {{ tsupasswd \"db.internal\"   \"api token\" }}\n\
+         helm: {{ .Values.image }} {{ tsupasswdx }}\n",
+    )
+    .expect("failed to write template");
+    let expected = "user: dbuser\npass: 'p\"w$1'\ntoken: tok123\nhelm: {{ .Values.image }} {{ tsupasswdx }}\n";
+
+    // 出力先を省略すると標準出力
+    let template_arg = template.to_str().unwrap();
+    run(&home, &["inject", "-i", template_arg]).assert().success().stdout(expected);
+    run(&home, &["inject"]).write_stdin("x={{ tsupasswd \"db.internal\" }}").assert().success().stdout("x=p\"w$1");
+
+    let out = home.join("config.yaml");
+    std::fs::write(&out, "old").expect("failed to write");
+    run(&home, &["inject", "-i", template_arg, "-o", out.to_str().unwrap()]).assert().success();
+    assert_eq!(std::fs::read_to_string(&out).unwrap(), expected);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(std::fs::metadata(&out).unwrap().permissions().mode() & 0o777, 0o600);
+
+        // 残っていた一時ファイルがシンボリックリンクでも、リンク先には書かない
+        let victim = home.join("victim");
+        std::fs::write(&victim, "keep").expect("failed to write");
+        std::os::unix::fs::symlink(&victim, home.join(".config.yaml.tmp")).expect("failed to symlink");
+        run(&home, &["inject", "-i", template_arg, "-o", out.to_str().unwrap()]).assert().success();
+        assert_eq!(std::fs::read_to_string(&victim).unwrap(), "keep");
+        assert_eq!(std::fs::read_to_string(&out).unwrap(), expected);
+        assert_eq!(std::fs::symlink_metadata(&out).unwrap().permissions().mode() & 0o777, 0o600);
+    }
+
+    // 監査ログに値・URL は残らない
+    let log = run(&home, &["log", "--op", "inject"]).assert().success().get_output().stdout.clone();
+    let log = String::from_utf8(log).unwrap();
+    assert!(log.contains("op=\"inject\"") && !log.contains("tok123") && !log.contains("db.internal"), "{}", log);
+}
+
+#[test]
+fn inject_fails_on_unresolved_or_malformed_references() {
+    let home_dir = temp_home("tsupasswd_inject_test_");
+    let home = home_dir.path().to_path_buf();
+    let out = home.join("config.yaml");
+    let out_arg = out.to_str().unwrap().to_string();
+    run(&home, &["inject", "-o", &out_arg]).write_stdin("x").assert().code(3);
+    authenticate(&home);
+    run(&home, &["add", "db.internal", "dbuser", "pw"]).assert().success();
+
+    // 解決できない参照をすべて行番号つきで報告し、ファイルは作らない
+    run(&home, &["inject", "-o", &out_arg])
+        .write_stdin("a: {{ tsupasswd \"db.internal\" }}\nb: {{ tsupasswd \"missing.example\" }}\nc: {{ tsupasswd \"db.internal\" \"nope\" }}\n")
+        .assert()
+        .code(6)
+        .stderr(predicate::str::contains("2行目").and(predicate::str::contains("3行目: フィールドがありません: nope")));
+    assert!(!out.exists());
+    assert!(!home.join(".config.yaml.tmp").exists());
+    run(&home, &["inject", "--match", "exact"]).write_stdin("{{ tsupasswd \"https://db.internal\" }}").assert().code(6);
+
+    for bad in ["{{ tsupasswd db.internal }}", "{{ tsupasswd \"db.internal\"", "{{ tsupasswd }}", "{{ tsupasswd \"a\" \"b\" \"c\" }}", "{{ tsupasswd \"\" }}"] {
+        run(&home, &["inject"]).write_stdin(bad).assert().code(11).stderr(predicate::str::contains("1行目"));
+    }
+    run(&home, &["inject", "-i", home.join("missing.tmpl").to_str().unwrap()]).assert().code(9);
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use std::path::Path;
+use common::{authenticate, run, stdout_of, temp_home};
+
+fn id_of(home: &Path, keyword: &str) -> String {
+    stdout_of(&mut run(home, &["search", keyword]))
+        .split_whitespace()
+        .find_map(|tok| tok.strip_prefix("id="))
+        .expect("id not found in search output")
+        .to_string()
+}
+
+#[test]
+fn tags_and_folders_filter_search_and_list() {
+    let home_dir = temp_home("tsupasswd_organize_test_");
+    let home = home_dir.path().to_path_buf();
+    authenticate(&home);
+
+    run(&home, &["add", "console.aws.example", "admin", "pw1", "--folder", "work/aws/", "--tag", "prod,cloud"]).assert().success();
+    run(&home, &["add", "staging.aws.example", "dev", "pw2", "--folder", "work/aws/staging", "--tag", "staging"]).assert().success();
+    run(&home, &["add", "mail.example", "me", "pw3", "--folder", "personal", "--tag", "prod"]).assert().success();
+
+    // search --tag（キーワード省略可）
+    let out = stdout_of(&mut run(&home, &["search", "--tag", "prod"]));
+    assert_eq!(out.lines().count(), 2, "unexpected output: {}", out);
+    assert!(out.contains("console.aws.example"));
+    assert!(out.contains("mail.example"));
+    let out = stdout_of(&mut run(&home, &["search", "aws", "--tag", "prod"]));
+    assert_eq!(out.lines().count(), 1, "unexpected output: {}", out);
+    assert!(out.contains("tags=\"cloud,prod\""));
+
+    // list --folder は配下のフォルダも含み、パスワードは出力しない
+    let out = stdout_of(&mut run(&home, &["list", "--folder", "work/aws"]));
+    assert_eq!(out.lines().count(), 2, "unexpected output: {}", out);
+    assert!(!out.contains("pw1") && !out.contains("password="));
+    assert!(out.contains("folder=\"work/aws\""));
+    assert!(out.contains("folder=\"work/aws/staging\""));
+    let out = stdout_of(&mut run(&home, &["list", "--folder", "work/aws/staging"]));
+    assert_eq!(out.lines().count(), 1, "unexpected output: {}", out);
+
+    // update でフォルダ移動とタグの付け外し
+    let id = id_of(&home, "mail.example");
+    run(&home, &["update", &id, "--folder", "work", "--tag", "email", "--untag", "prod"]).assert().success();
+    let v: serde_json::Value = serde_json::from_str(&stdout_of(&mut run(&home, &["list", "--folder", "work", "--tag", "email", "--json"])))
+        .expect("invalid json");
+    assert_eq!(v.as_array().map(|a| a.len()), Some(1));
+    assert_eq!(v[0]["folder"], "work");
+    assert_eq!(v[0]["tags"], serde_json::json!(["email"]));
+    let out = stdout_of(&mut run(&home, &["search", "--tag", "prod"]));
+    assert_eq!(out.lines().count(), 1, "unexpected output: {}", out);
+}
+
+#[test]
+fn list_sorts_paginates_and_selects_columns() {
+    let home_dir = temp_home("tsupasswd_organize_test_");
+    let home = home_dir.path().to_path_buf();
+    authenticate(&home);
+
+    run(&home, &["add", "b.example", "bob", "secret-b", "--title", "Bravo"]).assert().success();
+    run(&home, &["add", "c.example", "carol", "secret-c", "--title", "alpha"]).assert().success();
+    run(&home, &["add", "a.example", "alice", "secret-a", "--title", "Charlie"]).assert().success();
+
+    // 既定は作成順（昇順）、パスワードは表示しない
+    let out = stdout_of(&mut run(&home, &["list"]));
+    let urls: Vec<&str> = out.lines().filter_map(|l| l.split_whitespace().find_map(|t| t.strip_prefix("url="))).collect();
+    assert_eq!(urls, ["\"b.example\"", "\"c.example\"", "\"a.example\""]);
+    assert!(!out.contains("secret-"));
+
+    let out = stdout_of(&mut run(&home, &["list", "--sort", "url", "--desc", "--columns", "url"]));
+    assert_eq!(out, "url=\"c.example\"\nurl=\"b.example\"\nurl=\"a.example\"\n");
+    let out = stdout_of(&mut run(&home, &["list", "--sort", "title", "--limit", "1", "--offset", "1", "--columns", "title,username"]));
+    assert_eq!(out, "title=\"Bravo\" username=\"bob\"\n");
+
+    // updated は最後に更新したものが末尾
+    let id = id_of(&home, "b.example");
+    run(&home, &["update", &id, "--note", "rotated"]).assert().success();
+    let out = stdout_of(&mut run(&home, &["list", "--sort", "updated", "--columns", "url"]));
+    assert_eq!(out.lines().last(), Some("url=\"b.example\""));
+
+    // password 列は --show-passwords が必要
+    run(&home, &["list", "--columns", "url,password"]).assert().failure();
+    let v: serde_json::Value = serde_json::from_str(&stdout_of(&mut run(&home, &["list", "--sort", "url", "--show-passwords", "--columns", "url,password", "--json"])))
+        .expect("invalid json");
+    assert_eq!(v[0], serde_json::json!({"url": "a.example", "password": "secret-a"}));
+
+    run(&home, &["list", "--sort", "name"]).assert().failure();
+    run(&home, &["list", "--limit", "x"]).assert().failure();
+    run(&home, &["list", "--columns", "url,secret"]).assert().failure();
+}
@@ -0,0 +1,63 @@
+use tsupasswd::otp::{Algorithm, Encoding, Kind, OtpParams};
+
+fn params(secret: &[u8], algorithm: Algorithm, digits: u32) -> OtpParams {
+    OtpParams {
+        kind: Kind::Totp,
+        encoding: Encoding::Decimal,
+        secret: secret.to_vec(),
+        algorithm,
+        digits,
+        period: 30,
+        counter: 0,
+        label: None,
+        issuer: None,
+    }
+}
+
+// RFC 6238 付録 B の試験ベクトル（8桁・30秒）
+#[test]
+fn totp_matches_rfc6238_vectors() {
+    let sha1 = params(b"12345678901234567890", Algorithm::Sha1, 8);
+    let sha256 = params(b"12345678901234567890123456789012", Algorithm::Sha256, 8);
+    let sha512 = params(b"1234567890123456789012345678901234567890123456789012345678901234", Algorithm::Sha512, 8);
+    let vectors: &[(u64, &str, &str, &str)] = &[
+        (59, "94287082", "46119246", "90693936"),
+        (1111111109, "07081804", "68084774", "25091201"),
+        (1111111111, "14050471", "67062674", "99943326"),
+        (1234567890, "89005924", "91819424", "93441116"),
+        (2000000000, "69279037", "90698825", "38618901"),
+        (20000000000, "65353130", "77737706", "47863826"),
+    ];
+    for &(time, c1, c256, c512) in vectors {
+        assert_eq!(sha1.totp_at(time).0, c1, "SHA1 t={}", time);
+        assert_eq!(sha256.totp_at(time).0, c256, "SHA256 t={}", time);
+        assert_eq!(sha512.totp_at(time).0, c512, "SHA512 t={}", time);
+    }
+    assert_eq!(sha1.totp_at(59).1, 1);
+}
+
+// RFC 4226 付録 D の試験ベクトル（6桁、カウンタ 0〜9）
+#[test]
+fn hotp_matches_rfc4226_vectors() {
+    let hotp = params(b"12345678901234567890", Algorithm::Sha1, 6);
+    let expected = ["755224", "287082", "359152", "969429", "338314", "254676", "287922", "162583", "399871", "520489"];
+    for (counter, code) in expected.iter().enumerate() {
+        assert_eq!(hotp.code_for_counter(counter as u64), *code, "counter={}", counter);
+    }
+}
+
+#[test]
+fn parse_accepts_uris_case_insensitively_and_rejects_non_ascii() {
+    // "12345678901234567890" の base32
+    let secret = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+    let p = OtpParams::parse(&format!("OTPAUTH://hotp/x?secret={}&counter=1&digits=6", secret)).unwrap();
+    assert_eq!((p.kind, p.counter), (Kind::Hotp, 1));
+    assert_eq!(p.code_for_counter(1), "287082");
+    assert_eq!(OtpParams::parse(&format!("Steam://{}", secret)).unwrap().encoding, Encoding::Steam);
+    assert_eq!(OtpParams::parse(secret).unwrap().secret, b"12345678901234567890");
+
+    // 接頭辞の長さの位置が多バイト文字の途中でもパニックしない
+    for bad in ["あいうえ", "otpauthあ://", "steamあ", "オtpauth://totp/x?secret=A", "あ"] {
+        assert!(OtpParams::parse(bad).is_err(), "{}", bad);
+    }
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use common::{authenticate, run, temp_home};
+
+#[test]
+fn totp_add_and_generate_code() {
+    let home_dir = temp_home("tsupasswd_otp_test_");
+    let home = home_dir.path().to_path_buf();
+    authenticate(&home);
+
+    // base32 のシードのみ（SHA1 / 6桁 / 30秒）
+    run(&home, &["add", "https://a.example.com", "alice", "pw", "--totp", "GEZD GNBV GY3T QOJQ GEZD GNBV GY3T QOJQ"])
+        .assert()
+        .success();
+    let out = run(&home, &["otp", "https://a.example.com"]).assert().success().get_output().stdout.clone();
+    let out = String::from_utf8_lossy(&out);
+    let code = out
+        .split_whitespace()
+        .find_map(|tok| tok.strip_prefix("code=\""))
+        .and_then(|tok| tok.strip_suffix('"'))
+        .expect("code not found in otp output");
+    assert_eq!(code.len(), 6);
+    assert!(code.chars().all(|c| c.is_ascii_digit()));
+
+    // otpauth URI（SHA256 / 8桁 / 60秒）を update で設定
+    let search = run(&home, &["search", "a.example.com"]).assert().success().get_output().stdout.clone();
+    let search = String::from_utf8_lossy(&search);
+    let id = search
+        .split_whitespace()
+        .find_map(|tok| tok.strip_prefix("id="))
+        .expect("id not found in search output")
+        .to_string();
+    run(&home, &[
+        "update", &id, "--totp",
+        "otpauth://totp/Example:alice?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&algorithm=SHA256&digits=8&period=60&issuer=Example",
+    ])
+    .assert()
+    .success();
+    let out = run(&home, &["otp", "https://a.example.com", "--json"]).assert().success().get_output().stdout.clone();
+    let v: serde_json::Value = serde_json::from_slice(&out).expect("invalid json");
+    let code = v[0]["code"].as_str().expect("code missing");
+    assert_eq!(code.len(), 8);
+    let remaining = v[0]["remaining_seconds"].as_u64().expect("remaining missing");
+    assert!((1..=60).contains(&remaining));
+}
+
+#[test]
+fn totp_rejects_invalid_secret_and_missing_seed() {
+    let home_dir = temp_home("tsupasswd_otp_test_");
+    let home = home_dir.path().to_path_buf();
+    authenticate(&home);
+
+    run(&home, &["add", "https://b.example.com", "bob", "pw", "--totp", "not-base32!"]).assert().failure();
+    run(&home, &["add", "https://b.example.com", "bob", "pw"]).assert().success();
+    // シード未登録のレコードは otp で失敗
+    run(&home, &["otp", "https://b.example.com"]).assert().failure();
+}
+
+#[test]
+fn hotp_counter_advances_on_each_call() {
+    let home_dir = temp_home("tsupasswd_otp_test_");
+    let home = home_dir.path().to_path_buf();
+    authenticate(&home);
+
+    // RFC 4226 付録Dのテストベクタ（secret = "12345678901234567890"）
+    run(&home, &[
+        "add", "vpn.example.com", "carol", "pw", "--totp",
+        "otpauth://hotp/VPN:carol?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&counter=0",
+    ])
+    .assert()
+    .success();
+    for (expected_code, expected_counter) in [("755224", "0"), ("287082", "1"), ("359152", "2")] {
+        let out = run(&home, &["otp", "vpn.example.com"]).assert().success().get_output().stdout.clone();
+        let out = String::from_utf8_lossy(&out);
+        assert!(out.contains(&format!("code=\"{}\"", expected_code)), "unexpected output: {}", out);
+        assert!(out.contains(&format!("counter={}", expected_counter)), "unexpected output: {}", out);
+    }
+
+    // エクスポートには現在のカウンタが反映される
+    let csv_path = home.join("otp.csv");
+    run(&home, &["export", csv_path.to_string_lossy().as_ref()]).assert().success();
+    let csv = std::fs::read_to_string(&csv_path).expect("csv not written");
+    assert!(csv.contains("counter=3"), "counter not exported: {}", csv);
+}
+
+#[test]
+fn steam_guard_code_uses_steam_alphabet() {
+    let home_dir = temp_home("tsupasswd_otp_test_");
+    let home = home_dir.path().to_path_buf();
+    authenticate(&home);
+
+    run(&home, &["add", "store.steampowered.com", "dave", "pw", "--totp", "steam://GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ"])
+        .assert()
+        .success();
+    let out = run(&home, &["otp", "store.steampowered.com", "--json"]).assert().success().get_output().stdout.clone();
+    let v: serde_json::Value = serde_json::from_slice(&out).expect("invalid json");
+    let code = v[0]["code"].as_str().expect("code missing");
+    assert_eq!(code.len(), 5);
+    assert!(code.chars().all(|c| "23456789BCDFGHJKMNPQRTVWXY".contains(c)), "unexpected code: {}", code);
+}
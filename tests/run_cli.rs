@@ -0,0 +1,80 @@
+mod common;
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use common::{authenticate, run, temp_home};
+
+fn output(cmd: &mut Command, code: i32) -> (String, String) {
+    let out = cmd.assert().code(code).get_output().clone();
+    (String::from_utf8(out.stdout).expect("utf8"), String::from_utf8(out.stderr).expect("utf8"))
+}
+
+#[test]
+fn run_passes_values_only_in_the_child_environment() {
+    let home_dir = temp_home("tsupasswd_run_test_");
+    let home = home_dir.path().to_path_buf();
+    run(&home, &["run", "-e", "X=url:db.internal", "--", "true"]).assert().code(3);
+    authenticate(&home);
+    run(&home, &["add", "https://db.internal/admin", "dbuser", "p\"w$1 x", "--field", "token=tok123"]).assert().success();
+
+    // 値は子プロセスの環境変数だけに入り、終了コードはそのまま返す
+    let script = "printf '%s|%s|%s' \"$DB_PASS\" \"$DB_USER\" \"$TOKEN\"; exit 3";
+    let (stdout, stderr) = output(
+        &mut run(&home, &["run", "--env", "DB_PASS=url:db.internal#password", "-e", "DB_USER=url:db.internal#username", "-e", "TOKEN=url:db.internal#token", "--", "sh", "-c", script]),
+        3,
+    );
+    assert_eq!(stdout, "p\"w$1 x|dbuser|tok123");
+    assert_eq!(stderr, "");
+    // 項目の省略は password
+    let (stdout, _) = output(&mut run(&home, &["run", "-e", "P=url:db.internal", "--", "sh", "-c", "printf '%s' \"$P\""]), 0);
+    assert_eq!(stdout, "p\"w$1 x");
+
+    // 保管庫の鍵と TSUPASSWD_* は子プロセスに引き継がない
+    let (stdout, _) = output(
+        &mut run(&home, &["run", "--redact", "-e", "P=url:db.internal", "--", "sh", "-c", "printf '%s|%s|%s' \"${AUTH_SECRET-unset}\" \"${TSUPASSWD_PBKDF2_ROUNDS-unset}\" \"$P\""]),
+        0,
+    );
+    assert_eq!(stdout, "unset|unset|<redacted>");
+
+    // 監査ログには環境変数名だけが残る
+    let (log, _) = output(&mut run(&home, &["log", "--op", "run"]), 0);
+    assert!(log.contains("detail=\"TOKEN\"") && !log.contains("tok123") && !log.contains("db.internal"), "{}", log);
+
+    // 解決できない参照ではコマンドを実行しない
+    let marker = home.join("ran");
+    let touch = format!("touch {}", marker.display());
+    run(&home, &["run", "-e", "X=url:missing.example", "--", "sh", "-c", &touch]).assert().code(6);
+    run(&home, &["run", "-e", "X=url:db.internal#missing", "--", "sh", "-c", &touch])
+        .assert()
+        .code(6)
+        .stderr(predicate::str::contains("missing"));
+    run(&home, &["run", "-e", "X=url:db.internal", "--match", "exact", "--", "sh", "-c", &touch]).assert().code(6);
+    assert!(!marker.exists());
+
+    run(&home, &["run", "-e", "X=db.internal", "--", "true"]).assert().code(2);
+    run(&home, &["run", "-e", "1X=url:db.internal", "--", "true"]).assert().code(2);
+    run(&home, &["run", "-e", "X=url:db.internal", "-e", "X=url:db.internal#token", "--", "true"]).assert().code(2);
+    run(&home, &["run", "-e", "X=url:db.internal", "true"]).assert().code(2);
+    run(&home, &["run", "-e", "X=url:db.internal", "--", "/nonexistent/command"]).assert().code(1);
+}
+
+#[test]
+fn run_redact_masks_values_in_child_output() {
+    let home_dir = temp_home("tsupasswd_run_test_");
+    let home = home_dir.path().to_path_buf();
+    authenticate(&home);
+    run(&home, &["add", "db.internal", "dbuser", "s3cret-pass", "--field", "token=s3cret-pass-long"]).assert().success();
+
+    // 長い値を優先し、書き込みの途中で分かれた値も置き換える
+    let script = "echo \"pass=$P token=$T\"; printf 's3cret-'; sleep 0.3; printf 'pass!\\n'; echo \"$P\" >&2; exit 5";
+    let (stdout, stderr) = output(
+        &mut run(&home, &["run", "--redact", "-e", "P=url:db.internal", "-e", "T=url:db.internal#token", "--", "sh", "-c", script]),
+        5,
+    );
+    assert_eq!(stdout, "pass=<redacted> token=<redacted>\n<redacted>!\n");
+    assert_eq!(stderr, "<redacted>\n");
+
+    // 値の途中で出力が終わった場合はそのまま出す
+    let (stdout, _) = output(&mut run(&home, &["run", "--redact", "-e", "P=url:db.internal", "--", "printf", "s3cret"]), 0);
+    assert_eq!(stdout, "s3cret");
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use std::path::Path;
+use common::{authenticate, run, stdout_of, temp_home};
+
+fn urls_of(out: &str) -> Vec<String> {
+    out.lines()
+        .filter_map(|l| l.split_whitespace().find_map(|t| t.strip_prefix("url=")))
+        .map(|u| u.trim_matches('"').to_string())
+        .collect()
+}
+
+fn seed(home: &Path) {
+    authenticate(home);
+    run(home, &["add", "https://github.com/login", "alice", "pw", "--title", "GitHub"]).assert().success();
+    run(home, &["add", "https://gitlab.example.com", "alice", "pw", "--title", "GitLab work"]).assert().success();
+    run(home, &["add", "https://mail.example.com", "alice", "pw", "--title", "Mail", "--note", "github recovery codes"]).assert().success();
+    run(home, &["add", "https://shop.example.com", "bob", "pw", "--title", "100%_off"]).assert().success();
+}
+
+#[test]
+fn fuzzy_search_tolerates_typos_and_ranks_title_over_note() {
+    let home_dir = temp_home("tsupasswd_search_test_");
+    let home = home_dir.path().to_path_buf();
+    seed(&home);
+
+    // 綴り間違い（入れ替え）でもヒットし、タイトル/ドメイン一致が備考一致より上位
+    let out = stdout_of(&mut run(&home, &["search", "gihtub", "--fuzzy"]));
+    assert_eq!(urls_of(&out).first().map(String::as_str), Some("https://github.com/login"), "unexpected output: {}", out);
+    let out = stdout_of(&mut run(&home, &["search", "github", "--fuzzy"]));
+    assert_eq!(urls_of(&out), ["https://github.com/login", "https://mail.example.com"]);
+    assert!(out.contains("score="));
+
+    // --limit で上位 N 件
+    let v: serde_json::Value = serde_json::from_str(&stdout_of(&mut run(&home, &["search", "git", "--fuzzy", "--limit", "1", "--json"])))
+        .expect("invalid json");
+    assert_eq!(v.as_array().map(|a| a.len()), Some(1));
+    assert!(v[0]["score"].as_f64().is_some());
+
+    run(&home, &["search", "zzzzzz", "--fuzzy"]).assert().failure();
+}
+
+#[test]
+fn plain_search_escapes_wildcards_and_ignores_ids() {
+    let home_dir = temp_home("tsupasswd_search_test_");
+    let home = home_dir.path().to_path_buf();
+    seed(&home);
+
+    // % と _ は文字として扱う
+    let out = stdout_of(&mut run(&home, &["search", "%_"]));
+    assert_eq!(urls_of(&out), ["https://shop.example.com"]);
+    let out = stdout_of(&mut run(&home, &["search", "%"]));
+    assert_eq!(urls_of(&out), ["https://shop.example.com"]);
+
+    // id の断片では一致しない
+    let id = stdout_of(&mut run(&home, &["search", "shop"]))
+        .split_whitespace()
+        .find_map(|tok| tok.strip_prefix("id="))
+        .expect("id not found in search output")
+        .to_string();
+    run(&home, &["search", &id]).assert().failure();
+}
+
+#[test]
+fn text_output_escapes_quotes_and_newlines() {
+    let home_dir = temp_home("tsupasswd_search_test_");
+    let home = home_dir.path().to_path_buf();
+    authenticate(&home);
+    run(&home, &["add", "quote.example.com", "a\"b", "p\"w\\1", "--title", "Quote \"x\"", "--note", "n1\nn2", "--folder", "f\"g"]).assert().success();
+
+    // 値の " \ 改行はエスケープし、1レコード1行にする
+    for args in [&["search", "quote"][..], &["search", "quote", "--fuzzy"][..]] {
+        let out = stdout_of(&mut run(&home, args));
+        assert_eq!(out.lines().count(), 1, "{}", out);
+        assert!(out.contains(r#"username="a\"b" password="p\"w\\1" title="Quote \"x\"" note="n1\nn2" folder="f\"g""#), "{}", out);
+    }
+    let out = stdout_of(&mut run(&home, &["list", "--columns", "username,title,note"]));
+    assert_eq!(out, "username=\"a\\\"b\" title=\"Quote \\\"x\\\"\" note=\"n1\\nn2\"\n");
+
+    run(&home, &["delete", "url:quote.example.com", "--yes"]).assert().success();
+    let out = stdout_of(&mut run(&home, &["trash", "list"]));
+    assert_eq!(out.lines().count(), 1, "{}", out);
+    assert!(out.contains(r#"username="a\"b" title="Quote \"x\"""#), "{}", out);
+}
+
+#[test]
+fn fts_search_supports_operators_prefix_and_japanese() {
+    let home_dir = temp_home("tsupasswd_search_test_");
+    let home = home_dir.path().to_path_buf();
+    seed(&home);
+    run(&home, &["add", "https://intra.example.jp", "carol", "pw", "--title", "社内ポータル", "--note", "経費精算システムのアカウント"]).assert().success();
+
+    let out = stdout_of(&mut run(&home, &["search", "--fts", "github"]));
+    assert_eq!(urls_of(&out).len(), 2, "unexpected output: {}", out);
+    let out = stdout_of(&mut run(&home, &["search", "--fts", "github NOT recovery"]));
+    assert_eq!(urls_of(&out), ["https://github.com/login"]);
+    let out = stdout_of(&mut run(&home, &["search", "--fts", "title:git*"]));
+    assert_eq!(urls_of(&out).len(), 2, "unexpected output: {}", out);
+    let out = stdout_of(&mut run(&home, &["search", "--fts", "精算システム"]));
+    assert_eq!(urls_of(&out), ["https://intra.example.jp"]);
+
+    // update / delete で索引も追従する
+    let id = stdout_of(&mut run(&home, &["search", "intra"]))
+        .split_whitespace()
+        .find_map(|tok| tok.strip_prefix("id="))
+        .expect("id not found in search output")
+        .to_string();
+    run(&home, &["update", &id, "--note", "勤怠管理"]).assert().success();
+    run(&home, &["search", "--fts", "精算システム"]).assert().failure();
+    run(&home, &["delete", &id, "--yes"]).assert().success();
+    run(&home, &["search", "--fts", "ポータル"]).assert().failure();
+
+    run(&home, &["search", "--fts", "\"unterminated"]).assert().failure();
+}
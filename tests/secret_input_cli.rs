@@ -0,0 +1,62 @@
+mod common;
+
+use predicates::prelude::*;
+use common::{SECRET, run, stdout_of, temp_home};
+
+#[test]
+fn auth_reads_secret_from_stdin_and_warns_on_argv() {
+    let home_dir = temp_home("tsupasswd_secret_test_");
+    let home = home_dir.path().to_path_buf();
+
+    run(&home, &["auth", "--password-stdin"])
+        .write_stdin(format!("{}\n", SECRET))
+        .assert()
+        .success()
+        .stderr(predicate::str::is_empty());
+    run(&home, &["auth", "--password-stdin"]).write_stdin("wrong\n").assert().failure();
+    run(&home, &["auth", SECRET])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("警告"));
+    // 入力元の重複はエラー
+    run(&home, &["auth", SECRET, "--password-stdin"]).write_stdin(format!("{}\n", SECRET)).assert().failure();
+}
+
+#[test]
+fn add_and_update_take_password_from_stdin_or_fd() {
+    let home_dir = temp_home("tsupasswd_secret_test_");
+    let home = home_dir.path().to_path_buf();
+    run(&home, &["auth", "--password-stdin"]).write_stdin(SECRET).assert().success();
+
+    run(&home, &["add", "a.example.com", "alice", "--password-stdin", "--title", "A"])
+        .write_stdin("from-stdin\n")
+        .assert()
+        .success()
+        .stderr(predicate::str::is_empty());
+    let out = stdout_of(&mut run(&home, &["get", "a.example.com"]));
+    assert!(out.contains("password=\"from-stdin\""), "unexpected output: {}", out);
+    assert!(out.contains("title=\"A\""), "unexpected output: {}", out);
+
+    // 位置引数のパスワードは保存されるが警告が出る
+    run(&home, &["add", "b.example.com", "bob", "on-argv"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("警告"));
+    // フラグは第3引数として扱わない（自動生成）
+    run(&home, &["add", "c.example.com", "carol", "--title", "C"]).assert().success();
+    let out = stdout_of(&mut run(&home, &["get", "c.example.com"]));
+    assert!(!out.contains("password=\"--title\""), "unexpected output: {}", out);
+
+    // --secret-fd 0 は標準入力と同じ
+    let id = stdout_of(&mut run(&home, &["search", "a.example.com"]))
+        .split_whitespace()
+        .find_map(|tok| tok.strip_prefix("id="))
+        .expect("id not found in search output")
+        .to_string();
+    run(&home, &["update", &id, "--secret-fd", "0"]).write_stdin("from-fd\n").assert().success();
+    let out = stdout_of(&mut run(&home, &["get", "a.example.com"]));
+    assert!(out.contains("password=\"from-fd\""), "unexpected output: {}", out);
+
+    run(&home, &["add", "d.example.com", "dave", "12", "--password-stdin"]).write_stdin("x").assert().failure();
+    run(&home, &["update", &id, "--password-stdin"]).write_stdin("").assert().failure();
+}
@@ -0,0 +1,81 @@
+mod common;
+
+use predicates::prelude::*;
+use std::path::Path;
+use common::{authenticate, run, temp_home};
+
+fn titles_of(home: &Path) -> Vec<(String, String)> {
+    let out = run(home, &["list", "--sort", "url", "--json"]).assert().success().get_output().stdout.clone();
+    let v: serde_json::Value = serde_json::from_slice(&out).expect("invalid json");
+    v.as_array()
+        .expect("array expected")
+        .iter()
+        .map(|e| (e["url"].as_str().unwrap().to_string(), e["title"].as_str().unwrap_or_default().to_string()))
+        .collect()
+}
+
+#[test]
+fn id_prefix_must_be_unique() {
+    let home_dir = temp_home("tsupasswd_selector_test_");
+    let home = home_dir.path().to_path_buf();
+    authenticate(&home);
+    run(&home, &["add", "https://a.example.com", "alice", "pw"]).assert().success();
+    run(&home, &["add", "https://b.example.com", "bob", "pw"]).assert().success();
+
+    // 先頭が共通する id を用意する
+    let db = rusqlite::Connection::open(home.join(".local").join("share").join("tsupasswd").join("passwords.db")).unwrap();
+    db.execute("UPDATE passwords SET id = '3f2a0000-0000-4000-8000-000000000001' WHERE username = 'alice'", []).unwrap();
+    db.execute("UPDATE passwords SET id = '3f2a1111-0000-4000-8000-000000000002' WHERE username = 'bob'", []).unwrap();
+    drop(db);
+
+    run(&home, &["update", "3f2a", "--title", "x"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("複数あります").and(predicate::str::contains("3f2a0000")).and(predicate::str::contains("3f2a1111")));
+    run(&home, &["update", "3f2", "--title", "x"]).assert().failure().stderr(predicate::str::contains("4文字以上"));
+
+    run(&home, &["update", "3f2a1", "--title", "bob's"]).assert().success().stdout(predicate::str::contains("3f2a1111-0000-4000-8000-000000000002"));
+    run(&home, &["delete", "3f2a0", "--yes"]).assert().success().stdout(predicate::str::contains("username=\"alice\""));
+    assert_eq!(titles_of(&home), vec![("https://b.example.com".to_string(), "bob's".to_string())]);
+}
+
+#[test]
+fn selectors_require_all_matching_for_bulk_changes() {
+    let home_dir = temp_home("tsupasswd_selector_test_");
+    let home = home_dir.path().to_path_buf();
+    authenticate(&home);
+    run(&home, &["add", "https://a.example.com", "alice", "pw"]).assert().success();
+    run(&home, &["add", "https://b.example.com", "alice", "pw"]).assert().success();
+    run(&home, &["add", "https://c.other.org", "alice", "pw"]).assert().success();
+
+    run(&home, &["update", "url:example.com", "user:alice", "--title", "work"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--all-matching").and(predicate::str::contains("b.example.com")));
+    run(&home, &["update", "url:example.com", "user:nobody", "--title", "work"]).assert().failure();
+    run(&home, &["update", "color:red", "--title", "work"]).assert().failure().stderr(predicate::str::contains("未知の条件"));
+
+    // --dry-run は対象を表示するだけ
+    run(&home, &["update", "url:example.com", "--title", "work", "--all-matching", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2 件"));
+    // 端末でない場合、複数件は --yes が必要
+    run(&home, &["update", "url:example.com", "--title", "work", "--all-matching"]).assert().failure();
+    run(&home, &["update", "url:example.com", "user:alice", "--title", "work", "--all-matching", "--yes"]).assert().success();
+    run(&home, &["update", "url:example.com", "--length", "20", "--all-matching", "--yes"]).assert().failure();
+
+    // 1件だけ一致する条件は --all-matching なしで使える
+    run(&home, &["update", "url:other.org", "--title", "personal"]).assert().success();
+    assert_eq!(
+        titles_of(&home),
+        vec![
+            ("https://a.example.com".to_string(), "work".to_string()),
+            ("https://b.example.com".to_string(), "work".to_string()),
+            ("https://c.other.org".to_string(), "personal".to_string()),
+        ]
+    );
+
+    run(&home, &["delete", "title:work", "--all-matching", "--yes"]).assert().success();
+    assert_eq!(titles_of(&home), vec![("https://c.other.org".to_string(), "personal".to_string())]);
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use predicates::prelude::*;
+use std::path::Path;
+use common::{authenticate, run, temp_home};
+
+fn json_of(home: &Path, args: &[&str]) -> Vec<serde_json::Value> {
+    let out = run(home, args).assert().success().get_output().stdout.clone();
+    serde_json::from_slice::<serde_json::Value>(&out).expect("invalid json").as_array().expect("array expected").clone()
+}
+
+fn add_and_get_id(home: &Path, url: &str, user: &str) -> String {
+    run(home, &["add", url, user, "pw"]).assert().success();
+    let found = json_of(home, &["search", url, "--json"]);
+    found[0]["id"].as_str().expect("id missing").to_string()
+}
+
+#[test]
+fn delete_moves_to_trash_and_restore_brings_it_back() {
+    let home_dir = temp_home("tsupasswd_trash_test_");
+    let home = home_dir.path().to_path_buf();
+    authenticate(&home);
+    let id = add_and_get_id(&home, "https://example.com", "alice");
+
+    // 端末でない場合は --yes が無ければ中止
+    run(&home, &["delete", &id]).assert().failure().stderr(predicate::str::contains("--yes"));
+    assert_eq!(json_of(&home, &["list", "--json"]).len(), 1);
+
+    run(&home, &["delete", &id, "--yes"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("url=\"https://example.com\" username=\"alice\""));
+    run(&home, &["get", "https://example.com"]).assert().failure();
+    assert!(json_of(&home, &["list", "--json"]).is_empty());
+    run(&home, &["search", "example"]).assert().failure();
+    run(&home, &["update", &id, "--title", "x"]).assert().failure();
+    run(&home, &["delete", &id, "--yes"]).assert().failure();
+
+    let trash = json_of(&home, &["trash", "list", "--json"]);
+    assert_eq!(trash.len(), 1);
+    assert_eq!(trash[0]["id"], id.as_str());
+    assert!(trash[0]["deleted_at"].is_string());
+
+    run(&home, &["trash", "restore", &id]).assert().success();
+    run(&home, &["get", "https://example.com"]).assert().success().stdout(predicate::str::contains("alice"));
+    assert!(json_of(&home, &["trash", "list", "--json"]).is_empty());
+    run(&home, &["trash", "restore", &id]).assert().failure();
+}
+
+#[test]
+fn purge_removes_only_entries_older_than_threshold() {
+    let home_dir = temp_home("tsupasswd_trash_test_");
+    let home = home_dir.path().to_path_buf();
+    authenticate(&home);
+    let a = add_and_get_id(&home, "https://a.example.com", "alice");
+    let b = add_and_get_id(&home, "https://b.example.com", "bob");
+    run(&home, &["delete", &a, "--yes"]).assert().success();
+    run(&home, &["delete", &b, "--yes"]).assert().success();
+
+    run(&home, &["trash", "purge", "--older-than", "30d"]).assert().success().stdout(predicate::str::contains("0 件"));
+    assert_eq!(json_of(&home, &["trash", "list", "--json"]).len(), 2);
+    run(&home, &["trash", "purge", "--older-than", "soon"]).assert().failure();
+    // 多バイトの単位・日時の範囲を超える期間はパニックせず引数の誤り
+    for bad in ["30日", "日", "9223372036854775807s", "100000000000000d", "9999999999d"] {
+        run(&home, &["trash", "purge", "--older-than", bad]).assert().code(2);
+    }
+
+    run(&home, &["trash", "purge"]).assert().success().stdout(predicate::str::contains("2 件"));
+    assert!(json_of(&home, &["trash", "list", "--json"]).is_empty());
+    run(&home, &["trash", "restore", &a]).assert().failure();
+}
@@ -0,0 +1,20 @@
+mod common;
+
+use predicates::prelude::*;
+use common::{authenticate, run, temp_home};
+
+#[test]
+fn tui_requires_session_and_terminal() {
+    let home_dir = temp_home("tsupasswd_tui_test_");
+    let home = home_dir.path();
+
+    // セッションなしでは起動しない
+    run(home, &["tui"]).assert().failure();
+
+    // 標準出力が端末でなければ画面を初期化せずエラー終了する
+    authenticate(home);
+    run(home, &["tui"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("TUI"));
+}
@@ -0,0 +1,129 @@
+mod common;
+
+use common::{SECRET, temp_home};
+use tsupasswd::site::MatchMode;
+use tsupasswd::{EntryChanges, Error, FileStore, NewEntry, Vault};
+
+#[test]
+fn add_get_search_update_delete_through_library() {
+    let mut vault = Vault::in_memory();
+    vault.unlock(SECRET).unwrap();
+
+    let saved = vault
+        .add(&NewEntry {
+            url: "https://example.com".to_string(),
+            username: "alice".to_string(),
+            password: "pw-1".to_string(),
+            title: Some("Example".to_string()),
+            folder: Some("/work//web/".to_string()),
+            tags: vec!["dev".to_string()],
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(saved.password, "pw-1");
+    assert_eq!(saved.folder.as_deref(), Some("work/web"));
+    assert_eq!(vault.get(&saved.id).unwrap().tags, vec!["dev".to_string()]);
+
+    let found = vault.search("example", None).unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].id, saved.id);
+    assert_eq!(vault.fetch_by_url("www.example.com", MatchMode::Host).unwrap().id, saved.id);
+    assert!(matches!(vault.fetch_by_url("www.example.com", MatchMode::Exact), Err(Error::NotFound(_))));
+
+    let changes = EntryChanges { password: Some("pw-2".to_string()), ..Default::default() };
+    let updated = vault.update(&saved.id[..8], &changes);
+    assert!(matches!(updated, Err(Error::NotFound(_))));
+    let targets = vault.resolve(&[saved.id[..8].to_string()], false).unwrap();
+    let updated = vault.update(&targets[0].id, &changes).unwrap();
+    assert_eq!(updated.password, "pw-2");
+    assert!(updated.updated_at.is_some());
+
+    vault.delete(&saved.id).unwrap();
+    assert!(matches!(vault.get(&saved.id), Err(Error::NotFound(_))));
+    assert_eq!(vault.trash().unwrap().len(), 1);
+    vault.restore(&saved.id).unwrap();
+    assert_eq!(vault.get(&saved.id).unwrap().password, "pw-2");
+}
+
+#[test]
+fn vault_must_be_unlocked_with_the_enrolled_secret() {
+    let dir = temp_home("tsupasswd_vault_api_test_");
+    let path = dir.path().join("passwords.db");
+    {
+        let mut vault = Vault::open(&path).unwrap();
+        let entry = NewEntry { url: "https://example.com".to_string(), username: "alice".to_string(), password: "pw".to_string(), ..Default::default() };
+        assert!(matches!(vault.add(&entry), Err(Error::Locked)));
+        vault.unlock(SECRET).unwrap();
+        vault.add(&entry).unwrap();
+    }
+
+    let mut vault = Vault::open(&path).unwrap();
+    assert!(!vault.is_unlocked());
+    assert!(matches!(vault.search("example", None), Err(Error::Locked)));
+    assert!(matches!(vault.unlock("wrong-secret"), Err(Error::WrongSecret)));
+    assert!(!vault.is_unlocked());
+    vault.unlock(SECRET).unwrap();
+    assert_eq!(vault.search("example", None).unwrap()[0].password, "pw");
+}
+
+#[test]
+fn site_columns_are_backfilled_and_matched() {
+    let dir = temp_home("tsupasswd_vault_api_test_");
+    let path = dir.path().join("passwords.db");
+    {
+        let mut vault = Vault::open(&path).unwrap();
+        vault.unlock(SECRET).unwrap();
+        let entry = NewEntry { url: "https://www.example.co.uk/login".to_string(), username: "alice".to_string(), password: "pw".to_string(), ..Default::default() };
+        vault.add(&entry).unwrap();
+    }
+    let sites = || {
+        let conn = rusqlite::Connection::open(&path).unwrap();
+        conn.query_row("SELECT host, domain FROM passwords", [], |row| Ok((row.get::<_, Option<String>>(0)?, row.get::<_, Option<String>>(1)?)))
+            .unwrap()
+    };
+    let expected = (Some("example.co.uk".to_string()), Some("example.co.uk".to_string()));
+    assert_eq!(sites(), expected);
+
+    // host / domain 列の追加前に保存したレコードは開くときに補完する
+    rusqlite::Connection::open(&path).unwrap().execute("UPDATE passwords SET host = NULL, domain = NULL", []).unwrap();
+    let mut vault = Vault::open(&path).unwrap();
+    assert_eq!(sites(), expected);
+    vault.unlock(SECRET).unwrap();
+    assert_eq!(vault.fetch_by_url("example.co.uk", MatchMode::Host).unwrap().username, "alice");
+    assert_eq!(vault.fetch_by_url("mail.example.co.uk", MatchMode::Domain).unwrap().username, "alice");
+    assert!(matches!(vault.fetch_by_url("mail.example.co.uk", MatchMode::Host), Err(Error::NotFound(_))));
+}
+
+#[test]
+fn file_store_round_trips_and_keeps_no_plaintext() {
+    let dir = temp_home("tsupasswd_vault_api_test_");
+    let path = dir.path().join("vault.json");
+    let id = {
+        let mut vault = Vault::with_store(Box::new(FileStore::open(&path, SECRET).unwrap()));
+        vault.unlock(SECRET).unwrap();
+        let saved = vault
+            .add(&NewEntry {
+                url: "https://example.com".to_string(),
+                username: "alice".to_string(),
+                password: "pw-file".to_string(),
+                tags: vec!["dev".to_string()],
+                ..Default::default()
+            })
+            .unwrap();
+        assert!(matches!(vault.search_fts("example", None), Err(Error::InvalidInput(_))));
+        saved.id
+    };
+
+    let raw = std::fs::read_to_string(&path).unwrap();
+    assert!(raw.contains("tsupasswd-vault"));
+    assert!(!raw.contains("example.com"));
+    assert!(!raw.contains("alice"));
+
+    assert!(matches!(FileStore::open(&path, "wrong-secret"), Err(Error::WrongSecret)));
+
+    let mut vault = Vault::with_store(Box::new(FileStore::open(&path, SECRET).unwrap()));
+    vault.unlock(SECRET).unwrap();
+    let entry = vault.get(&id).unwrap();
+    assert_eq!(entry.password, "pw-file");
+    assert_eq!(entry.tags, vec!["dev".to_string()]);
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use predicates::prelude::*;
+use std::fs;
+use std::process::Stdio;
+use assert_cmd::Command;
+use common::{SECRET, command, run, temp_home};
+
+fn search_count(cmd: &mut Command) -> usize {
+    let out = cmd.assert().success().get_output().stdout.clone();
+    serde_json::from_slice::<serde_json::Value>(&out).expect("invalid json").as_array().expect("array expected").len()
+}
+
+#[test]
+fn named_vaults_keep_separate_records_and_sessions() {
+    let home_dir = temp_home("tsupasswd_vault_location_test_");
+    let home = home_dir.path().to_path_buf();
+    run(&home, &["auth", SECRET]).assert().success();
+
+    // work はまだ認証していない
+    run(&home, &["--vault", "work", "add", "https://work.example.com", "bob", "pw"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("未認証"));
+    run(&home, &["--vault", "work", "auth", SECRET]).assert().success();
+    run(&home, &["--vault=work", "add", "https://work.example.com", "bob", "pw"]).assert().success();
+    run(&home, &["add", "https://home.example.com", "alice", "pw"]).assert().success();
+
+    assert_eq!(search_count(&mut run(&home, &["search", "example", "--json"])), 1);
+    assert_eq!(search_count(run(&home, &["search", "example", "--json"]).env("TSUPASSWD_VAULT", "work")), 1);
+    run(&home, &["search", "bob", "--vault", "work"]).assert().success().stdout(predicate::str::contains("work.example.com"));
+    run(&home, &["search", "bob"]).assert().failure();
+
+    let share = home.join(".local").join("share").join("tsupasswd");
+    let state = home.join(".local").join("state").join("tsupasswd");
+    assert!(share.join("passwords.db").exists());
+    assert!(share.join("vaults").join("work.db").exists());
+    assert!(state.join("vaults").join("work").join("session").exists());
+
+    // ログアウトはその保管庫のセッションだけを終える
+    run(&home, &["--vault", "work", "logout"]).assert().success();
+    run(&home, &["status"]).assert().success().stdout(predicate::str::contains("保管庫: default"));
+    run(&home, &["--vault", "work", "status"]).assert().failure();
+}
+
+#[test]
+fn xdg_dirs_and_legacy_dirs_are_respected() {
+    let home_dir = temp_home("tsupasswd_vault_location_test_");
+    let home = home_dir.path().to_path_buf();
+    let data = home.join("data");
+    let state = home.join("state");
+    let xdg = |args: &[&str]| {
+        let mut cmd = run(&home, args);
+        cmd.env("XDG_DATA_HOME", &data).env("XDG_STATE_HOME", &state);
+        cmd
+    };
+    xdg(&["auth", SECRET]).assert().success();
+    xdg(&["add", "https://example.com", "alice", "pw"]).assert().success();
+    assert!(data.join("tsupasswd").join("passwords.db").exists());
+    assert!(state.join("tsupasswd").join("session").exists());
+
+    // 旧バージョンのディレクトリがあればそちらを使い続ける
+    let legacy_home_dir = temp_home("tsupasswd_vault_location_test_");
+    let legacy_home = legacy_home_dir.path().to_path_buf();
+    fs::create_dir_all(legacy_home.join(".tsupasswd_db")).unwrap();
+    fs::create_dir_all(legacy_home.join(".password_cli")).unwrap();
+    run(&legacy_home, &["auth", SECRET]).assert().success();
+    run(&legacy_home, &["add", "https://example.com", "alice", "pw"]).assert().success();
+    assert!(legacy_home.join(".tsupasswd_db").join("passwords.db").exists());
+    assert!(legacy_home.join(".password_cli").join("session").exists());
+}
+
+#[test]
+fn vault_path_with_vault_extension_is_an_encrypted_file() {
+    let home_dir = temp_home("tsupasswd_vault_location_test_");
+    let home = home_dir.path().to_path_buf();
+    let path = home.join("team.vault");
+    let path_arg = path.to_str().unwrap();
+    run(&home, &["--vault", path_arg, "auth", SECRET]).assert().success();
+    run(&home, &["--vault", path_arg, "add", "https://example.com", "alice", "pw-file"]).assert().success();
+    run(&home, &["--vault", path_arg, "get", "https://example.com"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("pw-file"));
+
+    let raw = fs::read_to_string(&path).expect("vault file missing");
+    assert!(!raw.contains("example.com"));
+
+    // 同時に変更する別のプロセスの追加を上書きしない
+    let children: Vec<_> = (0..8)
+        .map(|i| {
+            let url = format!("https://c{}.example.com", i);
+            command(&home, &["--vault", path_arg, "add", &url, "alice", "pw"]).stdout(Stdio::null()).stderr(Stdio::null()).spawn().expect("failed to spawn")
+        })
+        .collect();
+    for mut child in children {
+        assert!(child.wait().expect("failed to wait").success());
+    }
+    let out = run(&home, &["--vault", path_arg, "list", "--json"]).assert().success().get_output().stdout.clone();
+    let v: serde_json::Value = serde_json::from_slice(&out).expect("invalid json");
+    assert_eq!(v.as_array().map(|a| a.len()), Some(9));
+    // 既定の保管庫には書かない
+    run(&home, &["get", "https://example.com"]).assert().failure();
+
+    run(&home, &["--vault"]).assert().failure().stderr(predicate::str::contains("--vault"));
+}